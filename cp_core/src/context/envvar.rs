@@ -64,11 +64,11 @@ impl EnvironmentVariableRegistry {
 
     pub fn from_args(args: &RunPipelineArgs) -> CpResult<EnvironmentVariableRegistry> {
         let mut ev = EnvironmentVariableRegistry::init(args.config.to_owned(), args.output.to_owned())?;
-        if args.datetime.is_some() {
-            let dt = parse_datetime_str(args.datetime.as_ref().unwrap())?;
+        if let Some(datetime) = &args.datetime {
+            let dt = parse_datetime_str(datetime)?;
             ev.set::<DateTime<FixedOffset>>(DEFAULT_KEYWORD_REF_DATETIME, &dt)?;
-        } else if args.date.is_some() {
-            let dt = parse_date_str(args.date.as_ref().unwrap())?;
+        } else if let Some(date) = &args.date {
+            let dt = parse_date_str(date)?;
             ev.set::<NaiveDate>(DEFAULT_KEYWORD_REF_DATE, &dt)?;
         }
         ev.set_str(DEFAULT_KEYWORD_PIPELINE, args.pipeline.clone())?;
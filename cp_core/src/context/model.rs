@@ -1,7 +1,7 @@
-use std::collections::HashMap;
+use std::collections::{HashMap, HashSet};
 
 use crate::{
-    model::common::{ModelConfig, ModelFields},
+    model::common::{ExtendedModelConfig, ModelConfig, ModelFields},
     util::error::{CpError, CpResult},
 };
 
@@ -58,6 +58,93 @@ impl ModelRegistry {
     }
 }
 
+/// A model node is either a flat field list, or extends other models when it has an `extends` list.
+enum ModelDeclaration {
+    Extended(ExtendedModelConfig),
+    Fields(ModelFields),
+}
+
+impl ModelDeclaration {
+    fn parse(value: serde_yaml_ng::Value) -> Result<Self, serde_yaml_ng::Error> {
+        let is_extended = value
+            .as_mapping()
+            .and_then(|x| x.get("extends"))
+            .is_some_and(|x| x.is_sequence());
+        if is_extended {
+            serde_yaml_ng::from_value::<ExtendedModelConfig>(value).map(ModelDeclaration::Extended)
+        } else {
+            serde_yaml_ng::from_value::<ModelFields>(value).map(ModelDeclaration::Fields)
+        }
+    }
+}
+
+impl ModelRegistry {
+    /// Resolves the fields of `name`, resolving the models it extends first.
+    /// `path` holds the models being resolved, to detect cycles, and `failed` the models that could
+    /// not be resolved, so that each error is reported once.
+    fn resolve(
+        &mut self,
+        name: &str,
+        declarations: &HashMap<String, ExtendedModelConfig>,
+        path: &mut Vec<String>,
+        failed: &mut HashSet<String>,
+    ) -> CpResult<ModelFields> {
+        if failed.contains(name) {
+            return Err(CpError::ConfigError(
+                "Model",
+                format!(
+                    "model `{}` extended by `{}` is invalid",
+                    name,
+                    path.last().map_or("?", |x| x.as_str())
+                ),
+            ));
+        }
+        if path.iter().any(|x| x == name) {
+            path.push(name.to_owned());
+            return Err(CpError::ConfigError(
+                "Model",
+                format!("cycle in model extends: {}", path.join(" -> ")),
+            ));
+        }
+        if let Some(model) = self.configs.get(name) {
+            return Ok(model.fields.clone());
+        }
+        let Some(extended) = declarations.get(name) else {
+            return Err(CpError::ConfigError(
+                "Model",
+                format!(
+                    "model `{}` extended by `{}` not found",
+                    name,
+                    path.last().map_or("?", |x| x.as_str())
+                ),
+            ));
+        };
+        path.push(name.to_owned());
+        let mut bases = vec![];
+        for base in &extended.extends {
+            match self.resolve(base, declarations, path, failed) {
+                Ok(fields) => bases.push(fields),
+                Err(e) => {
+                    failed.insert(name.to_owned());
+                    return Err(e);
+                }
+            }
+        }
+        path.pop();
+        let fields = extended.merge(name, &bases).inspect_err(|_| {
+            failed.insert(name.to_owned());
+        })?;
+        self.configs.insert(
+            name.to_owned(),
+            ModelConfig {
+                label: name.to_owned(),
+                fields: fields.clone(),
+            },
+        );
+        Ok(fields)
+    }
+}
+
 impl Configurable for ModelRegistry {
     fn get_node_name() -> &'static str {
         "model"
@@ -68,9 +155,10 @@ impl Configurable for ModelRegistry {
     ) -> CpResult<()> {
         let configs = config_pack.remove(ModelRegistry::get_node_name()).unwrap_or_default();
         let mut errors = vec![];
+        let mut declarations = HashMap::new();
         for (config_name, fields) in configs {
-            match serde_yaml_ng::from_value::<ModelFields>(fields) {
-                Ok(model_fields) => {
+            match ModelDeclaration::parse(fields) {
+                Ok(ModelDeclaration::Fields(model_fields)) => {
                     self.configs.insert(
                         config_name.clone(),
                         ModelConfig {
@@ -79,6 +167,10 @@ impl Configurable for ModelRegistry {
                         },
                     );
                 }
+                Ok(ModelDeclaration::Extended(extended)) => {
+                    let _ = self.configs.remove(&config_name);
+                    let _ = declarations.insert(config_name, extended);
+                }
                 Err(e) => {
                     errors.push(CpError::ConfigError(
                         "Model",
@@ -87,6 +179,18 @@ impl Configurable for ModelRegistry {
                 }
             };
         }
+        // extended models are resolved once every flat model is known
+        let mut names = declarations.keys().cloned().collect::<Vec<_>>();
+        names.sort();
+        let mut failed = HashSet::new();
+        for name in names {
+            if failed.contains(&name) {
+                continue;
+            }
+            if let Err(e) = self.resolve(&name, &declarations, &mut vec![], &mut failed) {
+                errors.push(e);
+            }
+        }
         if !errors.is_empty() {
             Err(CpError::ConfigError(
                 "ModelRegistry: model",
@@ -225,4 +329,138 @@ value:
             ])
         );
     }
+
+    #[test]
+    fn valid_model_registry_extends() {
+        let config = "
+model:
+    audited:
+        created_at: datetime_utc
+        updated_at: datetime_utc
+    keyed:
+        id:
+            dtype: int64
+            constraints: [primary]
+    player:
+        extends: [keyed, audited]
+        exclude: [updated_at]
+        fields:
+            name: str
+            id: uint64
+    goalie:
+        extends: [player]
+        fields:
+            $stat: double
+";
+        let mut config_pack = create_config_pack([config]);
+        let actual = ModelRegistry::from(&mut config_pack).unwrap();
+        let player = ModelFields::from([
+            (
+                StrKeyword::with_value("id".to_owned()),
+                ModelFieldKeyword::with_value(ModelFieldInfo::with_dtype(DType(DataType::UInt64))),
+            ),
+            (
+                StrKeyword::with_value("created_at".to_owned()),
+                ModelFieldKeyword::with_value(ModelFieldInfo::with_dtype(DType(DataType::Datetime(
+                    TimeUnit::Milliseconds,
+                    Some("UTC".into()),
+                )))),
+            ),
+            (
+                StrKeyword::with_value("name".to_owned()),
+                ModelFieldKeyword::with_value(ModelFieldInfo::with_dtype(DType(DataType::String))),
+            ),
+        ]);
+        assert_eq!(
+            actual.get_model("player").unwrap(),
+            ModelConfig {
+                label: "player".to_owned(),
+                fields: player.clone(),
+            }
+        );
+        let context = serde_yaml_ng::from_str::<serde_yaml_ng::Mapping>("stat: save_pct").unwrap();
+        let goalie = actual.get_substituted_model_fields("goalie", &context).unwrap();
+        assert_eq!(
+            goalie
+                .iter()
+                .map(|(x, _)| x.value().unwrap().as_str())
+                .collect::<Vec<_>>(),
+            vec!["id", "created_at", "name", "save_pct"]
+        );
+    }
+
+    #[test]
+    fn invalid_model_registry_extends() {
+        for config in [
+            "
+model:
+    a:
+        extends: [b]
+    b:
+        extends: [c]
+        fields:
+            x: str
+    c:
+        extends: [a]
+",
+            "
+model:
+    a:
+        extends: [missing]
+",
+            "
+model:
+    a:
+        extends: [b]
+        unknown: [x]
+    b:
+        x: str
+",
+        ] {
+            let mut config_pack = create_config_pack([config]);
+            assert!(ModelRegistry::from(&mut config_pack).is_err(), "{}", config);
+        }
+        let config = "
+model:
+    a:
+        extends: [a]
+";
+        let mut config_pack = create_config_pack([config]);
+        let err = ModelRegistry::from(&mut config_pack).unwrap_err().to_string();
+        assert!(err.contains("a -> a"), "{}", err);
+
+        let config = "
+model:
+    a:
+        extends: [b]
+    b:
+        extends: [c]
+    c:
+        extends: [a]
+    d:
+        extends: [a]
+";
+        let mut config_pack = create_config_pack([config]);
+        let err = ModelRegistry::from(&mut config_pack).unwrap_err().to_string();
+        assert_eq!(err.matches("cycle in model extends").count(), 1, "{}", err);
+        assert!(err.contains("a -> b -> c -> a"), "{}", err);
+        assert!(err.contains("model `a` extended by `d` is invalid"), "{}", err);
+
+        let config = "
+model:
+    a:
+        x: str
+        y: str
+    b:
+        extends: [a]
+        exclude: [y, z, w]
+";
+        let mut config_pack = create_config_pack([config]);
+        let err = ModelRegistry::from(&mut config_pack).unwrap_err().to_string();
+        assert!(
+            err.contains("model `b` excludes fields it does not inherit: z, w"),
+            "{}",
+            err
+        );
+    }
 }
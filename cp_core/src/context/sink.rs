@@ -120,7 +120,9 @@ sink:
                 label: "empty".to_owned(),
                 input: StrKeyword::with_symbol("input"),
                 max_threads: 1,
-                sinks: vec![]
+                sinks: vec![],
+                constraint_policy: None,
+                rejects: None,
             }
         );
         assert_eq!(
@@ -138,6 +140,8 @@ sink:
 "
                 )
                 .unwrap(),
+                constraint_policy: None,
+                rejects: None,
             }
         );
     }
@@ -127,7 +127,9 @@ source:
             SourceGroupConfig {
                 label: "empty".to_owned(),
                 max_threads: 1,
-                sources: vec![]
+                sources: vec![],
+                constraint_policy: None,
+                rejects: None,
             }
         );
         assert_eq!(
@@ -150,6 +152,8 @@ source:
 "
                 )
                 .unwrap(),
+                constraint_policy: None,
+                rejects: None,
             }
         );
     }
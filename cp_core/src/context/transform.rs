@@ -126,7 +126,11 @@ transform:
                 label: "empty_trf".to_owned(),
                 input: StrKeyword::with_value("TEST".to_owned()),
                 output: StrKeyword::with_symbol("output"),
-                steps: vec![]
+                steps: vec![],
+                model: None,
+                constraint_policy: None,
+                routes: None,
+                otherwise: None,
             }
         );
         assert_eq!(
@@ -143,7 +147,11 @@ select:
 "
                     )
                     .unwrap(),
-                ]
+                ],
+                model: None,
+                constraint_policy: None,
+                routes: None,
+                otherwise: None,
             }
         );
     }
@@ -1,4 +1,5 @@
 use polars::prelude::{Expr, Null, Schema, coalesce, col, lit};
+use regex::escape;
 use serde::{Deserialize, Deserializer, de};
 
 use crate::{
@@ -7,13 +8,19 @@ use crate::{
         keyword::{Keyword, ModelFieldKeyword, StrKeyword},
         model::ModelConstraint,
     },
-    util::error::CpResult,
+    util::error::{CpError, CpResult},
 };
 
 #[derive(Debug, Clone, PartialEq, Eq, Deserialize)]
 pub struct ModelFieldInfo {
     pub dtype: DType,
     pub constraints: Option<Vec<ModelConstraint>>,
+    // name of the upstream column, or a `.` separated path into struct columns, renamed to the field
+    pub source: Option<String>,
+    // literal replacing nulls and missing columns, cast with the rest of the column
+    pub default: Option<serde_yaml_ng::Value>,
+    // `nullable: false` is a shorthand for the `not_null` constraint
+    pub nullable: Option<bool>,
 }
 
 impl ModelFieldInfo {
@@ -21,14 +28,66 @@ impl ModelFieldInfo {
         Self {
             dtype,
             constraints: None,
+            source: None,
+            default: None,
+            nullable: None,
         }
     }
     pub fn new(dtype: DType, constraints: &[ModelConstraint]) -> Self {
         Self {
             dtype,
             constraints: Some(constraints.to_owned()),
+            source: None,
+            default: None,
+            nullable: None,
         }
     }
+    /// True if the field renames an upstream column or fills a default, so it cannot be read by name.
+    pub fn is_projected(&self) -> bool {
+        self.source.is_some() || self.default.is_some()
+    }
+    /// The declared constraints, with `not_null` added for `nullable: false`.
+    pub fn all_constraints(&self) -> Vec<ModelConstraint> {
+        let mut constraints = self.constraints.clone().unwrap_or_default();
+        if self.nullable == Some(false) && !constraints.contains(&ModelConstraint::NotNull) {
+            constraints.push(ModelConstraint::NotNull);
+        }
+        constraints
+    }
+    /// The upstream column, renamed to `name` and filled with the default, before casting.
+    fn source_column(&self, name: &str) -> CpResult<Expr> {
+        let source = match self.source.as_deref() {
+            Some(path) if path.contains('.') => {
+                let mut parts = path.split('.');
+                let root = parts.next().unwrap_or_default();
+                parts.fold(col(root), |expr, field| expr.struct_().field_by_name(field))
+            }
+            Some(source) => col(format!("^{}$", escape(source))),
+            None => col(format!("^{}$", name)),
+        };
+        let default = match &self.default {
+            None => lit(Null {}),
+            Some(serde_yaml_ng::Value::Bool(x)) => lit(*x),
+            Some(serde_yaml_ng::Value::String(x)) => lit(x.clone()),
+            Some(serde_yaml_ng::Value::Number(x)) => match (x.as_i64(), x.as_f64()) {
+                (Some(x), _) => lit(x),
+                (_, Some(x)) => lit(x),
+                _ => {
+                    return Err(CpError::ConfigError(
+                        "Model",
+                        format!("default of `{}` is out of range: {}", name, x),
+                    ));
+                }
+            },
+            Some(other) => {
+                return Err(CpError::ConfigError(
+                    "Model",
+                    format!("default of `{}` must be a bool, number or string: {:?}", name, other),
+                ));
+            }
+        };
+        Ok(coalesce(&[source, default]).alias(name))
+    }
 }
 
 #[derive(Debug, Clone, PartialEq, Eq)]
@@ -43,7 +102,14 @@ impl Default for ModelFields {
 }
 
 impl ModelFields {
-    pub fn iter(&self) -> std::slice::Iter<(StrKeyword, ModelFieldKeyword)> {
+    /// True if any field renames an upstream column or fills a default.
+    pub fn is_projected(&self) -> bool {
+        self.list
+            .iter()
+            .any(|(_, field)| field.value().is_some_and(|x| x.is_projected()))
+    }
+
+    pub fn iter(&self) -> std::slice::Iter<'_, (StrKeyword, ModelFieldKeyword)> {
         self.list.iter()
     }
 
@@ -115,6 +181,50 @@ pub struct ModelConfig {
     pub fields: ModelFields,
 }
 
+/// A model declared on top of other models: the fields of each model in `extends` in order, then `fields`
+/// overriding the inherited fields of the same name or adding new ones, without the `exclude`d fields.
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct ExtendedModelConfig {
+    pub extends: Vec<String>,
+    #[serde(default)]
+    pub exclude: Vec<StrKeyword>,
+    #[serde(default)]
+    pub fields: ModelFields,
+}
+
+impl ExtendedModelConfig {
+    /// Merges the fields of the already resolved `bases`, in the order of `extends`.
+    /// Fails if an `exclude`d field is not one of the inherited fields.
+    pub fn merge(&self, label: &str, bases: &[ModelFields]) -> CpResult<ModelFields> {
+        let mut list: Vec<(StrKeyword, ModelFieldKeyword)> = vec![];
+        for (name, field) in bases.iter().flat_map(|x| x.iter()).chain(self.fields.iter()) {
+            match list.iter_mut().find(|(x, _)| x == name) {
+                Some(existing) => existing.1 = field.clone(),
+                None => list.push((name.clone(), field.clone())),
+            }
+        }
+        let unknown = self
+            .exclude
+            .iter()
+            .filter(|x| !bases.iter().any(|base| base.iter().any(|(name, _)| name == *x)))
+            .map(|x| x.value().map(|x| x.as_str()).or(x.symbol()).unwrap_or("?"))
+            .collect::<Vec<_>>();
+        if !unknown.is_empty() {
+            return Err(CpError::ConfigError(
+                "Model",
+                format!(
+                    "model `{}` excludes fields it does not inherit: {}",
+                    label,
+                    unknown.join(", ")
+                ),
+            ));
+        }
+        list.retain(|(name, _)| !self.exclude.contains(name));
+        Ok(list.into())
+    }
+}
+
 impl ModelConfig {
     pub fn schema(&self) -> CpResult<Schema> {
         let mut schema = Schema::with_capacity(self.fields.len());
@@ -128,6 +238,7 @@ impl ModelConfig {
         }
         Ok(schema)
     }
+    /// The model fields renamed from their `source` and filled with their `default`, cast to the model dtypes.
     pub fn columns(&self) -> CpResult<Vec<Expr>> {
         let mut cols = vec![];
         for (field_name, field_detail) in self.fields.iter() {
@@ -136,10 +247,35 @@ impl ModelConfig {
                 .expect("value not present for model field_name")
                 .as_str();
             let detail = field_detail.value().expect("value not present for model field_detail");
-            cols.push(coalesce(&[col(format!("^{}$", name)), lit(Null {}).alias(name)]).cast(detail.dtype.0.clone()));
+            cols.push(detail.source_column(name)?.cast(detail.dtype.0.clone()));
+        }
+        Ok(cols)
+    }
+    /// Same as `columns`, without the cast.
+    pub fn source_columns(&self) -> CpResult<Vec<Expr>> {
+        let mut cols = vec![];
+        for (field_name, field_detail) in self.fields.iter() {
+            let name = field_name
+                .value()
+                .expect("value not present for model field_name")
+                .as_str();
+            let detail = field_detail.value().expect("value not present for model field_detail");
+            cols.push(detail.source_column(name)?);
         }
         Ok(cols)
     }
+    /// Names of the fields carrying the `primary` constraint, in model order.
+    pub fn primary_keys(&self) -> Vec<String> {
+        self.fields
+            .iter()
+            .filter(|(_, field_detail)| {
+                field_detail
+                    .value()
+                    .is_some_and(|x| x.all_constraints().contains(&ModelConstraint::Primary))
+            })
+            .filter_map(|(field_name, _)| field_name.value().cloned())
+            .collect()
+    }
     pub fn substitute_model_fields(&self, context: &serde_yaml_ng::Mapping) -> CpResult<ModelFields> {
         let mut fields = vec![];
         log::debug!("original model: {:?}", self);
@@ -156,7 +292,10 @@ impl ModelConfig {
 
 #[cfg(test)]
 mod tests {
-    use polars::prelude::{DataType, Field, TimeUnit};
+    use polars::{
+        df,
+        prelude::{DataFrame, DataType, Field, IntoLazy, IntoSeries, NamedFrom, Series, TimeUnit},
+    };
 
     use crate::{
         model::common::ModelFields,
@@ -173,7 +312,7 @@ mod tests {
         ($key:expr, $value:expr) => {
             (
                 StrKeyword::with_value(($key).to_owned()),
-                ModelFieldKeyword::with_value(ModelFieldInfo::with_dtype(DType(($value)))),
+                ModelFieldKeyword::with_value(ModelFieldInfo::with_dtype(DType($value))),
             )
         };
     }
@@ -182,7 +321,7 @@ mod tests {
         ($key:expr, $value:expr, $constraints:expr) => {
             (
                 StrKeyword::with_value(($key).to_owned()),
-                ModelFieldKeyword::with_value(ModelFieldInfo::new(DType(($value)), ($constraints))),
+                ModelFieldKeyword::with_value(ModelFieldInfo::new(DType($value), $constraints)),
             )
         };
     }
@@ -221,6 +360,26 @@ fields:
         assert_eq!(actual, expected);
     }
 
+    #[test]
+    fn valid_model_primary_keys() {
+        let model_config = "
+label: OUTPUT
+fields:
+    id:
+        dtype: int64
+        constraints: [primary]
+    name: str
+    season:
+        dtype: int32
+        constraints: [primary, not_null]
+    team:
+        dtype: str
+        constraints: [unique]
+";
+        let actual: ModelConfig = serde_yaml_ng::from_str(model_config).unwrap();
+        assert_eq!(actual.primary_keys(), vec!["id", "season"]);
+    }
+
     #[test]
     fn valid_model_full_config_no_constraints() {
         let model_config = "
@@ -272,4 +431,58 @@ fields:
         let actual: ModelConfig = serde_yaml_ng::from_str(model_config).unwrap();
         assert_eq!(actual, expected);
     }
+
+    #[test]
+    fn valid_model_source_default_nullable() {
+        let model_config = "
+label: OUTPUT
+fields:
+    player_id:
+        dtype: int64
+        source: playerId
+        nullable: false
+    city:
+        dtype: str
+        source: address.city
+        default: unknown
+    active:
+        dtype: bool
+        default: true
+";
+        let actual: ModelConfig = serde_yaml_ng::from_str(model_config).unwrap();
+        let player_id = actual.fields.iter().next().unwrap().1.value().unwrap();
+        assert_eq!(player_id.source.as_deref(), Some("playerId"));
+        assert_eq!(player_id.all_constraints(), vec![ModelConstraint::NotNull]);
+        assert!(actual.fields.is_projected());
+
+        let address = df!( "city" => [Some("Oslo"), None] )
+            .unwrap()
+            .into_struct("address".into());
+        let frame = DataFrame::new(vec![
+            Series::new("playerId".into(), ["1", "2"]).into(),
+            address.into_series().into(),
+        ])
+        .unwrap();
+        let projected = frame.lazy().select(actual.columns().unwrap()).collect().unwrap();
+        let expected = df!(
+            "player_id" => [1i64, 2],
+            "city" => ["Oslo", "unknown"],
+            "active" => [true, true],
+        )
+        .unwrap();
+        assert_eq!(projected, expected);
+    }
+
+    #[test]
+    fn invalid_model_default() {
+        let model_config = "
+label: OUTPUT
+fields:
+    a:
+        dtype: int64
+        default: [1]
+";
+        let actual: ModelConfig = serde_yaml_ng::from_str(model_config).unwrap();
+        assert!(actual.columns().is_err());
+    }
 }
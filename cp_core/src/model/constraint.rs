@@ -0,0 +1,426 @@
+use std::fmt;
+
+use polars::prelude::{BooleanChunked, ChunkFull, DataFrame, UniqueKeepStrategy};
+
+use crate::{
+    model::common::ModelFields,
+    parser::{
+        keyword::Keyword,
+        model::{ConstraintPolicy, ModelConstraint},
+    },
+    util::error::{CpError, CpResult},
+};
+
+/// Number of offending key values named per violation in a report.
+const MAX_SAMPLE_KEYS: usize = 5;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ViolationKind {
+    Null,
+    Duplicate,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ConstraintViolation {
+    pub constraint: ModelConstraint,
+    pub kind: ViolationKind,
+    pub fields: Vec<String>,
+    pub rows: usize,
+    /// Some of the duplicated key values, e.g. `id=2`
+    pub sample_keys: Vec<String>,
+}
+
+impl ConstraintViolation {
+    fn names(&self) -> (&'static str, &'static str) {
+        let constraint = match self.constraint {
+            ModelConstraint::Primary => "primary",
+            ModelConstraint::Unique => "unique",
+            ModelConstraint::NotNull => "not_null",
+        };
+        let kind = match self.kind {
+            ViolationKind::Null => "null",
+            ViolationKind::Duplicate => "duplicate",
+        };
+        (constraint, kind)
+    }
+
+    /// Why a single row violates the constraint, e.g. `primary(id): duplicate`
+    pub fn reason(&self) -> String {
+        let (constraint, kind) = self.names();
+        format!("{}({}): {}", constraint, self.fields.join(", "), kind)
+    }
+}
+
+/// Appends `reason` to the `; ` separated reasons of a row
+pub fn push_reason(error: &mut Option<String>, reason: &str) {
+    match error {
+        Some(x) => {
+            x.push_str("; ");
+            x.push_str(reason);
+        }
+        None => {
+            let _ = error.insert(reason.to_owned());
+        }
+    }
+}
+
+impl fmt::Display for ConstraintViolation {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let (constraint, kind) = self.names();
+        write!(
+            f,
+            "{}({}): {} {} rows",
+            constraint,
+            self.fields.join(", "),
+            self.rows,
+            kind
+        )?;
+        if !self.sample_keys.is_empty() {
+            write!(f, ", e.g. [{}]", self.sample_keys.join("; "))?;
+        }
+        Ok(())
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ConstraintReport {
+    pub frame: String,
+    pub violations: Vec<ConstraintViolation>,
+    /// Rows violating at least one constraint
+    pub violating_rows: usize,
+}
+
+impl fmt::Display for ConstraintReport {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let violations = self.violations.iter().map(|x| x.to_string()).collect::<Vec<_>>();
+        write!(
+            f,
+            "`{}` has {} rows violating model constraints: {}",
+            self.frame,
+            self.violating_rows,
+            violations.join("; ")
+        )
+    }
+}
+
+/// Checks frames against the constraints of a model, applying `policy` to the violating rows.
+/// Primary keys are checked together as one composite key, which may be neither null nor duplicated.
+/// Unique fields are checked individually, ignoring nulls. Fields missing from the frame count as null.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ModelConstraintCheck {
+    frame: String,
+    policy: ConstraintPolicy,
+    primary: Vec<String>,
+    unique: Vec<String>,
+    not_null: Vec<String>,
+}
+
+fn null_mask(frame: &DataFrame, field: &str) -> BooleanChunked {
+    match frame.column(field) {
+        Ok(column) => column.is_null(),
+        Err(_) => BooleanChunked::full(field.into(), true, frame.height()),
+    }
+}
+
+fn sample_keys(frame: &DataFrame, fields: &[String], mask: &BooleanChunked) -> CpResult<Vec<String>> {
+    let keys = frame
+        .select(fields.iter().map(|x| x.as_str()))?
+        .filter(mask)?
+        .unique_stable(None, UniqueKeepStrategy::First, Some((0, MAX_SAMPLE_KEYS)))?;
+    let mut samples = vec![];
+    for idx in 0..keys.height() {
+        let row = keys.get_row(idx)?;
+        let pairs = fields
+            .iter()
+            .zip(row.0.iter())
+            .map(|(field, value)| format!("{}={}", field, value))
+            .collect::<Vec<_>>();
+        samples.push(pairs.join(", "));
+    }
+    Ok(samples)
+}
+
+impl ModelConstraintCheck {
+    /// `None` if the model declares no constraints.
+    pub fn new(frame: &str, fields: &ModelFields, policy: ConstraintPolicy) -> Option<Self> {
+        let mut check = Self {
+            frame: frame.to_owned(),
+            policy,
+            primary: vec![],
+            unique: vec![],
+            not_null: vec![],
+        };
+        for (field_name, field_detail) in fields.iter() {
+            let (Some(name), Some(detail)) = (field_name.value(), field_detail.value()) else {
+                continue;
+            };
+            for constraint in &detail.all_constraints() {
+                match constraint {
+                    ModelConstraint::Primary => check.primary.push(name.to_owned()),
+                    ModelConstraint::Unique => check.unique.push(name.to_owned()),
+                    ModelConstraint::NotNull => check.not_null.push(name.to_owned()),
+                }
+            }
+        }
+        if check.primary.is_empty() && check.unique.is_empty() && check.not_null.is_empty() {
+            None
+        } else {
+            Some(check)
+        }
+    }
+
+    fn duplicate_mask(&self, frame: &DataFrame, fields: &[String]) -> CpResult<Option<BooleanChunked>> {
+        if fields.iter().any(|x| frame.column(x).is_err()) {
+            return Ok(None);
+        }
+        let mut mask = frame.select(fields.iter().map(|x| x.as_str()))?.is_duplicated()?;
+        for field in fields {
+            mask = &mask & &!null_mask(frame, field);
+        }
+        Ok(Some(mask))
+    }
+
+    /// Every violated constraint, with the mask of the rows violating it
+    fn violations(&self, frame: &DataFrame) -> CpResult<Vec<(ConstraintViolation, BooleanChunked)>> {
+        let mut violations = vec![];
+        let mut push = |constraint, kind, fields: &[String], mask: BooleanChunked, sample_keys| {
+            let rows = mask.sum().unwrap_or(0) as usize;
+            if rows > 0 {
+                violations.push((
+                    ConstraintViolation {
+                        constraint,
+                        kind,
+                        fields: fields.to_vec(),
+                        rows,
+                        sample_keys,
+                    },
+                    mask,
+                ));
+            }
+        };
+        if !self.primary.is_empty() {
+            let mut nulls = BooleanChunked::full("primary".into(), false, frame.height());
+            for field in &self.primary {
+                nulls = &nulls | &null_mask(frame, field);
+            }
+            push(
+                ModelConstraint::Primary,
+                ViolationKind::Null,
+                &self.primary,
+                nulls,
+                vec![],
+            );
+            if let Some(mask) = self.duplicate_mask(frame, &self.primary)? {
+                let samples = sample_keys(frame, &self.primary, &mask)?;
+                push(
+                    ModelConstraint::Primary,
+                    ViolationKind::Duplicate,
+                    &self.primary,
+                    mask,
+                    samples,
+                );
+            }
+        }
+        for field in &self.unique {
+            let fields = [field.clone()];
+            if let Some(mask) = self.duplicate_mask(frame, &fields)? {
+                let samples = sample_keys(frame, &fields, &mask)?;
+                push(
+                    ModelConstraint::Unique,
+                    ViolationKind::Duplicate,
+                    &fields,
+                    mask,
+                    samples,
+                );
+            }
+        }
+        for field in &self.not_null {
+            let fields = [field.clone()];
+            push(
+                ModelConstraint::NotNull,
+                ViolationKind::Null,
+                &fields,
+                null_mask(frame, field),
+                vec![],
+            );
+        }
+        Ok(violations)
+    }
+
+    fn report_with_mask(&self, frame: &DataFrame) -> CpResult<(ConstraintReport, BooleanChunked)> {
+        let mut all = BooleanChunked::full("violations".into(), false, frame.height());
+        let mut violations = vec![];
+        for (violation, mask) in self.violations(frame)? {
+            all = &all | &mask;
+            violations.push(violation);
+        }
+        let report = ConstraintReport {
+            frame: self.frame.clone(),
+            violations,
+            violating_rows: all.sum().unwrap_or(0) as usize,
+        };
+        Ok((report, all))
+    }
+
+    /// The reasons each row violates the model constraints, `None` for conforming rows
+    pub fn row_errors(&self, frame: &DataFrame) -> CpResult<Vec<Option<String>>> {
+        let mut errors: Vec<Option<String>> = vec![None; frame.height()];
+        for (violation, mask) in self.violations(frame)? {
+            let reason = violation.reason();
+            for (error, violated) in errors.iter_mut().zip(&mask) {
+                if violated.unwrap_or(false) {
+                    push_reason(error, &reason);
+                }
+            }
+        }
+        Ok(errors)
+    }
+
+    pub fn report(&self, frame: &DataFrame) -> CpResult<ConstraintReport> {
+        Ok(self.report_with_mask(frame)?.0)
+    }
+
+    /// Applies the policy: `error` fails with the report, `warn` logs it and passes the frame
+    /// through, and `drop_violating_rows` logs it and filters out every violating row.
+    pub fn enforce(&self, frame: DataFrame) -> CpResult<DataFrame> {
+        let (report, mask) = self.report_with_mask(&frame)?;
+        if report.violations.is_empty() {
+            return Ok(frame);
+        }
+        match self.policy {
+            ConstraintPolicy::Error => Err(CpError::TaskError("Model constraint violation", report.to_string())),
+            ConstraintPolicy::Warn => {
+                log::warn!("{}", report);
+                Ok(frame)
+            }
+            ConstraintPolicy::DropViolatingRows => {
+                log::warn!("Dropping violating rows: {}", report);
+                Ok(frame.filter(&!mask)?)
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use polars::{df, prelude::AnyValue};
+
+    use crate::{model::common::ModelFields, parser::model::ConstraintPolicy};
+
+    use super::{ModelConstraintCheck, ViolationKind};
+
+    fn fields() -> ModelFields {
+        serde_yaml_ng::from_str(
+            "
+id:
+    dtype: int64
+    constraints: [primary]
+season:
+    dtype: int32
+    constraints: [primary]
+team:
+    dtype: str
+    constraints: [unique]
+coach:
+    dtype: str
+    constraints: [not_null]
+",
+        )
+        .unwrap()
+    }
+
+    fn frame() -> polars::prelude::DataFrame {
+        df!(
+            "id" => [Some(1), Some(2), Some(2), None, Some(3)],
+            "season" => [1, 1, 1, 1, 2],
+            "team" => [Some("a"), Some("b"), Some("c"), None, Some("a")],
+            "coach" => [Some("x"), Some("y"), None, Some("z"), Some("w")],
+        )
+        .unwrap()
+    }
+
+    #[test]
+    fn valid_constraint_check_none_without_constraints() {
+        let fields: ModelFields = serde_yaml_ng::from_str("id: int64").unwrap();
+        assert!(ModelConstraintCheck::new("test", &fields, ConstraintPolicy::Error).is_none());
+    }
+
+    #[test]
+    fn valid_constraint_report() {
+        let check = ModelConstraintCheck::new("test", &fields(), ConstraintPolicy::Error).unwrap();
+        let report = check.report(&frame()).unwrap();
+        let summary = report
+            .violations
+            .iter()
+            .map(|x| (x.fields.join(","), x.kind, x.rows))
+            .collect::<Vec<_>>();
+        assert_eq!(
+            summary,
+            vec![
+                ("id,season".to_owned(), ViolationKind::Null, 1),
+                ("id,season".to_owned(), ViolationKind::Duplicate, 2),
+                ("team".to_owned(), ViolationKind::Duplicate, 2),
+                ("coach".to_owned(), ViolationKind::Null, 1),
+            ]
+        );
+        assert_eq!(report.violating_rows, 5);
+        assert_eq!(report.violations[1].sample_keys, vec!["id=2, season=1"]);
+        assert_eq!(report.violations[2].sample_keys, vec![r#"team="a""#]);
+        assert_eq!(
+            report.violations[1].to_string(),
+            "primary(id, season): 2 duplicate rows, e.g. [id=2, season=1]"
+        );
+    }
+
+    #[test]
+    fn valid_constraint_report_missing_column() {
+        let fields: ModelFields = serde_yaml_ng::from_str(
+            "
+id:
+    dtype: int64
+    constraints: [primary]
+",
+        )
+        .unwrap();
+        let check = ModelConstraintCheck::new("test", &fields, ConstraintPolicy::Error).unwrap();
+        let report = check.report(&df!("other" => [1, 2]).unwrap()).unwrap();
+        assert_eq!(report.violations.len(), 1);
+        assert_eq!(report.violations[0].kind, ViolationKind::Null);
+        assert_eq!(report.violating_rows, 2);
+    }
+
+    #[test]
+    fn valid_constraint_enforce_error() {
+        let check = ModelConstraintCheck::new("test", &fields(), ConstraintPolicy::Error).unwrap();
+        let err = check.enforce(frame()).unwrap_err().to_string();
+        assert!(err.contains("`test` has 5 rows violating model constraints"));
+        assert!(err.contains("id=2, season=1"));
+        let clean = frame().slice(0, 2);
+        assert_eq!(check.enforce(clean.clone()).unwrap(), clean);
+    }
+
+    #[test]
+    fn valid_constraint_enforce_warn() {
+        let check = ModelConstraintCheck::new("test", &fields(), ConstraintPolicy::Warn).unwrap();
+        assert_eq!(check.enforce(frame()).unwrap(), frame());
+    }
+
+    #[test]
+    fn valid_constraint_enforce_drop() {
+        let fields: ModelFields = serde_yaml_ng::from_str(
+            "
+id:
+    dtype: int64
+    constraints: [primary]
+coach:
+    dtype: str
+    constraints: [not_null]
+",
+        )
+        .unwrap();
+        let check = ModelConstraintCheck::new("test", &fields, ConstraintPolicy::DropViolatingRows).unwrap();
+        let actual = check.enforce(frame()).unwrap();
+        assert_eq!(actual.height(), 2);
+        assert_eq!(actual.column("id").unwrap().get(1).unwrap(), AnyValue::Int32(3));
+    }
+}
@@ -0,0 +1,200 @@
+use std::fmt;
+
+use polars::prelude::{DataType, Expr, Schema, all};
+
+use crate::{
+    model::common::ModelFields,
+    parser::{keyword::Keyword, model::SchemaDriftPolicy},
+    util::error::{CpError, CpResult},
+};
+
+/// Differences between the schema of a frame as read and its model.
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+pub struct SchemaDriftReport {
+    pub frame: String,
+    /// Columns not read by any model field
+    pub added: Vec<String>,
+    /// Columns read by a model field but absent from the frame
+    pub missing: Vec<String>,
+    /// Columns of another dtype than their model field, as `(column, model dtype, frame dtype)`
+    pub retyped: Vec<(String, DataType, DataType)>,
+}
+
+impl SchemaDriftReport {
+    pub fn is_empty(&self) -> bool {
+        self.added.is_empty() && self.missing.is_empty() && self.retyped.is_empty()
+    }
+}
+
+impl fmt::Display for SchemaDriftReport {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let mut parts = vec![];
+        if !self.added.is_empty() {
+            parts.push(format!("added [{}]", self.added.join(", ")));
+        }
+        if !self.missing.is_empty() {
+            parts.push(format!("missing [{}]", self.missing.join(", ")));
+        }
+        if !self.retyped.is_empty() {
+            let retyped = self
+                .retyped
+                .iter()
+                .map(|(name, expected, actual)| format!("{}: {} -> {}", name, expected, actual))
+                .collect::<Vec<_>>();
+            parts.push(format!("retyped [{}]", retyped.join(", ")));
+        }
+        write!(f, "`{}` drifted from its model: {}", self.frame, parts.join("; "))
+    }
+}
+
+/// Compares the schema of frames read without their model against the model.
+/// Fields read from a nested `source` path are only checked for the presence of their root column.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SchemaDrift {
+    frame: String,
+    policy: SchemaDriftPolicy,
+    // upstream column and, unless read from a nested path, the model dtype
+    columns: Vec<(String, Option<DataType>)>,
+    // model field names, which `evolve` does not pass through twice
+    fields: Vec<String>,
+}
+
+impl SchemaDrift {
+    pub fn new(frame: &str, fields: &ModelFields, policy: SchemaDriftPolicy) -> Self {
+        let mut drift = Self {
+            frame: frame.to_owned(),
+            policy,
+            columns: vec![],
+            fields: vec![],
+        };
+        for (field_name, field_detail) in fields.iter() {
+            let (Some(name), Some(detail)) = (field_name.value(), field_detail.value()) else {
+                continue;
+            };
+            let column = match detail.source.as_deref() {
+                Some(path) => match path.split_once('.') {
+                    Some((root, _)) => (root.to_owned(), None),
+                    None => (path.to_owned(), Some(detail.dtype.0.clone())),
+                },
+                None => (name.to_owned(), Some(detail.dtype.0.clone())),
+            };
+            if !drift.columns.iter().any(|(x, _)| x == &column.0) {
+                drift.columns.push(column);
+            }
+            drift.fields.push(name.to_owned());
+        }
+        drift
+    }
+
+    pub fn report(&self, schema: &Schema) -> SchemaDriftReport {
+        let mut report = SchemaDriftReport {
+            frame: self.frame.clone(),
+            ..Default::default()
+        };
+        for name in schema.iter_names() {
+            if !self.columns.iter().any(|(x, _)| x == name.as_str()) {
+                report.added.push(name.to_string());
+            }
+        }
+        for (name, dtype) in &self.columns {
+            match (schema.get(name), dtype) {
+                (None, _) => report.missing.push(name.clone()),
+                // all null columns carry no dtype to compare
+                (Some(DataType::Null), _) | (_, None) => {}
+                (Some(actual), Some(expected)) if actual != expected => {
+                    report.retyped.push((name.clone(), expected.clone(), actual.clone()))
+                }
+                _ => {}
+            }
+        }
+        report
+    }
+
+    /// Applies the policy to the drift of `schema`, returning the report if the schema drifted.
+    pub fn check(&self, schema: &Schema) -> CpResult<Option<SchemaDriftReport>> {
+        if self.policy == SchemaDriftPolicy::Ignore {
+            return Ok(None);
+        }
+        let report = self.report(schema);
+        if report.is_empty() {
+            return Ok(None);
+        }
+        match self.policy {
+            SchemaDriftPolicy::Error => return Err(CpError::TaskError("Schema drift", report.to_string())),
+            SchemaDriftPolicy::Warn => log::warn!("{}", report),
+            SchemaDriftPolicy::Evolve => log::warn!("Evolving: {}", report),
+            SchemaDriftPolicy::Ignore => {}
+        }
+        Ok(Some(report))
+    }
+
+    /// The columns outside of the model, kept next to the model fields when evolving.
+    pub fn passthrough(&self) -> Expr {
+        let known = self
+            .columns
+            .iter()
+            .map(|(x, _)| x.as_str())
+            .chain(self.fields.iter().map(|x| x.as_str()))
+            .collect::<Vec<_>>();
+        all().exclude(known)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use polars::{
+        df,
+        prelude::{DataType, IntoLazy},
+    };
+
+    use crate::{model::common::ModelFields, parser::model::SchemaDriftPolicy};
+
+    use super::SchemaDrift;
+
+    fn fields() -> ModelFields {
+        serde_yaml_ng::from_str(
+            "
+id: int64
+name:
+    dtype: str
+    source: Name
+city:
+    dtype: str
+    source: address.city
+score: double
+",
+        )
+        .unwrap()
+    }
+
+    #[test]
+    fn valid_schema_drift_report() {
+        let frame = df!( "id" => ["1"], "Name" => ["a"], "extra" => [true] ).unwrap();
+        let drift = SchemaDrift::new("players", &fields(), SchemaDriftPolicy::Warn);
+        let report = drift.check(&frame.schema()).unwrap().unwrap();
+        assert_eq!(report.added, vec!["extra"]);
+        assert_eq!(report.missing, vec!["address", "score"]);
+        assert_eq!(
+            report.retyped,
+            vec![("id".to_owned(), DataType::Int64, DataType::String)]
+        );
+        assert_eq!(
+            report.to_string(),
+            "`players` drifted from its model: added [extra]; missing [address, score]; retyped [id: i64 -> str]"
+        );
+    }
+
+    #[test]
+    fn valid_schema_drift_policies() {
+        let frame = df!( "id" => [1i64], "Name" => ["a"], "score" => [1.0], "extra" => [true] ).unwrap();
+        let model = fields();
+        let ignore = SchemaDrift::new("players", &model, SchemaDriftPolicy::Ignore);
+        assert!(ignore.check(&frame.schema()).unwrap().is_none());
+        let error = SchemaDrift::new("players", &model, SchemaDriftPolicy::Error);
+        assert!(error.check(&frame.schema()).is_err());
+        let evolve = SchemaDrift::new("players", &model, SchemaDriftPolicy::Evolve);
+        assert!(evolve.check(&frame.schema()).unwrap().is_some());
+        let passed = frame.lazy().select([evolve.passthrough()]).collect().unwrap();
+        assert_eq!(passed, df!( "extra" => [true] ).unwrap());
+    }
+}
@@ -0,0 +1,410 @@
+use std::{path::Path, sync::Arc};
+
+use polars::prelude::{DataFrame, DataType};
+
+use crate::{
+    context::{connection::ConnectionRegistry, model::ModelRegistry, source::SourceRegistry},
+    model::common::{ModelConfig, ModelFieldInfo, ModelFields},
+    parser::{
+        common::{pack_configs_from_files, read_configs},
+        dtype::DType,
+        keyword::{Keyword, ModelFieldKeyword, StrKeyword},
+        model::ModelConstraint,
+    },
+    pipeline::context::DefaultPipelineContext,
+    task::source::config::SourceGroupConfig,
+    util::{
+        args::InferModelArgs,
+        error::{CpError, CpResult},
+    },
+};
+
+/// Something to sample without a source config
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum BareSource {
+    /// csv, json, parquet or bson file, by extension
+    File(String),
+    /// http url returning json
+    Url(String),
+    /// table in a postgres, mysql or sqlite database
+    Table { db_url: String, table: String },
+}
+
+impl BareSource {
+    /// The source config reading into `output`, to be sampled with `SourceGroupConfig::sample`
+    pub fn source_config(&self, output: &str) -> CpResult<serde_yaml_ng::Value> {
+        let mut inner = serde_yaml_ng::Mapping::new();
+        inner.insert("output".into(), output.into());
+        let source_type = match self {
+            BareSource::File(filepath) => {
+                inner.insert("filepath".into(), filepath.as_str().into());
+                match Path::new(filepath).extension().and_then(|x| x.to_str()) {
+                    Some(ext @ ("csv" | "json" | "parquet" | "bson")) => ext,
+                    ext => {
+                        return Err(CpError::ConfigError(
+                            "Unknown file type",
+                            format!("expected a csv, json, parquet or bson file, found: {:?}", ext),
+                        ));
+                    }
+                }
+            }
+            BareSource::Url(url) => {
+                inner.insert("url".into(), url.as_str().into());
+                "http"
+            }
+            BareSource::Table { db_url, table } => {
+                let source_type = match db_url.split_once("://").map(|x| x.0) {
+                    Some("postgres" | "postgresql") => "postgres",
+                    Some("mysql") => "mysql",
+                    Some("sqlite") => "sqlite",
+                    None if db_url.ends_with(".db") || db_url.ends_with(".sqlite") => "sqlite",
+                    _ => {
+                        return Err(CpError::ConfigError(
+                            "Unknown database",
+                            format!("expected a postgres, mysql or sqlite url, found: {}", db_url),
+                        ));
+                    }
+                };
+                inner.insert("url".into(), db_url.as_str().into());
+                inner.insert("table".into(), table.as_str().into());
+                source_type
+            }
+        };
+        let mut config = serde_yaml_ng::Mapping::new();
+        config.insert(source_type.into(), inner.into());
+        Ok(config.into())
+    }
+}
+
+/// Infers a model from a sample frame. Columns without nulls are marked `not_null`, and columns
+/// that are also distinct in the sample are marked `unique`. Both are only candidates: a larger
+/// sample may disprove them.
+pub fn infer_model(label: &str, frame: &DataFrame) -> ModelConfig {
+    let mut fields = vec![];
+    for column in frame.get_columns() {
+        let dtype = column.dtype().clone();
+        let mut constraints = vec![];
+        if frame.height() > 0 && column.null_count() == 0 {
+            constraints.push(ModelConstraint::NotNull);
+            let hashable = !matches!(
+                dtype,
+                DataType::List(_) | DataType::Struct(_) | DataType::Array(_, _) | DataType::Null
+            );
+            if hashable && column.n_unique().is_ok_and(|x| x == frame.height()) {
+                constraints.push(ModelConstraint::Unique);
+            }
+        }
+        let info = if constraints.is_empty() {
+            ModelFieldInfo::with_dtype(DType(dtype))
+        } else {
+            ModelFieldInfo::new(DType(dtype), &constraints)
+        };
+        fields.push((
+            StrKeyword::with_value(column.name().to_string()),
+            ModelFieldKeyword::with_value(info),
+        ));
+    }
+    ModelConfig {
+        label: label.to_owned(),
+        fields: ModelFields::from(fields),
+    }
+}
+
+fn constraint_str(constraint: &ModelConstraint) -> &'static str {
+    match constraint {
+        ModelConstraint::Primary => "primary",
+        ModelConstraint::Unique => "unique",
+        ModelConstraint::NotNull => "not_null",
+    }
+}
+
+/// Renders models as a `model` config node, with fields written in the short `name: dtype` form
+/// unless they carry constraints.
+pub fn models_to_yaml(models: &[ModelConfig]) -> CpResult<String> {
+    let mut node = serde_yaml_ng::Mapping::new();
+    for model in models {
+        let mut fields = serde_yaml_ng::Mapping::new();
+        for (name, field) in model.fields.iter() {
+            let (Some(name), Some(field)) = (name.value(), field.value()) else {
+                return Err(CpError::ConfigError(
+                    "Model",
+                    format!("model `{}` has unsubstituted fields", model.label),
+                ));
+            };
+            let dtype = serde_yaml_ng::to_value(&field.dtype)?;
+            if serde_yaml_ng::from_value::<DType>(dtype.clone()).ok().as_ref() != Some(&field.dtype) {
+                return Err(CpError::ConfigError(
+                    "Model",
+                    format!(
+                        "dtype {:?} of `{}.{}` cannot be written as a model dtype",
+                        field.dtype.0, model.label, name
+                    ),
+                ));
+            }
+            let value = match field.constraints.as_ref().filter(|x| !x.is_empty()) {
+                Some(constraints) => {
+                    let mut detail = serde_yaml_ng::Mapping::new();
+                    detail.insert("dtype".into(), dtype);
+                    detail.insert(
+                        "constraints".into(),
+                        constraints.iter().map(constraint_str).collect::<Vec<_>>().into(),
+                    );
+                    detail.into()
+                }
+                None => dtype,
+            };
+            fields.insert(name.as_str().into(), value);
+        }
+        node.insert(model.label.as_str().into(), fields.into());
+    }
+    let mut root = serde_yaml_ng::Mapping::new();
+    root.insert("model".into(), node.into());
+    Ok(serde_yaml_ng::to_string(&root)?)
+}
+
+/// Samples the source described by the cli args and returns the inferred models as yaml
+pub fn infer_models_from_args(args: &InferModelArgs) -> CpResult<String> {
+    let mut pack = match &args.config {
+        Some(dir) => pack_configs_from_files(&read_configs(dir, &["yml", "yaml"])?)?,
+        None => Default::default(),
+    };
+    let context = match &args.args {
+        Some(x) => serde_yaml_ng::from_str::<serde_yaml_ng::Mapping>(x)?,
+        None => serde_yaml_ng::Mapping::new(),
+    };
+    let ctx = Arc::new(
+        DefaultPipelineContext::new()
+            .with_model_registry(ModelRegistry::from(&mut pack)?)
+            .with_connection_registry(ConnectionRegistry::from(&mut pack)?),
+    );
+    let bare = match (&args.file, &args.url, &args.table, &args.db_url) {
+        (Some(file), None, None, None) => Some(BareSource::File(file.clone())),
+        (None, Some(url), None, None) => Some(BareSource::Url(url.clone())),
+        (None, None, Some(table), Some(db_url)) => Some(BareSource::Table {
+            db_url: db_url.clone(),
+            table: table.clone(),
+        }),
+        (None, None, None, None) => None,
+        _ => {
+            return Err(CpError::ComponentError(
+                "Input CLI Args",
+                "pass exactly one of --file, --url or --table with --db-url".to_owned(),
+            ));
+        }
+    };
+    let group = match (bare, &args.source) {
+        (Some(bare), None) => {
+            let output = args.label.clone().unwrap_or_else(|| "sample".to_owned());
+            SourceGroupConfig {
+                label: "infer_model".to_owned(),
+                max_threads: 1,
+                sources: vec![bare.source_config(&output)?],
+                constraint_policy: None,
+                rejects: None,
+            }
+        }
+        (None, Some(label)) => SourceRegistry::from(&mut pack)?
+            .get_source_config(label)
+            .ok_or_else(|| CpError::ComponentError("Input CLI Args", format!("source `{}` not found", label)))?,
+        _ => {
+            return Err(CpError::ComponentError(
+                "Input CLI Args",
+                "pass either --source or a bare --file, --url or --table".to_owned(),
+            ));
+        }
+    };
+    let samples = group
+        .sample(ctx, &context, args.rows)
+        .map_err(|errors| CpError::ConfigError("Sampling failed", format!("{:?}", errors)))?;
+    let models = samples
+        .iter()
+        .map(|(name, frame)| {
+            let label = match (&args.label, samples.len()) {
+                (Some(label), 1) => label.as_str(),
+                _ => name.as_str(),
+            };
+            infer_model(label, frame)
+        })
+        .collect::<Vec<_>>();
+    models_to_yaml(&models)
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Arc;
+
+    use polars::{df, prelude::DataType};
+
+    use crate::{
+        context::model::ModelRegistry,
+        model::common::{ModelFieldInfo, ModelFields},
+        parser::{
+            dtype::DType,
+            keyword::{Keyword, ModelFieldKeyword, StrKeyword},
+            model::ModelConstraint,
+        },
+        pipeline::context::DefaultPipelineContext,
+        task::source::config::SourceGroupConfig,
+        util::{
+            common::{create_config_pack, rng_str},
+            tmp::TempFile,
+        },
+    };
+
+    use super::{BareSource, infer_model, models_to_yaml};
+
+    fn field(name: &str, dtype: DataType, constraints: &[ModelConstraint]) -> (StrKeyword, ModelFieldKeyword) {
+        let info = if constraints.is_empty() {
+            ModelFieldInfo::with_dtype(DType(dtype))
+        } else {
+            ModelFieldInfo::new(DType(dtype), constraints)
+        };
+        (
+            StrKeyword::with_value(name.to_owned()),
+            ModelFieldKeyword::with_value(info),
+        )
+    }
+
+    #[test]
+    fn valid_infer_model() {
+        let frame = df!(
+            "id" => [1, 2, 3],
+            "team" => ["a", "a", "b"],
+            "score" => [Some(1.5), None, Some(2.0)],
+        )
+        .unwrap();
+        let model = infer_model("player", &frame);
+        assert_eq!(
+            model.fields,
+            ModelFields::from([
+                field(
+                    "id",
+                    DataType::Int32,
+                    &[ModelConstraint::NotNull, ModelConstraint::Unique]
+                ),
+                field("team", DataType::String, &[ModelConstraint::NotNull]),
+                field("score", DataType::Float64, &[]),
+            ])
+        );
+    }
+
+    #[test]
+    fn valid_models_to_yaml_round_trip() {
+        let frame = df!( "id" => [1i64, 2], "name" => [Some("a"), None] ).unwrap();
+        let yaml = models_to_yaml(&[infer_model("player", &frame)]).unwrap();
+        assert_eq!(
+            yaml,
+            "model:\n  player:\n    id:\n      dtype: int64\n      constraints:\n      - not_null\n      - unique\n    name: str\n"
+        );
+        let mut config_pack = create_config_pack([yaml.as_str()]);
+        let registry = ModelRegistry::from(&mut config_pack).unwrap();
+        assert_eq!(registry.get_model("player").unwrap(), infer_model("player", &frame));
+    }
+
+    #[test]
+    fn invalid_models_to_yaml_dtype() {
+        let frame = df!( "id" => [1i64, 2] ).unwrap();
+        let mut model = infer_model("player", &frame);
+        model.fields = ModelFields::from([field("pos", DataType::Array(Box::new(DataType::Float64), 2), &[])]);
+        let err = models_to_yaml(&[model]).unwrap_err().to_string();
+        assert!(err.contains("player.pos"), "{}", err);
+    }
+
+    #[test]
+    fn valid_bare_source_config() {
+        let actual = [
+            BareSource::File("data/players.csv".to_owned()),
+            BareSource::Url("https://example.com/players".to_owned()),
+            BareSource::Table {
+                db_url: "postgres://user:pw@localhost/db".to_owned(),
+                table: "players".to_owned(),
+            },
+        ]
+        .map(|x| x.source_config("PLAYERS").unwrap());
+        let expected = [
+            "csv: {output: PLAYERS, filepath: data/players.csv}",
+            "http: {output: PLAYERS, url: 'https://example.com/players'}",
+            "postgres: {output: PLAYERS, url: 'postgres://user:pw@localhost/db', table: players}",
+        ]
+        .map(|x| serde_yaml_ng::from_str::<serde_yaml_ng::Value>(x).unwrap());
+        assert_eq!(actual, expected);
+        assert!(BareSource::File("players.xlsx".to_owned()).source_config("P").is_err());
+        assert!(
+            BareSource::Table {
+                db_url: "oracle://localhost".to_owned(),
+                table: "t".to_owned()
+            }
+            .source_config("P")
+            .is_err()
+        );
+    }
+
+    #[test]
+    fn valid_source_group_sample_infer() {
+        let tmp = TempFile::default();
+        tmp.write_json(&mut df!( "id" => [1i64, 2, 3], "name" => ["a", "b", "c"] ).unwrap())
+            .unwrap();
+        let config = format!(
+            "
+label: load
+max_threads: 1
+sources:
+    - json:
+        filepath: {}
+        output: PLAYERS
+        model: not_registered
+",
+            &tmp.filepath
+        );
+        let group = serde_yaml_ng::from_str::<SourceGroupConfig>(&config).unwrap();
+        let ctx = Arc::new(DefaultPipelineContext::new());
+        let samples = group.sample(ctx, &serde_yaml_ng::Mapping::new(), 2).unwrap();
+        assert_eq!(samples.len(), 1);
+        assert_eq!(samples[0].0, "PLAYERS");
+        let model = infer_model(&samples[0].0, &samples[0].1);
+        assert_eq!(
+            model.fields,
+            ModelFields::from([
+                field(
+                    "id",
+                    DataType::Int64,
+                    &[ModelConstraint::NotNull, ModelConstraint::Unique]
+                ),
+                field(
+                    "name",
+                    DataType::String,
+                    &[ModelConstraint::NotNull, ModelConstraint::Unique]
+                ),
+            ])
+        );
+        assert_eq!(samples[0].1.height(), 2);
+    }
+
+    #[test]
+    fn valid_source_group_sample_sqlite() {
+        let path = std::env::temp_dir().join(format!("{}.db", rng_str(8)));
+        let conn = rusqlite::Connection::open(&path).unwrap();
+        conn.execute_batch(
+            "
+CREATE TABLE players (id INTEGER, name TEXT);
+INSERT INTO players VALUES (1, 'a'), (2, 'b'), (3, 'c');
+",
+        )
+        .unwrap();
+        let bare = BareSource::Table {
+            db_url: path.to_str().unwrap().to_owned(),
+            table: "players".to_owned(),
+        };
+        let group = SourceGroupConfig {
+            label: "load".to_owned(),
+            max_threads: 1,
+            sources: vec![bare.source_config("PLAYERS").unwrap()],
+            constraint_policy: None,
+            rejects: None,
+        };
+        let ctx = Arc::new(DefaultPipelineContext::new());
+        let samples = group.sample(ctx, &serde_yaml_ng::Mapping::new(), 2).unwrap();
+        std::fs::remove_file(&path).unwrap();
+        assert_eq!(samples[0].1.height(), 2);
+    }
+}
@@ -1 +1,5 @@
 pub mod common;
+pub mod constraint;
+pub mod drift;
+pub mod infer;
+pub mod reject;
@@ -0,0 +1,158 @@
+use polars::prelude::{BooleanChunked, DataFrame, DataType, Expr, IntoLazy, NamedFrom, NewChunkedArray, Series};
+
+use crate::{
+    model::{
+        common::{ModelConfig, ModelFields},
+        constraint::{ModelConstraintCheck, push_reason},
+    },
+    parser::model::ConstraintPolicy,
+    util::error::CpResult,
+};
+
+/// Reasons a row was rejected, separated by `; `
+pub const ERROR_COLUMN: &str = "_error";
+/// Label of the stage that rejected the row
+pub const STAGE_COLUMN: &str = "_stage";
+
+/// Splits frames into the rows conforming to a model, cast to the model dtypes, and the rejected rows.
+/// A row is rejected if a non-null value fails to cast, or if the cast row violates a model constraint.
+/// Rejected rows keep their original values, with the reasons in `_error` and the stage label in `_stage`.
+#[derive(Debug, Clone)]
+pub struct ModelRejects {
+    stage: String,
+    fields: Vec<(String, DataType)>,
+    source_columns: Vec<Expr>,
+    columns: Vec<Expr>,
+    check: Option<ModelConstraintCheck>,
+}
+
+impl ModelRejects {
+    pub fn new(stage: &str, fields: &ModelFields) -> CpResult<Self> {
+        let model = ModelConfig {
+            label: stage.to_owned(),
+            fields: fields.clone(),
+        };
+        let fields = model
+            .schema()?
+            .iter()
+            .map(|(name, dtype)| (name.to_string(), dtype.clone()))
+            .collect();
+        Ok(Self {
+            stage: stage.to_owned(),
+            fields,
+            source_columns: model.source_columns()?,
+            columns: model.columns()?,
+            check: ModelConstraintCheck::new(stage, &model.fields, ConstraintPolicy::DropViolatingRows),
+        })
+    }
+
+    /// Keeps the columns selected by `expr` next to the model fields in the conforming rows,
+    /// e.g. the columns added by an evolving schema.
+    pub fn with_passthrough(mut self, expr: Expr) -> Self {
+        self.columns.push(expr);
+        self
+    }
+
+    /// The conforming rows and, if any row was rejected, the rejected rows
+    pub fn split(&self, frame: DataFrame) -> CpResult<(DataFrame, Option<DataFrame>)> {
+        // renamed and defaulted, but not cast yet
+        let original = frame.clone().lazy().select(self.source_columns.clone()).collect()?;
+        let cast = frame.clone().lazy().select(self.columns.clone()).collect()?;
+        let mut errors: Vec<Option<String>> = vec![None; frame.height()];
+        for (name, dtype) in &self.fields {
+            let failed = &original.column(name)?.is_not_null() & &cast.column(name)?.is_null();
+            if failed.any() {
+                let reason = format!("cast({}): not a valid {}", name, dtype);
+                for (error, row_failed) in errors.iter_mut().zip(&failed) {
+                    if row_failed.unwrap_or(false) {
+                        push_reason(error, &reason);
+                    }
+                }
+            }
+        }
+        if let Some(check) = &self.check {
+            // failed casts would otherwise be reported again as nulls
+            let castable = BooleanChunked::from_iter_values("castable".into(), errors.iter().map(|x| x.is_none()));
+            let mut row_errors = check.row_errors(&cast.filter(&castable)?)?.into_iter();
+            for error in errors.iter_mut().filter(|x| x.is_none()) {
+                *error = row_errors.next().flatten();
+            }
+        }
+        let rejected = BooleanChunked::from_iter_values("rejected".into(), errors.iter().map(|x| x.is_some()));
+        if !rejected.any() {
+            return Ok((cast, None));
+        }
+        let accepted = cast.filter(&!&rejected)?;
+        let mut rejects = frame.filter(&rejected)?;
+        let height = rejects.height();
+        rejects.with_column(Series::new(
+            ERROR_COLUMN.into(),
+            errors.into_iter().flatten().collect::<Vec<String>>(),
+        ))?;
+        rejects.with_column(Series::new(STAGE_COLUMN.into(), vec![self.stage.as_str(); height]))?;
+        Ok((accepted, Some(rejects)))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use polars::df;
+
+    use crate::model::common::ModelFields;
+
+    use super::ModelRejects;
+
+    fn fields() -> ModelFields {
+        serde_yaml_ng::from_str(
+            "
+id:
+    dtype: int64
+    constraints: [primary]
+score: double
+name:
+    dtype: str
+    constraints: [not_null]
+",
+        )
+        .unwrap()
+    }
+
+    #[test]
+    fn valid_model_rejects_split() {
+        let frame = df!(
+            "id" => ["1", "2", "bad", "4", "3"],
+            "score" => [Some("1.5"), Some("nan?"), Some("2"), None, Some("4")],
+            "name" => [Some("a"), Some("b"), Some("c"), None, Some("e")],
+        )
+        .unwrap();
+        let (accepted, rejected) = ModelRejects::new("load", &fields()).unwrap().split(frame).unwrap();
+        assert_eq!(
+            accepted,
+            df!( "id" => [1i64, 3], "score" => [Some(1.5), Some(4.0)], "name" => ["a", "e"] ).unwrap()
+        );
+        let expected = df!(
+            "id" => ["2", "bad", "4"],
+            "score" => [Some("nan?"), Some("2"), None],
+            "name" => [Some("b"), Some("c"), None],
+            "_error" => [
+                "cast(score): not a valid f64",
+                "cast(id): not a valid i64",
+                "not_null(name): null",
+            ],
+            "_stage" => ["load", "load", "load"],
+        )
+        .unwrap();
+        assert_eq!(rejected.unwrap(), expected);
+    }
+
+    #[test]
+    fn valid_model_rejects_none() {
+        let frame = df!( "id" => [1, 2], "name" => ["a", "b"] ).unwrap();
+        let (accepted, rejected) = ModelRejects::new("load", &fields()).unwrap().split(frame).unwrap();
+        assert!(rejected.is_none());
+        assert_eq!(
+            accepted,
+            df!( "id" => [1i64, 2], "score" => [None::<f64>, None], "name" => ["a", "b"] ).unwrap()
+        );
+    }
+}
@@ -1,4 +1,4 @@
-use polars::prelude::{DataType, Expr, concat_str, format_str};
+use polars::prelude::{DataType, Expr, Null, Operator, binary_expr, coalesce, concat_str, format_str, lit, when};
 use serde::Deserialize;
 
 use crate::{
@@ -6,7 +6,11 @@ use crate::{
     util::error::{CpError, CpResult},
 };
 
-use super::keyword::{PolarsExprKeyword, StrKeyword};
+use super::{
+    dtype::DType,
+    filter::FilterStmt,
+    keyword::{PolarsExprKeyword, StrKeyword},
+};
 
 pub trait ExprAction {
     fn expr(&self) -> CpResult<polars::prelude::Expr>;
@@ -104,3 +108,253 @@ impl ExprAction for FormatAction {
         Ok(format_str(self.template.value().unwrap(), args)?)
     }
 }
+
+fn materialized_expr(kw: &PolarsExprKeyword, action: &'static str) -> CpResult<Expr> {
+    match kw.value() {
+        Some(x) => Ok(x.clone()),
+        None => Err(CpError::TaskError(
+            action,
+            format!("symbol not replaced: {:?}", kw.symbol()),
+        )),
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize)]
+pub struct CastAction {
+    pub col: PolarsExprKeyword,
+    pub dtype: DType,
+}
+
+impl ExprAction for CastAction {
+    fn validate(&self) -> CpResult<()> {
+        let _ = materialized_expr(&self.col, "CastAction.col not materialized")?;
+        Ok(())
+    }
+    fn expr(&self) -> CpResult<Expr> {
+        let col = materialized_expr(&self.col, "CastAction.col not materialized")?;
+        Ok(col.cast(self.dtype.0.clone()))
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ArithmeticOp {
+    Add,
+    Sub,
+    Mul,
+    Div,
+    Mod,
+}
+
+/// Folds the operands left to right, e.g. `sub: [a, b, c]` is `(a - b) - c`. `div` is true division.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ArithmeticAction {
+    pub op: ArithmeticOp,
+    pub operands: Vec<PolarsExprKeyword>,
+}
+
+impl ExprAction for ArithmeticAction {
+    fn validate(&self) -> CpResult<()> {
+        if self.operands.len() < 2 {
+            return Err(CpError::TaskError(
+                "`ArithmeticAction` TransformAction parsing failed",
+                format!("{:?} needs at least 2 operands", self.op),
+            ));
+        }
+        for x in &self.operands {
+            let _ = materialized_expr(x, "ArithmeticAction.operands[?] not materialized")?;
+        }
+        Ok(())
+    }
+    fn expr(&self) -> CpResult<Expr> {
+        self.validate()?;
+        let mut operands = self.operands.iter().map(|x| x.value().unwrap().clone());
+        let first = operands.next().unwrap();
+        Ok(operands.fold(first, |acc, x| match self.op {
+            ArithmeticOp::Add => acc + x,
+            ArithmeticOp::Sub => acc - x,
+            ArithmeticOp::Mul => acc * x,
+            ArithmeticOp::Div => binary_expr(acc, Operator::TrueDivide, x),
+            ArithmeticOp::Mod => acc % x,
+        }))
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize)]
+#[serde(transparent)]
+pub struct CoalesceAction {
+    pub columns: Vec<PolarsExprKeyword>,
+}
+
+impl ExprAction for CoalesceAction {
+    fn validate(&self) -> CpResult<()> {
+        if self.columns.is_empty() {
+            return Err(CpError::TaskError(
+                "`CoalesceAction` TransformAction parsing failed",
+                "no columns to coalesce".to_owned(),
+            ));
+        }
+        for x in &self.columns {
+            let _ = materialized_expr(x, "CoalesceAction.columns[?] not materialized")?;
+        }
+        Ok(())
+    }
+    fn expr(&self) -> CpResult<Expr> {
+        self.validate()?;
+        let args = self
+            .columns
+            .iter()
+            .map(|x| x.value().unwrap().clone())
+            .collect::<Vec<_>>();
+        Ok(coalesce(&args))
+    }
+}
+
+/// `when: { cond: <filter>, then: expr, otherwise: expr }`, where the condition uses the
+/// filter step grammar and `otherwise` (null if absent) may be another `when` action to chain.
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct WhenAction {
+    pub cond: FilterStmt,
+    pub then: PolarsExprKeyword,
+    pub otherwise: Option<PolarsExprKeyword>,
+}
+
+impl ExprAction for WhenAction {
+    fn validate(&self) -> CpResult<()> {
+        let mut errors = vec![];
+        self.cond.validate(&mut errors);
+        if let Some(e) = errors.pop() {
+            return Err(CpError::TaskError("WhenAction.cond not materialized", format!("{}", e)));
+        }
+        let _ = materialized_expr(&self.then, "WhenAction.then not materialized")?;
+        if let Some(otherwise) = &self.otherwise {
+            let _ = materialized_expr(otherwise, "WhenAction.otherwise not materialized")?;
+        }
+        Ok(())
+    }
+    fn expr(&self) -> CpResult<Expr> {
+        self.validate()?;
+        let otherwise = self
+            .otherwise
+            .as_ref()
+            .map(|x| x.value().unwrap().clone())
+            .unwrap_or(lit(Null {}));
+        Ok(when(self.cond.expr())
+            .then(self.then.value().unwrap().clone())
+            .otherwise(otherwise))
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize)]
+pub struct FillNullAction {
+    pub col: PolarsExprKeyword,
+    pub value: PolarsExprKeyword,
+}
+
+impl ExprAction for FillNullAction {
+    fn validate(&self) -> CpResult<()> {
+        let _ = materialized_expr(&self.col, "FillNullAction.col not materialized")?;
+        let _ = materialized_expr(&self.value, "FillNullAction.value not materialized")?;
+        Ok(())
+    }
+    fn expr(&self) -> CpResult<Expr> {
+        self.validate()?;
+        Ok(self
+            .col
+            .value()
+            .unwrap()
+            .clone()
+            .fill_null(self.value.value().unwrap().clone()))
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum StrCaseOp {
+    Lower,
+    Upper,
+    Strip,
+}
+
+/// `lower: col`, `upper: col` and `strip: col` (surrounding whitespace)
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct StrCaseAction {
+    pub op: StrCaseOp,
+    pub col: PolarsExprKeyword,
+}
+
+impl ExprAction for StrCaseAction {
+    fn validate(&self) -> CpResult<()> {
+        let _ = materialized_expr(&self.col, "StrCaseAction.col not materialized")?;
+        Ok(())
+    }
+    fn expr(&self) -> CpResult<Expr> {
+        let col = materialized_expr(&self.col, "StrCaseAction.col not materialized")?.str();
+        Ok(match self.op {
+            StrCaseOp::Lower => col.to_lowercase(),
+            StrCaseOp::Upper => col.to_uppercase(),
+            StrCaseOp::Strip => col.strip_chars(lit(Null {})),
+        })
+    }
+}
+
+/// Replaces all matches of `pattern` (a regex unless `literal` is set) with `value`
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize)]
+pub struct ReplaceAction {
+    pub col: PolarsExprKeyword,
+    pub pattern: String,
+    pub value: String,
+    pub literal: Option<bool>,
+}
+
+impl ExprAction for ReplaceAction {
+    fn validate(&self) -> CpResult<()> {
+        let _ = materialized_expr(&self.col, "ReplaceAction.col not materialized")?;
+        Ok(())
+    }
+    fn expr(&self) -> CpResult<Expr> {
+        self.validate()?;
+        Ok(self.col.value().unwrap().clone().str().replace_all(
+            lit(self.pattern.clone()),
+            lit(self.value.clone()),
+            self.literal.unwrap_or(false),
+        ))
+    }
+}
+
+/// Substring from `offset` (negative counts from the end), to the end if `length` is absent
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize)]
+pub struct SliceAction {
+    pub col: PolarsExprKeyword,
+    pub offset: i64,
+    pub length: Option<u64>,
+}
+
+impl ExprAction for SliceAction {
+    fn validate(&self) -> CpResult<()> {
+        let _ = materialized_expr(&self.col, "SliceAction.col not materialized")?;
+        Ok(())
+    }
+    fn expr(&self) -> CpResult<Expr> {
+        let col = materialized_expr(&self.col, "SliceAction.col not materialized")?;
+        let length = self.length.map(lit).unwrap_or(lit(Null {}));
+        Ok(col.str().slice(lit(self.offset), length))
+    }
+}
+
+/// Splits a string column into a list of strings
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize)]
+pub struct SplitAction {
+    pub col: PolarsExprKeyword,
+    pub by: String,
+}
+
+impl ExprAction for SplitAction {
+    fn validate(&self) -> CpResult<()> {
+        let _ = materialized_expr(&self.col, "SplitAction.col not materialized")?;
+        Ok(())
+    }
+    fn expr(&self) -> CpResult<Expr> {
+        self.validate()?;
+        Ok(self.col.value().unwrap().clone().str().split(lit(self.by.clone())))
+    }
+}
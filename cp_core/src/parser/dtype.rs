@@ -1,11 +1,14 @@
 use std::collections::HashMap;
 
-use polars::prelude::{ArrowDataType, CompatLevel, DataType, Field, TimeUnit, TimeZone};
-use serde::{Deserialize, Deserializer, Serialize, de};
+use polars::{
+    export::arrow::array::Utf8ViewArray,
+    prelude::{ArrowDataType, CompatLevel, DataType, Field, TimeUnit, TimeZone, create_enum_dtype},
+};
+use serde::{Deserialize, Deserializer, Serialize, de, ser::SerializeMap};
 
 use crate::util::{
     common::{NYT, UTC},
-    error::CpResult,
+    error::{CpError, CpResult},
 };
 
 #[derive(Clone, Debug, PartialEq, Eq)]
@@ -24,6 +27,107 @@ impl From<DType> for ArrowDataType {
     }
 }
 
+/// Arguments of `datetime` and `duration`, e.g. `datetime: {unit: us, tz: Asia/Tokyo}`
+#[derive(Debug, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+struct TemporalArgs {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    unit: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    tz: Option<String>,
+}
+
+/// Arguments of `decimal`, e.g. `decimal: {precision: 18, scale: 6}`
+#[derive(Debug, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+struct DecimalArgs {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    precision: Option<usize>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    scale: Option<usize>,
+}
+
+/// Serializes struct fields as a map of field name to dtype
+struct StructFields<'a>(&'a [Field]);
+
+impl Serialize for StructFields<'_> {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: serde::Serializer,
+    {
+        let mut map = serializer.serialize_map(Some(self.0.len()))?;
+        for field in self.0 {
+            map.serialize_entry(field.name().as_str(), &DType(field.dtype().clone()))?;
+        }
+        map.end()
+    }
+}
+
+fn time_unit_str(unit: &TimeUnit) -> &'static str {
+    match unit {
+        TimeUnit::Milliseconds => "ms",
+        TimeUnit::Microseconds => "us",
+        TimeUnit::Nanoseconds => "ns",
+    }
+}
+
+fn parse_time_unit(unit: Option<&str>) -> CpResult<TimeUnit> {
+    match unit {
+        None | Some("ms") | Some("milliseconds") => Ok(TimeUnit::Milliseconds),
+        Some("us") | Some("microseconds") => Ok(TimeUnit::Microseconds),
+        Some("ns") | Some("nanoseconds") => Ok(TimeUnit::Nanoseconds),
+        Some(x) => Err(CpError::ConfigError(
+            "Unknown time unit",
+            format!("expected one of ms, us, ns, found: {}", x),
+        )),
+    }
+}
+
+/// `datetime: <tz>` or `datetime: {unit, tz}`, defaulting to milliseconds
+fn parse_datetime(args: serde_yaml_ng::Value) -> Result<CpResult<DataType>, serde_yaml_ng::Error> {
+    let args = match args {
+        serde_yaml_ng::Value::String(tz) => TemporalArgs {
+            unit: None,
+            tz: Some(tz),
+        },
+        args => serde_yaml_ng::from_value::<TemporalArgs>(args)?,
+    };
+    Ok(parse_time_unit(args.unit.as_deref()).map(|unit| DataType::Datetime(unit, args.tz.map(TimeZone::from))))
+}
+
+/// `duration: <unit>` or `duration: {unit}`, defaulting to milliseconds
+fn parse_duration(args: serde_yaml_ng::Value) -> Result<CpResult<DataType>, serde_yaml_ng::Error> {
+    let args = match args {
+        serde_yaml_ng::Value::String(unit) => TemporalArgs {
+            unit: Some(unit),
+            tz: None,
+        },
+        args => serde_yaml_ng::from_value::<TemporalArgs>(args)?,
+    };
+    if args.tz.is_some() {
+        return Ok(Err(CpError::ConfigError(
+            "Bad duration",
+            "duration does not take a timezone".to_owned(),
+        )));
+    }
+    Ok(parse_time_unit(args.unit.as_deref()).map(DataType::Duration))
+}
+
+fn parse_decimal(args: serde_yaml_ng::Value) -> Result<CpResult<DataType>, serde_yaml_ng::Error> {
+    let args = serde_yaml_ng::from_value::<DecimalArgs>(args)?;
+    Ok(match (args.precision, args.scale) {
+        (Some(precision), _) if precision == 0 || precision > 38 => Err(CpError::ConfigError(
+            "Bad decimal",
+            format!("precision must be between 1 and 38, found: {}", precision),
+        )),
+        (Some(precision), Some(scale)) if scale > precision => Err(CpError::ConfigError(
+            "Bad decimal",
+            format!("scale {} is larger than precision {}", scale, precision),
+        )),
+        (precision, scale) => Ok(DataType::Decimal(precision, scale)),
+    })
+}
+
 impl Serialize for DType {
     fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
     where
@@ -34,13 +138,67 @@ impl Serialize for DType {
             DataType::Float32 => serializer.serialize_str("float"),
             DataType::Float64 => serializer.serialize_str("double"),
             DataType::Boolean => serializer.serialize_str("bool"),
-            enum_type => {
-                if enum_type == DataType::Datetime(TimeUnit::Milliseconds, Some(TimeZone::from_str(NYT))).as_ref() {
+            DataType::Decimal(None, None) => serializer.serialize_str("decimal128"),
+            DataType::Categorical(_, _) => serializer.serialize_str("categorical"),
+            DataType::Decimal(precision, scale) => {
+                let mut map = serializer.serialize_map(Some(1))?;
+                map.serialize_entry(
+                    "decimal",
+                    &DecimalArgs {
+                        precision: *precision,
+                        scale: *scale,
+                    },
+                )?;
+                map.end()
+            }
+            DataType::Duration(unit) => {
+                let mut map = serializer.serialize_map(Some(1))?;
+                map.serialize_entry(
+                    "duration",
+                    &TemporalArgs {
+                        unit: Some(time_unit_str(unit).to_owned()),
+                        tz: None,
+                    },
+                )?;
+                map.end()
+            }
+            DataType::Enum(rev_map, _) => {
+                let categories = rev_map
+                    .as_ref()
+                    .map(|x| x.get_categories().values_iter().collect::<Vec<_>>())
+                    .unwrap_or_default();
+                let mut map = serializer.serialize_map(Some(1))?;
+                map.serialize_entry("enum", &categories)?;
+                map.end()
+            }
+            DataType::List(inner) => {
+                let mut map = serializer.serialize_map(Some(1))?;
+                map.serialize_entry("list", &DType(*inner.clone()))?;
+                map.end()
+            }
+            DataType::Struct(fields) => {
+                let mut map = serializer.serialize_map(Some(1))?;
+                map.serialize_entry("struct", &StructFields(fields))?;
+                map.end()
+            }
+            DataType::Datetime(unit, tz) => {
+                if self.0 == DataType::Datetime(TimeUnit::Milliseconds, Some(TimeZone::from_str(NYT))) {
                     return serializer.serialize_str("datetime_nyt");
                 }
-                if enum_type == DataType::Datetime(TimeUnit::Milliseconds, Some(TimeZone::from_str(UTC))).as_ref() {
+                if self.0 == DataType::Datetime(TimeUnit::Milliseconds, Some(TimeZone::from_str(UTC))) {
                     return serializer.serialize_str("datetime_utc");
                 }
+                let mut map = serializer.serialize_map(Some(1))?;
+                map.serialize_entry(
+                    "datetime",
+                    &TemporalArgs {
+                        unit: Some(time_unit_str(unit).to_owned()),
+                        tz: tz.as_ref().map(|x| x.to_string()),
+                    },
+                )?;
+                map.end()
+            }
+            enum_type => {
                 let repr = format!("{:?}", enum_type).trim().to_lowercase().to_owned();
                 serializer.serialize_str(&repr)
             }
@@ -74,8 +232,13 @@ impl<'de> Deserialize<'de> for DType {
                 "float" => Ok(DType(DataType::Float32)),
                 "double" => Ok(DType(DataType::Float64)),
                 "str" => Ok(DType(DataType::String)),
+                "binary" => Ok(DType(DataType::Binary)),
+                "null" => Ok(DType(DataType::Null)),
+                "categorical" => Ok(DType(DataType::Categorical(None, Default::default()))),
                 "time" => Ok(DType(DataType::Time)),
                 "date" => Ok(DType(DataType::Date)),
+                "datetime" => Ok(DType(DataType::Datetime(TimeUnit::Milliseconds, None))),
+                "duration" => Ok(DType(DataType::Duration(TimeUnit::Milliseconds))),
                 "datetime_nyt" => Ok(DType(DataType::Datetime(
                     TimeUnit::Milliseconds,
                     Some(TimeZone::from_str(NYT)),
@@ -84,6 +247,9 @@ impl<'de> Deserialize<'de> for DType {
                     TimeUnit::Milliseconds,
                     Some(TimeZone::from_str(UTC)),
                 ))),
+                // bson types: ObjectIds are kept as hex strings, Decimal128s infer their scale
+                "objectid" => Ok(DType(DataType::String)),
+                "decimal128" => Ok(DType(DataType::Decimal(None, None))),
                 s => Err(de::Error::custom(format!("Unknown dtype in model: {}", s))),
             },
             Helper::Full(full) => {
@@ -108,8 +274,11 @@ impl<'de> Deserialize<'de> for DType {
                     "list" => {
                         serde_yaml_ng::from_value::<DType>(type_args).map(|dtype| Ok(DataType::List(Box::new(dtype.0))))
                     }
-                    "datetime" => serde_yaml_ng::from_value::<String>(type_args)
-                        .map(|tz| Ok(DataType::Datetime(TimeUnit::Milliseconds, Some(tz.into())))),
+                    "datetime" => parse_datetime(type_args),
+                    "duration" => parse_duration(type_args),
+                    "decimal" => parse_decimal(type_args),
+                    "enum" => serde_yaml_ng::from_value::<Vec<String>>(type_args)
+                        .map(|categories| Ok(create_enum_dtype(Utf8ViewArray::from_slice_values(categories)))),
                     x => {
                         return Err(de::Error::custom(format!("Unrecognized datatype: {}", x)));
                     }
@@ -229,6 +398,17 @@ mod tests {
         assert_eq!(actual, expected);
     }
 
+    #[test]
+    fn valid_dtype_de_bson() {
+        assert_eq!(
+            serde_yaml_ng::from_str::<DType>("objectid").unwrap(),
+            DType(DataType::String)
+        );
+        let decimal = serde_yaml_ng::from_str::<DType>("decimal128").unwrap();
+        assert_eq!(decimal, DType(DataType::Decimal(None, None)));
+        assert_eq!(serde_yaml_ng::to_string(&decimal).unwrap().trim(), "decimal128");
+    }
+
     #[test]
     fn other_dtype_ser() {
         assert_eq!(
@@ -237,9 +417,91 @@ mod tests {
         );
     }
 
+    #[test]
+    fn valid_dtype_de_extended() {
+        let configs = [
+            "binary",
+            "'null'",
+            "categorical",
+            "datetime",
+            "duration",
+            "datetime: {unit: us, tz: Asia/Tokyo}",
+            "datetime: {unit: ns}",
+            "duration: us",
+            "duration: {unit: nanoseconds}",
+            "decimal: {precision: 18, scale: 6}",
+            "decimal: {scale: 2}",
+        ];
+        let expected = [
+            DataType::Binary,
+            DataType::Null,
+            DataType::Categorical(None, Default::default()),
+            DataType::Datetime(TimeUnit::Milliseconds, None),
+            DataType::Duration(TimeUnit::Milliseconds),
+            DataType::Datetime(TimeUnit::Microseconds, Some("Asia/Tokyo".into())),
+            DataType::Datetime(TimeUnit::Nanoseconds, None),
+            DataType::Duration(TimeUnit::Microseconds),
+            DataType::Duration(TimeUnit::Nanoseconds),
+            DataType::Decimal(Some(18), Some(6)),
+            DataType::Decimal(None, Some(2)),
+        ];
+        let actual = configs.map(|c| serde_yaml_ng::from_str::<DType>(c).unwrap().0);
+        assert_eq!(actual, expected);
+    }
+
+    #[test]
+    fn valid_dtype_de_enum() {
+        let actual = serde_yaml_ng::from_str::<DType>("enum: [low, mid, high]").unwrap();
+        let DataType::Enum(Some(rev_map), _) = &actual.0 else {
+            panic!("expected an enum, found {:?}", actual);
+        };
+        assert_eq!(
+            rev_map.get_categories().values_iter().collect::<Vec<_>>(),
+            vec!["low", "mid", "high"]
+        );
+    }
+
+    #[test]
+    fn valid_dtype_round_trip() {
+        let configs = [
+            "datetime: {unit: us, tz: Asia/Tokyo}",
+            "datetime: {unit: ms}",
+            "duration: {unit: ns}",
+            "decimal: {precision: 18, scale: 6}",
+            "enum: [low, mid, high]",
+            "list: {decimal: {precision: 10, scale: 2}}",
+            "struct: {a: {datetime: {unit: ns, tz: Europe/London}}, b: categorical}",
+            "categorical",
+            "binary",
+            "'null'",
+        ];
+        for config in configs {
+            let dtype = serde_yaml_ng::from_str::<DType>(config).unwrap();
+            let serialized = serde_yaml_ng::to_string(&dtype).unwrap();
+            let round_trip = serde_yaml_ng::from_str::<DType>(&serialized).unwrap();
+            assert_eq!(round_trip, dtype, "{} -> {}", config, serialized);
+        }
+        assert_eq!(
+            serde_yaml_ng::to_string(&DType(DataType::Duration(TimeUnit::Microseconds)))
+                .unwrap()
+                .trim(),
+            "duration:\n  unit: us"
+        );
+    }
+
     #[test]
     fn invalid_dtype_de() {
         assert!(serde_yaml_ng::from_str::<DType>("bad").is_err());
+        for config in [
+            "datetime: {unit: hours}",
+            "datetime: {unit: ms, zone: UTC}",
+            "duration: {unit: ms, tz: UTC}",
+            "decimal: {precision: 40, scale: 2}",
+            "decimal: {precision: 4, scale: 6}",
+            "enum: low",
+        ] {
+            assert!(serde_yaml_ng::from_str::<DType>(config).is_err(), "{}", config);
+        }
     }
 
     #[test]
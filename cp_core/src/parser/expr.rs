@@ -7,34 +7,85 @@ pub fn parse_str_to_col_expr(rawexpr: &str) -> Option<Expr> {
     parse_col_expr(&mut fields, |_: Option<Expr>, arg: &str| Some(col(arg)), None)
 }
 
+/// Splits `name[0][-1]` into the name and its list indices
+fn parse_list_indices(segment: &str) -> Option<(&str, Vec<i64>)> {
+    let (name, mut rest) = match segment.find('[') {
+        Some(pos) => (&segment[..pos], &segment[pos..]),
+        None => (segment, ""),
+    };
+    let mut indices = vec![];
+    while !rest.is_empty() {
+        let close = rest.find(']')?;
+        if !rest.starts_with('[') {
+            return None;
+        }
+        indices.push(rest[1..close].trim().parse::<i64>().ok()?);
+        rest = &rest[close + 1..];
+    }
+    if name.is_empty() {
+        return None;
+    }
+    Some((name, indices))
+}
+
 fn parse_col_expr<F>(fields: &mut Vec<&str>, transformer: F, acc_expr: Option<Expr>) -> Option<Expr>
 where
     F: Fn(Option<Expr>, &str) -> Option<Expr>,
 {
-    if fields.is_empty() || fields.last().unwrap().is_empty() {
-        return acc_expr;
-    }
-    let head = fields.pop().unwrap();
-    let next_transform = match head.chars().last().unwrap() {
-        // (.xxx : struct.field(xxx)) DONE
-        // TODO: Parse expression
+    // Each level is evaluated on the elements of the list in the level above, e.g.
+    // `games*scores.home` is [col(games), element().struct.field(scores).struct.field(home)]
+    let mut levels: Vec<Expr> = vec![];
+    let mut delim: Option<char> = None;
+    while let Some(head) = fields.pop() {
+        let next_delim = head.chars().last().filter(|x| COL_EXPR_DELIMITERS.contains(x));
+        let segment = head
+            .strip_suffix(|x: char| COL_EXPR_DELIMITERS.contains(&x))
+            .unwrap_or(head);
+        // empty segments such as `a..b` or `.a` are malformed
+        if segment.is_empty() {
+            return None;
+        }
+        let (field, indices) = parse_list_indices(segment)?;
+        match (levels.pop(), delim) {
+            // (xxx : col(xxx))
+            (None, _) => levels.push(transformer(acc_expr.clone(), field)?),
+            // (.xxx : struct.field(xxx))
+            (Some(left), Some('.')) => levels.push(left.struct_().field_by_name(field)),
+            // (*xxx : list.eval(element().struct.field(xxx)))
+            (Some(left), Some('*')) => {
+                levels.push(left);
+                levels.push(col("").struct_().field_by_name(field));
+            }
+            // `@` is reserved
+            (Some(_), _) => return None,
+        }
         // ([idx] : list.get(idx))
-        // (*xxx : list.eval(element().struct.field(xxx)))
-        '.' => |left: Option<Expr>, next_arg: &str| Some(left.unwrap().struct_().field_by_name(next_arg)),
-        _ => |left: Option<Expr>, _: &str| left,
-    };
-    let field = head
-        .strip_suffix(|delim: char| COL_EXPR_DELIMITERS.clone().contains(&delim))
-        .unwrap_or(head);
-    parse_col_expr(fields, next_transform, transformer(acc_expr, field))
+        if let Some(left) = levels.pop() {
+            let indexed = indices
+                .into_iter()
+                .fold(left, |expr, idx| expr.list().get(lit(idx), true));
+            levels.push(indexed);
+        }
+        delim = next_delim;
+    }
+    // a trailing delimiter such as `a.` has nothing to apply to
+    if delim.is_some() {
+        return None;
+    }
+    let mut expr = levels.pop();
+    while let Some(outer) = levels.pop() {
+        expr = Some(outer.list().eval(expr?, false));
+    }
+    expr.or(acc_expr)
 }
 
 #[cfg(test)]
 mod tests {
 
-    use polars::prelude::{Expr, col};
+    use polars::prelude::*;
 
-    use crate::parser::expr::{COL_EXPR_DELIMITERS, parse_col_expr};
+    use crate::parser::expr::{COL_EXPR_DELIMITERS, parse_col_expr, parse_str_to_col_expr};
+    use crate::util::common::str_json_to_df;
 
     #[test]
     fn parse_col_expr_one_level() {
@@ -58,4 +109,95 @@ mod tests {
                 .field_by_name("once")
         );
     }
+
+    #[test]
+    fn parse_col_expr_list_index() {
+        assert_eq!(
+            parse_str_to_col_expr("args[0][-1]").unwrap(),
+            col("args").list().get(lit(0i64), true).list().get(lit(-1i64), true)
+        );
+        assert_eq!(
+            parse_str_to_col_expr("args[2].test").unwrap(),
+            col("args").list().get(lit(2i64), true).struct_().field_by_name("test")
+        );
+    }
+
+    #[test]
+    fn parse_col_expr_invalid() {
+        for rawexpr in [
+            "args[",
+            "args[x]",
+            "args]0[",
+            "[0]",
+            "args.[0]",
+            "args@test",
+            "args..test",
+            "args.",
+            ".args",
+            "args*",
+            "",
+        ] {
+            assert!(parse_str_to_col_expr(rawexpr).is_none(), "{}", rawexpr);
+        }
+    }
+
+    #[test]
+    fn run_col_expr_nested_lists() {
+        let df = str_json_to_df(
+            r#"[
+                { "games": [1, 2], "played": [{ "id": 1, "stats": { "goals": 3 }, "assists": [{ "by": "A" }] }, { "id": 2, "stats": { "goals": 1 }, "assists": [] }] },
+                { "games": [3], "played": [{ "id": 1, "stats": { "goals": 0 }, "assists": [{ "by": "B" }, { "by": "C" }] }] }
+            ]"#,
+        )
+        .unwrap();
+        let actual = df
+            .lazy()
+            .select([
+                parse_str_to_col_expr("games[-1]").unwrap().alias("last"),
+                parse_str_to_col_expr("played*stats.goals").unwrap().alias("goals"),
+                parse_str_to_col_expr("played*assists*by").unwrap().alias("assists"),
+                parse_str_to_col_expr("played*assists[0].by")
+                    .unwrap()
+                    .alias("first_assist"),
+                parse_str_to_col_expr("played[0].id").unwrap().alias("first_id"),
+            ])
+            .collect()
+            .unwrap();
+        assert_eq!(
+            actual.column("last").unwrap().i64().unwrap().to_vec(),
+            vec![Some(2), Some(3)]
+        );
+        assert_eq!(
+            actual.column("first_id").unwrap().i64().unwrap().to_vec(),
+            vec![Some(1), Some(1)]
+        );
+        let goals = actual
+            .column("goals")
+            .unwrap()
+            .list()
+            .unwrap()
+            .get_as_series(0)
+            .unwrap();
+        assert_eq!(goals.i64().unwrap().to_vec(), vec![Some(3), Some(1)]);
+        let assists = actual
+            .column("assists")
+            .unwrap()
+            .list()
+            .unwrap()
+            .get_as_series(1)
+            .unwrap();
+        let assists = assists.list().unwrap().get_as_series(0).unwrap();
+        assert_eq!(assists.str().unwrap().get(1), Some("C"));
+        let first_assist = actual
+            .column("first_assist")
+            .unwrap()
+            .list()
+            .unwrap()
+            .get_as_series(0)
+            .unwrap();
+        assert_eq!(
+            first_assist.str().unwrap().iter().collect::<Vec<_>>(),
+            vec![Some("A"), None]
+        );
+    }
 }
@@ -0,0 +1,369 @@
+use polars::prelude::{Expr, concat_expr, lit};
+use serde::{Deserialize, Deserializer, de};
+use serde_yaml_ng::Value;
+
+use crate::{
+    util::error::{CpError, CpResult},
+    valid_or_insert_error,
+};
+
+use super::keyword::{Keyword, PolarsExprKeyword, StrKeyword};
+
+/// A single predicate applied to the column on the left hand side of a `FilterStmt`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum FilterPredicate {
+    Eq(PolarsExprKeyword),
+    Ne(PolarsExprKeyword),
+    Gt(PolarsExprKeyword),
+    Gte(PolarsExprKeyword),
+    Lt(PolarsExprKeyword),
+    Lte(PolarsExprKeyword),
+    In(Vec<PolarsExprKeyword>),
+    IsNull(bool),
+    Between(PolarsExprKeyword, PolarsExprKeyword),
+    Matches(StrKeyword),
+}
+
+/// Filter statement, parsed from `{ col_keyword_a: { OP: col_keyword_b } }` or the logical
+/// combinators `and: [..]`, `or: [..]` and `not: {..}`. Several entries in the same map (or
+/// several ops for the same column) are combined with `and`, as is a top-level list.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum FilterStmt {
+    And(Vec<FilterStmt>),
+    Or(Vec<FilterStmt>),
+    Not(Box<FilterStmt>),
+    Compare(PolarsExprKeyword, Vec<FilterPredicate>),
+}
+
+fn parse_operand(value: Value) -> Result<PolarsExprKeyword, String> {
+    serde_yaml_ng::from_value::<PolarsExprKeyword>(value.clone())
+        .map_err(|e| format!("invalid filter operand {:?}: {}", value, e))
+}
+
+fn parse_operands(value: Value) -> Result<Vec<PolarsExprKeyword>, String> {
+    match value {
+        Value::Sequence(seq) => seq.into_iter().map(parse_operand).collect(),
+        x => Err(format!("expected a list of filter operands, found: {:?}", x)),
+    }
+}
+
+impl FilterPredicate {
+    fn from_entry(op: &str, value: Value) -> Result<FilterPredicate, String> {
+        let pred = match op {
+            "eq" | "==" => FilterPredicate::Eq(parse_operand(value)?),
+            "ne" | "!=" => FilterPredicate::Ne(parse_operand(value)?),
+            "gt" | ">" => FilterPredicate::Gt(parse_operand(value)?),
+            "gte" | ">=" => FilterPredicate::Gte(parse_operand(value)?),
+            "lt" | "<" => FilterPredicate::Lt(parse_operand(value)?),
+            "lte" | "<=" => FilterPredicate::Lte(parse_operand(value)?),
+            "in" => FilterPredicate::In(parse_operands(value)?),
+            "is_null" => match value {
+                Value::Bool(x) => FilterPredicate::IsNull(x),
+                x => return Err(format!("`is_null` expects a bool, found: {:?}", x)),
+            },
+            "between" => {
+                let mut bounds = parse_operands(value)?;
+                if bounds.len() != 2 {
+                    return Err(format!("`between` expects [lower, upper], found: {:?}", bounds));
+                }
+                let upper = bounds.pop().unwrap();
+                let lower = bounds.pop().unwrap();
+                FilterPredicate::Between(lower, upper)
+            }
+            "matches" | "regex" => FilterPredicate::Matches(
+                serde_yaml_ng::from_value::<StrKeyword>(value).map_err(|e| format!("invalid regex pattern: {}", e))?,
+            ),
+            x => return Err(format!("unrecognized filter op: {}", x)),
+        };
+        Ok(pred)
+    }
+
+    fn emplace(&mut self, context: &serde_yaml_ng::Mapping) -> CpResult<()> {
+        match self {
+            FilterPredicate::Eq(x)
+            | FilterPredicate::Ne(x)
+            | FilterPredicate::Gt(x)
+            | FilterPredicate::Gte(x)
+            | FilterPredicate::Lt(x)
+            | FilterPredicate::Lte(x) => x.insert_value_from_context(context),
+            FilterPredicate::In(xs) => {
+                for x in xs.iter_mut() {
+                    x.insert_value_from_context(context)?;
+                }
+                Ok(())
+            }
+            FilterPredicate::Between(lower, upper) => {
+                lower.insert_value_from_context(context)?;
+                upper.insert_value_from_context(context)
+            }
+            FilterPredicate::Matches(pattern) => pattern.insert_value_from_context(context),
+            FilterPredicate::IsNull(_) => Ok(()),
+        }
+    }
+
+    fn validate(&self, errors: &mut Vec<CpError>) {
+        match self {
+            FilterPredicate::Eq(x)
+            | FilterPredicate::Ne(x)
+            | FilterPredicate::Gt(x)
+            | FilterPredicate::Gte(x)
+            | FilterPredicate::Lt(x)
+            | FilterPredicate::Lte(x) => valid_or_insert_error!(errors, x, "filter.operand"),
+            FilterPredicate::In(xs) => {
+                for x in xs {
+                    valid_or_insert_error!(errors, x, "filter.in");
+                }
+            }
+            FilterPredicate::Between(lower, upper) => {
+                valid_or_insert_error!(errors, lower, "filter.between");
+                valid_or_insert_error!(errors, upper, "filter.between");
+            }
+            FilterPredicate::Matches(pattern) => valid_or_insert_error!(errors, pattern, "filter.matches"),
+            FilterPredicate::IsNull(_) => {}
+        }
+    }
+
+    fn expr(&self, lhs: Expr) -> Expr {
+        let operand = |x: &PolarsExprKeyword| x.value().expect("filter.operand").clone();
+        match self {
+            FilterPredicate::Eq(x) => lhs.eq(operand(x)),
+            FilterPredicate::Ne(x) => lhs.neq(operand(x)),
+            FilterPredicate::Gt(x) => lhs.gt(operand(x)),
+            FilterPredicate::Gte(x) => lhs.gt_eq(operand(x)),
+            FilterPredicate::Lt(x) => lhs.lt(operand(x)),
+            FilterPredicate::Lte(x) => lhs.lt_eq(operand(x)),
+            FilterPredicate::In(xs) if xs.is_empty() => lit(false),
+            FilterPredicate::In(xs) => {
+                lhs.is_in(concat_expr(xs.iter().map(operand).collect::<Vec<_>>(), false).expect("filter.in"))
+            }
+            FilterPredicate::IsNull(true) => lhs.is_null(),
+            FilterPredicate::IsNull(false) => lhs.is_not_null(),
+            FilterPredicate::Between(lower, upper) => lhs.clone().gt_eq(operand(lower)).and(lhs.lt_eq(operand(upper))),
+            FilterPredicate::Matches(pattern) => lhs
+                .str()
+                .contains(lit(pattern.value().expect("filter.matches").clone()), true),
+        }
+    }
+}
+
+impl FilterStmt {
+    fn from_value(value: Value) -> Result<FilterStmt, String> {
+        match value {
+            Value::Sequence(seq) => Ok(FilterStmt::And(
+                seq.into_iter()
+                    .map(FilterStmt::from_value)
+                    .collect::<Result<Vec<_>, _>>()?,
+            )),
+            Value::Mapping(map) => {
+                let mut stmts = map
+                    .into_iter()
+                    .map(|(k, v)| FilterStmt::from_entry(k, v))
+                    .collect::<Result<Vec<_>, _>>()?;
+                match stmts.len() {
+                    0 => Err("empty filter statement".to_owned()),
+                    1 => Ok(stmts.pop().unwrap()),
+                    _ => Ok(FilterStmt::And(stmts)),
+                }
+            }
+            x => Err(format!("filter statement must be a map or a list, found: {:?}", x)),
+        }
+    }
+
+    fn from_entry(key: Value, value: Value) -> Result<FilterStmt, String> {
+        let key = match key {
+            Value::String(x) => x,
+            x => return Err(format!("filter key must be a string, found: {:?}", x)),
+        };
+        match key.as_str() {
+            "and" | "or" => {
+                let stmts = match value {
+                    Value::Sequence(seq) => seq
+                        .into_iter()
+                        .map(FilterStmt::from_value)
+                        .collect::<Result<Vec<_>, _>>()?,
+                    x => return Err(format!("`{}` expects a list of filter statements, found: {:?}", key, x)),
+                };
+                if key == "and" {
+                    Ok(FilterStmt::And(stmts))
+                } else {
+                    Ok(FilterStmt::Or(stmts))
+                }
+            }
+            "not" => Ok(FilterStmt::Not(Box::new(FilterStmt::from_value(value)?))),
+            _ => {
+                let lhs = parse_operand(Value::String(key))?;
+                let preds = match value {
+                    Value::Mapping(map) => map
+                        .into_iter()
+                        .map(|(op, v)| match op {
+                            Value::String(op) => FilterPredicate::from_entry(op.as_str(), v),
+                            x => Err(format!("filter op must be a string, found: {:?}", x)),
+                        })
+                        .collect::<Result<Vec<_>, _>>()?,
+                    x => return Err(format!("expected a map of {{ OP: operand }}, found: {:?}", x)),
+                };
+                if preds.is_empty() {
+                    return Err(format!("no filter ops found for {:?}", lhs));
+                }
+                Ok(FilterStmt::Compare(lhs, preds))
+            }
+        }
+    }
+
+    pub fn emplace(&mut self, context: &serde_yaml_ng::Mapping) -> CpResult<()> {
+        match self {
+            FilterStmt::And(stmts) | FilterStmt::Or(stmts) => {
+                for stmt in stmts.iter_mut() {
+                    stmt.emplace(context)?;
+                }
+            }
+            FilterStmt::Not(stmt) => stmt.emplace(context)?,
+            FilterStmt::Compare(lhs, preds) => {
+                lhs.insert_value_from_context(context)?;
+                for pred in preds.iter_mut() {
+                    pred.emplace(context)?;
+                }
+            }
+        }
+        Ok(())
+    }
+
+    pub fn validate(&self, errors: &mut Vec<CpError>) {
+        match self {
+            FilterStmt::And(stmts) | FilterStmt::Or(stmts) => {
+                for stmt in stmts {
+                    stmt.validate(errors);
+                }
+            }
+            FilterStmt::Not(stmt) => stmt.validate(errors),
+            FilterStmt::Compare(lhs, preds) => {
+                valid_or_insert_error!(errors, lhs, "filter.column");
+                for pred in preds {
+                    pred.validate(errors);
+                }
+            }
+        }
+    }
+
+    pub fn expr(&self) -> Expr {
+        match self {
+            FilterStmt::And(stmts) => stmts
+                .iter()
+                .map(|x| x.expr())
+                .reduce(|a, b| a.and(b))
+                .unwrap_or(lit(true)),
+            FilterStmt::Or(stmts) => stmts
+                .iter()
+                .map(|x| x.expr())
+                .reduce(|a, b| a.or(b))
+                .unwrap_or(lit(false)),
+            FilterStmt::Not(stmt) => stmt.expr().not(),
+            FilterStmt::Compare(lhs, preds) => {
+                let lhs = lhs.value().expect("filter.column");
+                preds
+                    .iter()
+                    .map(|x| x.expr(lhs.clone()))
+                    .reduce(|a, b| a.and(b))
+                    .unwrap_or(lit(true))
+            }
+        }
+    }
+}
+
+impl<'de> Deserialize<'de> for FilterStmt {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        let value = Value::deserialize(deserializer)?;
+        FilterStmt::from_value(value).map_err(de::Error::custom)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use polars::prelude::{col, lit};
+
+    use crate::parser::keyword::{Keyword, PolarsExprKeyword, StrKeyword};
+
+    use super::{FilterPredicate, FilterStmt};
+
+    #[test]
+    fn valid_filter_stmt_compare() {
+        let config = "
+price: { gt: $min_price, lte: { int64: 100 } }
+";
+        let actual: FilterStmt = serde_yaml_ng::from_str(config).unwrap();
+        assert_eq!(
+            actual,
+            FilterStmt::Compare(
+                PolarsExprKeyword::with_value(col("price")),
+                vec![
+                    FilterPredicate::Gt(PolarsExprKeyword::with_symbol("min_price")),
+                    FilterPredicate::Lte(PolarsExprKeyword::with_value(lit(100i64))),
+                ]
+            )
+        );
+    }
+
+    #[test]
+    fn valid_filter_stmt_nested() {
+        let config = "
+or:
+    - mkt: { in: [{ str: amer }, { str: emea }] }
+    - not:
+        ric: { matches: ^A }
+    - and:
+        - id: { between: [{ int32: 1 }, { int32: 3 }] }
+        - price: { is_null: false }
+";
+        let actual: FilterStmt = serde_yaml_ng::from_str(config).unwrap();
+        assert_eq!(
+            actual,
+            FilterStmt::Or(vec![
+                FilterStmt::Compare(
+                    PolarsExprKeyword::with_value(col("mkt")),
+                    vec![FilterPredicate::In(vec![
+                        PolarsExprKeyword::with_value(lit("amer")),
+                        PolarsExprKeyword::with_value(lit("emea")),
+                    ])]
+                ),
+                FilterStmt::Not(Box::new(FilterStmt::Compare(
+                    PolarsExprKeyword::with_value(col("ric")),
+                    vec![FilterPredicate::Matches(StrKeyword::with_value("^A".to_owned()))]
+                ))),
+                FilterStmt::And(vec![
+                    FilterStmt::Compare(
+                        PolarsExprKeyword::with_value(col("id")),
+                        vec![FilterPredicate::Between(
+                            PolarsExprKeyword::with_value(lit(1i32)),
+                            PolarsExprKeyword::with_value(lit(3i32)),
+                        )]
+                    ),
+                    FilterStmt::Compare(
+                        PolarsExprKeyword::with_value(col("price")),
+                        vec![FilterPredicate::IsNull(false)]
+                    ),
+                ]),
+            ])
+        );
+    }
+
+    #[test]
+    fn invalid_filter_stmt() {
+        for config in [
+            "price: { approx: 3 }",
+            "price: { between: [a] }",
+            "price: { is_null: maybe }",
+            "and: { price: { gt: a } }",
+            "price: {}",
+            "{}",
+        ] {
+            assert!(
+                serde_yaml_ng::from_str::<FilterStmt>(config).is_err(),
+                "expected error: {}",
+                config
+            );
+        }
+    }
+}
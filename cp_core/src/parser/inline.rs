@@ -0,0 +1,594 @@
+use polars::prelude::{Expr, lit};
+
+use crate::util::error::{CpError, CpResult};
+
+use super::{
+    action::{
+        ArithmeticAction, ArithmeticOp, CastAction, CoalesceAction, ExprAction, FillNullAction, StrCaseAction,
+        StrCaseOp,
+    },
+    dtype::DType,
+    expr::parse_str_to_col_expr,
+    keyword::{Keyword, PolarsExprKeyword},
+};
+
+#[derive(Debug, Clone, PartialEq)]
+enum Token {
+    /// column path, either bare or quoted in backticks
+    Column(String),
+    Symbol(String),
+    Int(i64),
+    Float(f64),
+    Str(String),
+    Word(String),
+    Op(&'static str),
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum BinaryOp {
+    Or,
+    And,
+    Eq,
+    Ne,
+    Lt,
+    Lte,
+    Gt,
+    Gte,
+    Arithmetic(ArithmeticOp),
+}
+
+#[derive(Debug, Clone, PartialEq)]
+enum Node {
+    Column(Expr),
+    Symbol(String),
+    Literal(Expr),
+    Neg(Box<Node>),
+    Not(Box<Node>),
+    Binary(BinaryOp, Box<Node>, Box<Node>),
+    Cast(Box<Node>, DType),
+    Call(String, Vec<Node>),
+}
+
+/// Functions callable from an inline expression, with their min and max number of arguments
+fn function_arity(name: &str) -> Option<(usize, usize)> {
+    match name {
+        "coalesce" => Some((1, usize::MAX)),
+        "lower" | "upper" | "strip" | "is_null" | "is_not_null" => Some((1, 1)),
+        "cast" | "fill_null" => Some((2, 2)),
+        _ => None,
+    }
+}
+
+fn parse_error(src: &str, offset: usize, msg: &str) -> CpError {
+    CpError::ConfigError(
+        "Inline expression parse error",
+        format!("{} at offset {}\n{}\n{}^", msg, offset, src, " ".repeat(offset)),
+    )
+}
+
+fn is_symbol_char(c: char) -> bool {
+    c.is_alphanumeric() || c == '_'
+}
+
+fn is_column_char(c: char) -> bool {
+    is_symbol_char(c) || c == '.' || c == '@'
+}
+
+/// Splits the expression into tokens, each with the character offset it starts at
+fn tokenize(src: &str) -> CpResult<Vec<(Token, usize)>> {
+    let chars = src.chars().collect::<Vec<_>>();
+    let mut tokens = vec![];
+    let mut i = 0;
+    while i < chars.len() {
+        let start = i;
+        let c = chars[i];
+        if c.is_whitespace() {
+            i += 1;
+            continue;
+        }
+        let token = match c {
+            '$' => {
+                i += 1;
+                while i < chars.len() && is_symbol_char(chars[i]) {
+                    i += 1;
+                }
+                if i == start + 1 {
+                    return Err(parse_error(src, start, "expected a symbol name after `$`"));
+                }
+                Token::Symbol(chars[start + 1..i].iter().collect())
+            }
+            '\'' | '"' => {
+                let mut value = String::new();
+                i += 1;
+                loop {
+                    match chars.get(i) {
+                        None => return Err(parse_error(src, start, "unterminated string")),
+                        Some('\\') if i + 1 < chars.len() => {
+                            value.push(chars[i + 1]);
+                            i += 2;
+                        }
+                        Some(x) if *x == c => {
+                            i += 1;
+                            break;
+                        }
+                        Some(x) => {
+                            value.push(*x);
+                            i += 1;
+                        }
+                    }
+                }
+                Token::Str(value)
+            }
+            '`' => {
+                i += 1;
+                while i < chars.len() && chars[i] != '`' {
+                    i += 1;
+                }
+                if i == chars.len() {
+                    return Err(parse_error(src, start, "unterminated quoted column"));
+                }
+                i += 1;
+                Token::Column(chars[start + 1..i - 1].iter().collect())
+            }
+            x if x.is_ascii_digit() => {
+                while i < chars.len() && (chars[i].is_ascii_digit() || chars[i] == '.') {
+                    i += 1;
+                }
+                let number = chars[start..i].iter().collect::<String>();
+                match number.contains('.') {
+                    true => Token::Float(number.parse().map_err(|_| parse_error(src, start, "invalid number"))?),
+                    false => Token::Int(number.parse().map_err(|_| parse_error(src, start, "invalid number"))?),
+                }
+            }
+            x if is_symbol_char(x) => {
+                // list indices such as `games[-1]` belong to the column path
+                while i < chars.len() && (is_column_char(chars[i]) || chars[i] == '[') {
+                    if chars[i] == '[' {
+                        while i < chars.len() && chars[i] != ']' {
+                            i += 1;
+                        }
+                    }
+                    i += 1;
+                }
+                let word = chars[start..i.min(chars.len())].iter().collect::<String>();
+                match word.as_str() {
+                    "and" | "or" | "not" | "true" | "false" | "null" => Token::Word(word),
+                    _ if chars.get(i) == Some(&'(') => Token::Word(word),
+                    _ => Token::Column(word),
+                }
+            }
+            _ => {
+                let two = chars[i..(i + 2).min(chars.len())].iter().collect::<String>();
+                let op = ["==", "!=", "<=", ">="]
+                    .into_iter()
+                    .find(|x| *x == two)
+                    .or_else(|| {
+                        ["+", "-", "*", "/", "%", "<", ">", "(", ")", ","]
+                            .into_iter()
+                            .find(|x| x.starts_with(c))
+                    })
+                    .ok_or_else(|| parse_error(src, start, &format!("unexpected character `{}`", c)))?;
+                i += op.len();
+                Token::Op(op)
+            }
+        };
+        tokens.push((token, start));
+    }
+    Ok(tokens)
+}
+
+/// Recursive descent over the tokens, by increasing precedence:
+/// `or`, `and`, `not`, comparisons, `+ -`, `* / %`, unary `-`, then operands and calls.
+struct Parser<'a> {
+    src: &'a str,
+    tokens: Vec<(Token, usize)>,
+    pos: usize,
+}
+
+impl Parser<'_> {
+    fn offset(&self) -> usize {
+        self.tokens
+            .get(self.pos)
+            .map_or(self.src.chars().count(), |(_, offset)| *offset)
+    }
+
+    fn error(&self, msg: &str) -> CpError {
+        parse_error(self.src, self.offset(), msg)
+    }
+
+    fn peek(&self) -> Option<&Token> {
+        self.tokens.get(self.pos).map(|(token, _)| token)
+    }
+
+    fn eat_op(&mut self, ops: &[&'static str]) -> Option<&'static str> {
+        match self.peek() {
+            Some(Token::Op(op)) if ops.contains(op) => {
+                let op = *op;
+                self.pos += 1;
+                Some(op)
+            }
+            _ => None,
+        }
+    }
+
+    fn eat_word(&mut self, word: &str) -> bool {
+        match self.peek() {
+            Some(Token::Word(x)) if x == word => {
+                self.pos += 1;
+                true
+            }
+            _ => false,
+        }
+    }
+
+    fn expect_op(&mut self, op: &'static str) -> CpResult<()> {
+        match self.eat_op(&[op]) {
+            Some(_) => Ok(()),
+            None => Err(self.error(&format!("expected `{}`", op))),
+        }
+    }
+
+    fn or(&mut self) -> CpResult<Node> {
+        let mut node = self.and()?;
+        while self.eat_word("or") {
+            node = Node::Binary(BinaryOp::Or, Box::new(node), Box::new(self.and()?));
+        }
+        Ok(node)
+    }
+
+    fn and(&mut self) -> CpResult<Node> {
+        let mut node = self.not()?;
+        while self.eat_word("and") {
+            node = Node::Binary(BinaryOp::And, Box::new(node), Box::new(self.not()?));
+        }
+        Ok(node)
+    }
+
+    fn not(&mut self) -> CpResult<Node> {
+        match self.eat_word("not") {
+            true => Ok(Node::Not(Box::new(self.not()?))),
+            false => self.comparison(),
+        }
+    }
+
+    fn comparison(&mut self) -> CpResult<Node> {
+        let node = self.sum()?;
+        let op = match self.eat_op(&["==", "!=", "<=", ">=", "<", ">"]) {
+            Some("==") => BinaryOp::Eq,
+            Some("!=") => BinaryOp::Ne,
+            Some("<=") => BinaryOp::Lte,
+            Some(">=") => BinaryOp::Gte,
+            Some("<") => BinaryOp::Lt,
+            Some(_) => BinaryOp::Gt,
+            None => return Ok(node),
+        };
+        Ok(Node::Binary(op, Box::new(node), Box::new(self.sum()?)))
+    }
+
+    fn sum(&mut self) -> CpResult<Node> {
+        let mut node = self.product()?;
+        while let Some(op) = self.eat_op(&["+", "-"]) {
+            let op = match op {
+                "+" => ArithmeticOp::Add,
+                _ => ArithmeticOp::Sub,
+            };
+            node = Node::Binary(BinaryOp::Arithmetic(op), Box::new(node), Box::new(self.product()?));
+        }
+        Ok(node)
+    }
+
+    fn product(&mut self) -> CpResult<Node> {
+        let mut node = self.unary()?;
+        while let Some(op) = self.eat_op(&["*", "/", "%"]) {
+            let op = match op {
+                "*" => ArithmeticOp::Mul,
+                "/" => ArithmeticOp::Div,
+                _ => ArithmeticOp::Mod,
+            };
+            node = Node::Binary(BinaryOp::Arithmetic(op), Box::new(node), Box::new(self.unary()?));
+        }
+        Ok(node)
+    }
+
+    fn unary(&mut self) -> CpResult<Node> {
+        if self.eat_op(&["-"]).is_none() {
+            return self.operand();
+        }
+        // negative numbers keep their literal dtype
+        let node = match self.peek() {
+            Some(Token::Int(x)) => Node::Literal(lit(-x)),
+            Some(Token::Float(x)) => Node::Literal(lit(-x)),
+            _ => return Ok(Node::Neg(Box::new(self.unary()?))),
+        };
+        self.pos += 1;
+        Ok(node)
+    }
+
+    fn operand(&mut self) -> CpResult<Node> {
+        let offset = self.offset();
+        let Some(token) = self.peek().cloned() else {
+            return Err(self.error("unexpected end of expression"));
+        };
+        self.pos += 1;
+        match token {
+            Token::Int(x) => Ok(Node::Literal(lit(x))),
+            Token::Float(x) => Ok(Node::Literal(lit(x))),
+            Token::Str(x) => Ok(Node::Literal(lit(x))),
+            Token::Symbol(x) => Ok(Node::Symbol(x)),
+            Token::Column(x) => match parse_str_to_col_expr(&x) {
+                Some(expr) => Ok(Node::Column(expr)),
+                None => Err(parse_error(self.src, offset, &format!("invalid column `{}`", x))),
+            },
+            Token::Op("(") => {
+                let node = self.or()?;
+                self.expect_op(")")?;
+                Ok(node)
+            }
+            Token::Word(x) => match x.as_str() {
+                "true" => Ok(Node::Literal(lit(true))),
+                "false" => Ok(Node::Literal(lit(false))),
+                "null" => Ok(Node::Literal(lit(polars::prelude::Null {}))),
+                name => self.call(name, offset),
+            },
+            Token::Op(op) => Err(parse_error(self.src, offset, &format!("unexpected `{}`", op))),
+        }
+    }
+
+    fn call(&mut self, name: &str, offset: usize) -> CpResult<Node> {
+        let Some((min_args, max_args)) = function_arity(name) else {
+            return Err(parse_error(self.src, offset, &format!("unknown function `{}`", name)));
+        };
+        self.expect_op("(")?;
+        let mut args = vec![];
+        if self.eat_op(&[")"]).is_none() {
+            loop {
+                // the dtype of `cast` is a name rather than an expression
+                if name == "cast" && args.len() == 1 {
+                    let dtype_offset = self.offset();
+                    let dtype = match self.peek() {
+                        Some(Token::Column(x)) => serde_yaml_ng::from_str::<DType>(x).ok(),
+                        _ => None,
+                    }
+                    .ok_or_else(|| parse_error(self.src, dtype_offset, "expected a dtype"))?;
+                    self.pos += 1;
+                    self.expect_op(")")?;
+                    return Ok(Node::Cast(Box::new(args.remove(0)), dtype));
+                }
+                args.push(self.or()?);
+                if self.eat_op(&[")"]).is_some() {
+                    break;
+                }
+                self.expect_op(",")?;
+            }
+        }
+        if args.len() < min_args || args.len() > max_args {
+            return Err(parse_error(
+                self.src,
+                offset,
+                &format!("`{}` takes {} arguments, found {}", name, min_args, args.len()),
+            ));
+        }
+        Ok(Node::Call(name.to_owned(), args))
+    }
+}
+
+/// An inline expression such as `goals + assists * 2`, `coalesce(a, b)`,
+/// `lower(name) == 'ovechkin'` or `cast(id, uint64)`, whose `$symbols` are resolved when compiled.
+#[derive(Debug, Clone, PartialEq)]
+pub struct InlineExpr {
+    node: Node,
+}
+
+impl InlineExpr {
+    pub fn parse(src: &str) -> CpResult<InlineExpr> {
+        let mut parser = Parser {
+            src,
+            tokens: tokenize(src)?,
+            pos: 0,
+        };
+        let node = parser.or()?;
+        if parser.pos < parser.tokens.len() {
+            return Err(parser.error("unexpected trailing input"));
+        }
+        Ok(InlineExpr { node })
+    }
+
+    /// The distinct `$symbols` of the expression, in order of appearance
+    pub fn symbols(&self) -> Vec<&str> {
+        fn visit<'a>(node: &'a Node, symbols: &mut Vec<&'a str>) {
+            match node {
+                Node::Symbol(x) if !symbols.contains(&x.as_str()) => symbols.push(x),
+                Node::Symbol(_) | Node::Column(_) | Node::Literal(_) => {}
+                Node::Neg(x) | Node::Not(x) | Node::Cast(x, _) => visit(x, symbols),
+                Node::Binary(_, left, right) => {
+                    visit(left, symbols);
+                    visit(right, symbols);
+                }
+                Node::Call(_, args) => args.iter().for_each(|x| visit(x, symbols)),
+            }
+        }
+        let mut symbols = vec![];
+        visit(&self.node, &mut symbols);
+        symbols
+    }
+
+    /// Compiles to a polars expression, `resolve` yielding the expression of each `$symbol`
+    pub fn expr<F>(&self, resolve: &F) -> CpResult<Expr>
+    where
+        F: Fn(&str) -> CpResult<Expr>,
+    {
+        compile(&self.node, resolve)
+    }
+}
+
+fn compile<F>(node: &Node, resolve: &F) -> CpResult<Expr>
+where
+    F: Fn(&str) -> CpResult<Expr>,
+{
+    let keyword = |node: &Node| compile(node, resolve).map(PolarsExprKeyword::with_value);
+    match node {
+        Node::Column(x) | Node::Literal(x) => Ok(x.clone()),
+        Node::Symbol(x) => resolve(x),
+        Node::Neg(x) => Ok(lit(0) - compile(x, resolve)?),
+        Node::Not(x) => Ok(compile(x, resolve)?.not()),
+        Node::Cast(x, dtype) => CastAction {
+            col: keyword(x)?,
+            dtype: dtype.clone(),
+        }
+        .expr(),
+        Node::Binary(BinaryOp::Arithmetic(op), left, right) => ArithmeticAction {
+            op: *op,
+            operands: vec![keyword(left)?, keyword(right)?],
+        }
+        .expr(),
+        Node::Binary(op, left, right) => {
+            let (left, right) = (compile(left, resolve)?, compile(right, resolve)?);
+            Ok(match op {
+                BinaryOp::Or => left.or(right),
+                BinaryOp::And => left.and(right),
+                BinaryOp::Eq => left.eq(right),
+                BinaryOp::Ne => left.neq(right),
+                BinaryOp::Lt => left.lt(right),
+                BinaryOp::Lte => left.lt_eq(right),
+                BinaryOp::Gt => left.gt(right),
+                BinaryOp::Gte => left.gt_eq(right),
+                BinaryOp::Arithmetic(_) => unreachable!("matched above"),
+            })
+        }
+        Node::Call(name, args) => {
+            let args = args.iter().map(keyword).collect::<CpResult<Vec<_>>>()?;
+            let mut args = args.into_iter();
+            match name.as_str() {
+                "coalesce" => CoalesceAction {
+                    columns: args.collect(),
+                }
+                .expr(),
+                "lower" | "upper" | "strip" => StrCaseAction {
+                    op: match name.as_str() {
+                        "lower" => StrCaseOp::Lower,
+                        "upper" => StrCaseOp::Upper,
+                        _ => StrCaseOp::Strip,
+                    },
+                    col: args.next().expect("arity checked when parsing"),
+                }
+                .expr(),
+                "fill_null" => FillNullAction {
+                    col: args.next().expect("arity checked when parsing"),
+                    value: args.next().expect("arity checked when parsing"),
+                }
+                .expr(),
+                "is_null" => Ok(args.next().and_then(|x| x.value().cloned()).expect("is_null").is_null()),
+                _ => Ok(args
+                    .next()
+                    .and_then(|x| x.value().cloned())
+                    .expect("is_not_null")
+                    .is_not_null()),
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use polars::prelude::*;
+
+    use crate::util::error::CpError;
+
+    use super::InlineExpr;
+
+    fn compile(src: &str) -> Expr {
+        InlineExpr::parse(src)
+            .unwrap()
+            .expr(&|symbol: &str| Ok(col(symbol)))
+            .unwrap()
+    }
+
+    #[test]
+    fn valid_inline_expr_symbols() {
+        let inline = InlineExpr::parse("coalesce($b, a) + $c * $b").unwrap();
+        assert_eq!(inline.symbols(), vec!["b", "c"]);
+        assert!(InlineExpr::parse("a + 1").unwrap().symbols().is_empty());
+    }
+
+    #[test]
+    fn valid_inline_expr_precedence() {
+        assert_eq!(
+            compile("goals + assists * 2"),
+            col("goals") + col("assists") * lit(2i64)
+        );
+        assert_eq!(
+            compile("(goals - assists) % 3 >= 1 or not is_null(name.first)"),
+            ((col("goals") - col("assists")) % lit(3i64))
+                .gt_eq(lit(1i64))
+                .or(col("name").struct_().field_by_name("first").is_null().not())
+        );
+        assert_eq!(compile("a == $b and c"), col("a").eq(col("b")).and(col("c")));
+    }
+
+    #[test]
+    fn valid_inline_expr_run() {
+        let df = df!(
+            "name" => [Some("Ovechkin"), Some("Crosby"), None],
+            "nick" => [None, Some("Sid"), Some("Gr8")],
+            "id" => ["8471214", "8471675", "8478402"],
+            "goals" => [3, 1, 0],
+            "assists" => [1, 2, 0],
+        )
+        .unwrap()
+        .with_column(Series::new(
+            "games".into(),
+            [
+                Series::new("".into(), [1i64, 2]),
+                Series::new("".into(), [3i64]),
+                Series::new_empty("".into(), &DataType::Int64),
+            ],
+        ))
+        .unwrap()
+        .clone();
+        let actual = df
+            .lazy()
+            .select([
+                compile("goals + assists * 2").alias("points"),
+                compile("coalesce(nick, name)").alias("display"),
+                compile("lower(name) == 'ovechkin'").alias("is_ovi"),
+                compile("cast(id, uint64)").alias("id"),
+                compile("goals / 2 - 1.5").alias("ratio"),
+                compile("fill_null(`games[-1]`, -1)").alias("last_game"),
+            ])
+            .collect()
+            .unwrap();
+        let expected = df!(
+            "points" => [5i64, 5, 0],
+            "display" => ["Ovechkin", "Sid", "Gr8"],
+            "is_ovi" => [Some(true), Some(false), None],
+            "id" => [8471214u64, 8471675, 8478402],
+            "ratio" => [0.0, -1.0, -1.5],
+            "last_game" => [2i64, 3, -1],
+        )
+        .unwrap();
+        assert_eq!(actual, expected);
+    }
+
+    #[test]
+    fn invalid_inline_expr_offset() {
+        let cases = [
+            ("goals + * 2", 8),
+            ("coalesce(a, b", 13),
+            ("lower(a, b)", 0),
+            ("median(a)", 0),
+            ("cast(id, int128)", 9),
+            ("a == 'x", 5),
+            ("a + b)", 5),
+            ("a ? b", 2),
+        ];
+        for (src, offset) in cases {
+            match InlineExpr::parse(src) {
+                Err(CpError::ConfigError(_, msg)) => {
+                    assert!(msg.contains(&format!("at offset {}\n", offset)), "{}: {}", src, msg);
+                    assert!(msg.ends_with(&format!("{}^", " ".repeat(offset))), "{}: {}", src, msg);
+                }
+                x => panic!("{}: {:?}", src, x),
+            }
+        }
+    }
+}
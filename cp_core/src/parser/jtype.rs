@@ -1,4 +1,4 @@
-use polars::prelude::JoinType;
+use polars::prelude::{AsOfOptions, JoinType};
 use serde::{Deserialize, Deserializer, Serialize, de};
 
 #[derive(Clone, Debug, PartialEq, Eq)]
@@ -15,8 +15,11 @@ impl Serialize for JType {
     where
         S: serde::Serializer,
     {
-        let enum_type = &self.0;
-        let repr = format!("{:?}", enum_type).trim().to_lowercase().to_owned();
+        let repr = match &self.0 {
+            // the options are configured next to `how`
+            JoinType::AsOf(_) => "asof".to_owned(),
+            enum_type => format!("{:?}", enum_type).trim().to_lowercase().to_owned(),
+        };
         serializer.serialize_str(&repr)
     }
 }
@@ -33,6 +36,9 @@ impl<'de> Deserialize<'de> for JType {
             "full" => Ok(JType(JoinType::Full)),
             "cross" => Ok(JType(JoinType::Cross)),
             "inner" => Ok(JType(JoinType::Inner)),
+            "semi" => Ok(JType(JoinType::Semi)),
+            "anti" => Ok(JType(JoinType::Anti)),
+            "asof" => Ok(JType(JoinType::AsOf(AsOfOptions::default()))),
             s => Err(de::Error::custom(format!("Unknown jointype in model: {}", s))),
         }
     }
@@ -40,7 +46,7 @@ impl<'de> Deserialize<'de> for JType {
 
 #[cfg(test)]
 mod tests {
-    use polars::prelude::JoinType;
+    use polars::prelude::{AsOfOptions, JoinType};
 
     use super::JType;
 
@@ -51,6 +57,9 @@ mod tests {
             JoinType::Full,
             JoinType::Cross,
             JoinType::Inner,
+            JoinType::Semi,
+            JoinType::Anti,
+            JoinType::AsOf(AsOfOptions::default()),
         ]
         .map(JType)
         .into_iter()
@@ -58,7 +67,7 @@ mod tests {
     }
 
     fn example_str() -> Vec<String> {
-        ["left", "right", "full", "cross", "inner"]
+        ["left", "right", "full", "cross", "inner", "semi", "anti", "asof"]
             .map(|x| x.to_owned())
             .into_iter()
             .collect::<Vec<_>>()
@@ -5,9 +5,15 @@ use serde::{Deserialize, Deserializer, Serialize, de, ser};
 
 use crate::parser::dtype::DType;
 use crate::util::error::{CpError, CpResult};
-use crate::{model::common::ModelFieldInfo, parser::expr::parse_str_to_col_expr};
+use crate::{
+    model::common::ModelFieldInfo,
+    parser::{expr::parse_str_to_col_expr, inline::InlineExpr},
+};
 
-use super::action::{ConcatAction, ExprAction, FormatAction};
+use super::action::{
+    ArithmeticAction, ArithmeticOp, CastAction, CoalesceAction, ConcatAction, ExprAction, FillNullAction, FormatAction,
+    ReplaceAction, SliceAction, SplitAction, StrCaseAction, StrCaseOp, WhenAction,
+};
 
 /// The keyword trait is shared by task configuration "keywords" which contain either a value or a
 /// symbol to be replaced by a value in the stage config (which invoke the task, which invokes the
@@ -39,11 +45,22 @@ impl Hash for StrKeyword {
     }
 }
 
-/// Keyword that yields a polars expression. Valid expressions are strings or actions (parsed from map).
+/// Keyword that yields a polars expression. Valid expressions are strings (column paths, or inline
+/// expressions prefixed with `=` such as `=goals + assists * 2`) or actions (parsed from map).
 #[derive(Debug, Clone, PartialEq, Eq)]
 pub struct PolarsExprKeyword {
+    // for an inline expression, its unresolved symbols, comma separated
     symbol: Option<String>,
     value: Option<Expr>,
+    // inline expression compiled once its symbols are replaced
+    inline: Option<String>,
+}
+
+/// Keyword that yields an integer, such as a row count.
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+pub struct IntKeyword {
+    symbol: Option<String>,
+    value: Option<i64>,
 }
 
 /// Keyword that yields a column definition expression. Valid expressions are dtype or complete field info (parsed from map).
@@ -102,6 +119,24 @@ impl<'de> Deserialize<'de> for PolarsExprKeyword {
             Full(HashMap<String, serde_yaml_ng::Value>),
         }
         match Helper::deserialize(deserializer)? {
+            Helper::Short(full) if full.trim().starts_with('=') => {
+                let src = full.trim()[1..].trim();
+                let inline = InlineExpr::parse(src).map_err(|e| de::Error::custom(e.to_string()))?;
+                let symbols = inline.symbols();
+                if !symbols.is_empty() {
+                    return Ok(PolarsExprKeyword {
+                        symbol: Some(symbols.join(", ")),
+                        value: None,
+                        inline: Some(src.to_owned()),
+                    });
+                }
+                match inline
+                    .expr(&|symbol: &str| Err(CpError::SymbolMissingValueError("inline expression", symbol.to_owned())))
+                {
+                    Ok(x) => Ok(PolarsExprKeyword::with_value(x)),
+                    Err(e) => Err(de::Error::custom(format!("Bad inline expression: {}", e))),
+                }
+            }
             Helper::Short(full) => {
                 let chars = full.chars().collect::<Vec<_>>();
                 match chars.first() {
@@ -129,6 +164,33 @@ impl<'de> Deserialize<'de> for PolarsExprKeyword {
                 let action: Result<CpResult<Expr>, serde_yaml_ng::Error> = match action_name.as_str() {
                     "format" => serde_yaml_ng::from_value::<FormatAction>(action_args).map(|x| x.expr()),
                     "concat" => serde_yaml_ng::from_value::<ConcatAction>(action_args).map(|x| x.expr()),
+                    "cast" => serde_yaml_ng::from_value::<CastAction>(action_args).map(|x| x.expr()),
+                    "add" | "sub" | "mul" | "div" | "mod" => {
+                        let op = match action_name.as_str() {
+                            "add" => ArithmeticOp::Add,
+                            "sub" => ArithmeticOp::Sub,
+                            "mul" => ArithmeticOp::Mul,
+                            "div" => ArithmeticOp::Div,
+                            _ => ArithmeticOp::Mod,
+                        };
+                        serde_yaml_ng::from_value::<Vec<PolarsExprKeyword>>(action_args)
+                            .map(|operands| ArithmeticAction { op, operands }.expr())
+                    }
+                    "coalesce" => serde_yaml_ng::from_value::<CoalesceAction>(action_args).map(|x| x.expr()),
+                    "when" => serde_yaml_ng::from_value::<WhenAction>(action_args).map(|x| x.expr()),
+                    "fill_null" => serde_yaml_ng::from_value::<FillNullAction>(action_args).map(|x| x.expr()),
+                    "lower" | "upper" | "strip" => {
+                        let op = match action_name.as_str() {
+                            "lower" => StrCaseOp::Lower,
+                            "upper" => StrCaseOp::Upper,
+                            _ => StrCaseOp::Strip,
+                        };
+                        serde_yaml_ng::from_value::<PolarsExprKeyword>(action_args)
+                            .map(|col| StrCaseAction { op, col }.expr())
+                    }
+                    "replace" => serde_yaml_ng::from_value::<ReplaceAction>(action_args).map(|x| x.expr()),
+                    "slice" => serde_yaml_ng::from_value::<SliceAction>(action_args).map(|x| x.expr()),
+                    "split" => serde_yaml_ng::from_value::<SplitAction>(action_args).map(|x| x.expr()),
                     "int8" => serde_yaml_ng::from_value::<i8>(action_args).map(|x| Ok(lit(x))),
                     "int16" => serde_yaml_ng::from_value::<i16>(action_args).map(|x| Ok(lit(x))),
                     "int32" => serde_yaml_ng::from_value::<i32>(action_args).map(|x| Ok(lit(x))),
@@ -137,6 +199,8 @@ impl<'de> Deserialize<'de> for PolarsExprKeyword {
                     "uint16" => serde_yaml_ng::from_value::<u16>(action_args).map(|x| Ok(lit(x))),
                     "uint32" => serde_yaml_ng::from_value::<u32>(action_args).map(|x| Ok(lit(x))),
                     "uint64" => serde_yaml_ng::from_value::<u64>(action_args).map(|x| Ok(lit(x))),
+                    "float32" => serde_yaml_ng::from_value::<f32>(action_args).map(|x| Ok(lit(x))),
+                    "float64" => serde_yaml_ng::from_value::<f64>(action_args).map(|x| Ok(lit(x))),
                     "bool" => serde_yaml_ng::from_value::<bool>(action_args).map(|x| Ok(lit(x))),
                     "str" => serde_yaml_ng::from_value::<String>(action_args).map(|x| Ok(lit(x))),
                     x => {
@@ -226,12 +290,14 @@ impl Keyword<'_, Expr> for PolarsExprKeyword {
         Self {
             symbol: None,
             value: Some(value),
+            inline: None,
         }
     }
     fn with_symbol(symbol: &str) -> Self {
         Self {
             symbol: Some(symbol.to_owned()),
             value: None,
+            inline: None,
         }
     }
     fn value(&'_ self) -> Option<&'_ Expr> {
@@ -252,6 +318,78 @@ impl Keyword<'_, Expr> for PolarsExprKeyword {
             log::debug!("value already exists: {:?}", self.value());
             return Ok(());
         }
+        if let Some(inline) = self.inline.as_ref() {
+            let expr = InlineExpr::parse(inline)?.expr(&|symbol: &str| expr_from_context(symbol, context))?;
+            let _ = self.value.insert(expr);
+            return Ok(());
+        }
+        let symbol = self.symbol().expect("no symbol or value");
+        let _ = self.value.insert(expr_from_context(symbol, context)?);
+        Ok(())
+    }
+}
+
+/// The expression of a symbol's value in the context, itself parsed as a `PolarsExprKeyword`
+fn expr_from_context(symbol: &str, context: &serde_yaml_ng::Mapping) -> CpResult<Expr> {
+    let value = match context.get(symbol) {
+        Some(x) => x,
+        None => {
+            return Err(CpError::ConfigError(
+                "value not found for variable",
+                format!("value of `{}` not found in context: {:?}", symbol, context),
+            ));
+        }
+    };
+    match serde_yaml_ng::from_value::<PolarsExprKeyword>(value.clone()) {
+        Ok(x) => match x.value() {
+            Some(value) => Ok(value.clone()),
+            None => Err(CpError::ConfigError(
+                "invalid value",
+                format!("substituted value of `{}: {:?}` doesn't exist", symbol, x),
+            )),
+        },
+        Err(e) => Err(CpError::ConfigError(
+            "invalid value",
+            format!(
+                "value of `{}: {:?}` is not string or otherwise invalid: {:?}",
+                symbol, value, e
+            ),
+        )),
+    }
+}
+
+impl Keyword<'_, ModelFieldInfo> for ModelFieldKeyword {
+    fn with_value(value: ModelFieldInfo) -> Self {
+        Self {
+            symbol: None,
+            value: Some(value),
+        }
+    }
+    fn with_symbol(symbol: &str) -> Self {
+        Self {
+            symbol: Some(symbol.to_owned()),
+            value: None,
+        }
+    }
+    fn value(&'_ self) -> Option<&'_ ModelFieldInfo> {
+        self.value.as_ref()
+    }
+    fn symbol(&'_ self) -> Option<&str> {
+        self.symbol.as_deref()
+    }
+    fn and_symbol(mut self, symbol: &str) -> Self {
+        let _ = self.symbol.insert(symbol.to_owned());
+        self
+    }
+    fn insert_value(&mut self, value: ModelFieldInfo) {
+        let _ = self.value.insert(value);
+    }
+    fn insert_value_from_context(&mut self, context: &serde_yaml_ng::Mapping) -> CpResult<()> {
+        if self.value().is_some() {
+            log::debug!("value already exists: {:?}", self.value());
+            return Ok(());
+        }
+        log::debug!("symbol: {:?}", self.symbol());
         let symbol = self.symbol().expect("no symbol or value");
         let value = match context.get(symbol) {
             Some(x) => x,
@@ -262,7 +400,7 @@ impl Keyword<'_, Expr> for PolarsExprKeyword {
                 ));
             }
         };
-        match serde_yaml_ng::from_value::<PolarsExprKeyword>(value.clone()) {
+        match serde_yaml_ng::from_value::<ModelFieldKeyword>(value.clone()) {
             Ok(x) => match x.value() {
                 Some(value) => {
                     let _ = self.value.insert(value.clone());
@@ -288,8 +426,8 @@ impl Keyword<'_, Expr> for PolarsExprKeyword {
     }
 }
 
-impl Keyword<'_, ModelFieldInfo> for ModelFieldKeyword {
-    fn with_value(value: ModelFieldInfo) -> Self {
+impl Keyword<'_, i64> for IntKeyword {
+    fn with_value(value: i64) -> Self {
         Self {
             symbol: None,
             value: Some(value),
@@ -301,7 +439,7 @@ impl Keyword<'_, ModelFieldInfo> for ModelFieldKeyword {
             value: None,
         }
     }
-    fn value(&'_ self) -> Option<&'_ ModelFieldInfo> {
+    fn value(&'_ self) -> Option<&'_ i64> {
         self.value.as_ref()
     }
     fn symbol(&'_ self) -> Option<&str> {
@@ -311,7 +449,7 @@ impl Keyword<'_, ModelFieldInfo> for ModelFieldKeyword {
         let _ = self.symbol.insert(symbol.to_owned());
         self
     }
-    fn insert_value(&mut self, value: ModelFieldInfo) {
+    fn insert_value(&mut self, value: i64) {
         let _ = self.value.insert(value);
     }
     fn insert_value_from_context(&mut self, context: &serde_yaml_ng::Mapping) -> CpResult<()> {
@@ -319,7 +457,6 @@ impl Keyword<'_, ModelFieldInfo> for ModelFieldKeyword {
             log::debug!("value already exists: {:?}", self.value());
             return Ok(());
         }
-        log::debug!("symbol: {:?}", self.symbol());
         let symbol = self.symbol().expect("no symbol or value");
         let value = match context.get(symbol) {
             Some(x) => x,
@@ -330,25 +467,14 @@ impl Keyword<'_, ModelFieldInfo> for ModelFieldKeyword {
                 ));
             }
         };
-        match serde_yaml_ng::from_value::<ModelFieldKeyword>(value.clone()) {
-            Ok(x) => match x.value() {
-                Some(value) => {
-                    let _ = self.value.insert(value.clone());
-                }
-                None => {
-                    return Err(CpError::ConfigError(
-                        "invalid value",
-                        format!("substituted value of `{}: {:?}` doesn't exist", symbol, x),
-                    ));
-                }
-            },
+        match serde_yaml_ng::from_value::<i64>(value.clone()) {
+            Ok(x) => {
+                let _ = self.value.insert(x);
+            }
             Err(e) => {
                 return Err(CpError::ConfigError(
                     "invalid value",
-                    format!(
-                        "value of `{}: {:?}` is not string or otherwise invalid: {:?}",
-                        symbol, value, e
-                    ),
+                    format!("value of `{}: {:?}` is not an integer: {:?}", symbol, value, e),
                 ));
             }
         }
@@ -387,11 +513,36 @@ impl<'de> Deserialize<'de> for StrKeyword {
     }
 }
 
+impl<'de> Deserialize<'de> for IntKeyword {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: serde::Deserializer<'de>,
+    {
+        #[derive(Deserialize)]
+        #[serde(untagged)]
+        enum Helper {
+            Value(i64),
+            Symbol(StrKeyword),
+        }
+        match Helper::deserialize(deserializer)? {
+            Helper::Value(x) => Ok(IntKeyword::with_value(x)),
+            Helper::Symbol(str_kw) => match str_kw.symbol() {
+                Some(sym) => Ok(IntKeyword::with_symbol(sym)),
+                None => Err(de::Error::custom(format!(
+                    "Integer keyword is not an integer or symbol: {:?}",
+                    str_kw
+                ))),
+            },
+        }
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use std::collections::HashMap;
 
-    use polars::prelude::{DataType, col, concat_str, format_str, lit};
+    use polars::df;
+    use polars::prelude::{DataType, IntoLazy, Operator, binary_expr, coalesce, col, concat_str, format_str, lit};
     use serde::{Deserialize, Serialize};
 
     use crate::{
@@ -399,7 +550,7 @@ mod tests {
         parser::{dtype::DType, model::ModelConstraint},
     };
 
-    use super::{Keyword, ModelFieldKeyword, PolarsExprKeyword, StrKeyword};
+    use super::{IntKeyword, Keyword, ModelFieldKeyword, PolarsExprKeyword, StrKeyword};
 
     #[derive(Debug, Serialize, Deserialize, PartialEq, Eq)]
     struct StrKeywordExample {
@@ -448,6 +599,28 @@ mod tests {
         assert!(serde_yaml_ng::from_str::<StrKeywordExample>("symbol: \"\n\", value: value").is_err());
     }
 
+    #[test]
+    fn int_keyword_de() {
+        let actual: IntKeyword = serde_yaml_ng::from_str("-3").unwrap();
+        assert_eq!(actual, IntKeyword::with_value(-3));
+        let mut actual: IntKeyword = serde_yaml_ng::from_str("$n").unwrap();
+        let context = serde_yaml_ng::from_str::<serde_yaml_ng::Mapping>("{n: 2, x: 2.5, s: abc}").unwrap();
+        actual.insert_value_from_context(&context).unwrap();
+        assert_eq!(actual.value(), Some(&2));
+        assert!(
+            IntKeyword::with_symbol("x")
+                .insert_value_from_context(&context)
+                .is_err()
+        );
+        assert!(
+            IntKeyword::with_symbol("s")
+                .insert_value_from_context(&context)
+                .is_err()
+        );
+        assert!(serde_yaml_ng::from_str::<IntKeyword>("1.5").is_err());
+        assert!(serde_yaml_ng::from_str::<IntKeyword>("ten").is_err());
+    }
+
     #[test]
     fn pl_expr_keyword_str_de() {
         let myconfig = "{symbol: $mysymb, simple: test, complex: test.another}";
@@ -518,6 +691,88 @@ concat:
         assert_eq!(actions[2].value().unwrap(), &lit("test"));
     }
 
+    #[test]
+    fn pl_expr_keyword_cast_arithmetic_action_de() {
+        let action_config = "
+- cast: { col: id, dtype: uint64 }
+- add: [goals, { mul: [assists, { int64: 2 }] }]
+- div: [a, b, c]
+- mod: [a, { int32: 3 }]
+- coalesce: [nickname, name.first]
+- fill_null: { col: price, value: { float64: 0.5 } }
+";
+        let actions: Vec<PolarsExprKeyword> = serde_yaml_ng::from_str(action_config).unwrap();
+        let expected = [
+            col("id").cast(DataType::UInt64),
+            col("goals") + col("assists") * lit(2i64),
+            binary_expr(
+                binary_expr(col("a"), Operator::TrueDivide, col("b")),
+                Operator::TrueDivide,
+                col("c"),
+            ),
+            col("a") % lit(3i32),
+            coalesce(&[col("nickname"), col("name").struct_().field_by_name("first")]),
+            col("price").fill_null(lit(0.5f64)),
+        ];
+        for (action, expected) in actions.iter().zip(expected) {
+            assert_eq!(action.value().unwrap(), &expected);
+        }
+    }
+
+    #[test]
+    fn pl_expr_keyword_when_str_action_run() {
+        let action_config = "
+tier:
+    when:
+        cond: { scores: { gte: { int32: 40 } } }
+        then: { str: high }
+        otherwise:
+            when:
+                cond: { scores: { lt: { int32: 0 } } }
+                then: { str: negative }
+                otherwise: { str: low }
+lower: { lower: name }
+upper: { upper: name }
+strip: { strip: padded }
+replace: { replace: { col: name, pattern: '[aeiou]', value: _ } }
+slice: { slice: { col: name, offset: -3, length: 2 } }
+split: { split: { col: padded, by: ' ' } }
+";
+        let actions: HashMap<String, PolarsExprKeyword> = serde_yaml_ng::from_str(action_config).unwrap();
+        let df = df![
+            "scores" => [43, -7, 12],
+            "name" => ["Darren", "Hunter", "Varya"],
+            "padded" => [" a b ", "c", "d e"],
+        ]
+        .unwrap();
+        let actual = df
+            .lazy()
+            .select(
+                ["tier", "lower", "upper", "strip", "replace", "slice", "split"]
+                    .map(|x| actions.get(x).unwrap().value().unwrap().clone().alias(x)),
+            )
+            .collect()
+            .unwrap();
+        let expected = df![
+            "tier" => ["high", "negative", "low"],
+            "lower" => ["darren", "hunter", "varya"],
+            "upper" => ["DARREN", "HUNTER", "VARYA"],
+            "strip" => ["a b", "c", "d e"],
+            "replace" => ["D_rr_n", "H_nt_r", "V_ry_"],
+            "slice" => ["re", "te", "ry"],
+        ]
+        .unwrap();
+        assert_eq!(actual.drop("split").unwrap(), expected);
+        let split = actual
+            .column("split")
+            .unwrap()
+            .list()
+            .unwrap()
+            .get_as_series(2)
+            .unwrap();
+        assert_eq!(split.str().unwrap().len(), 2);
+    }
+
     #[test]
     fn pl_expr_keyword_invalid_action_de() {
         [
@@ -540,11 +795,66 @@ format:
     columns: [format, is, invalid, here, too, few, brackets]
 ",
             "[not, a, struct, or, str]",
+            "cast: { col: id, dtype: notadtype }",
+            "add: [only_one]",
+            "coalesce: []",
+            "when: { cond: { a: { gt: $b } }, then: c }",
+            "slice: { col: name }",
         ]
         .iter()
         .for_each(|x| assert!(serde_yaml_ng::from_str::<PolarsExprKeyword>(x).is_err()));
     }
 
+    #[test]
+    fn pl_expr_keyword_inline_de() {
+        let actual: PolarsExprKeyword = serde_yaml_ng::from_str("=goals + assists * 2").unwrap();
+        assert_eq!(
+            actual,
+            PolarsExprKeyword::with_value(col("goals") + col("assists") * lit(2i64))
+        );
+        // symbols are resolved when emplaced, as column paths, actions or inline expressions
+        let mut actual: PolarsExprKeyword =
+            serde_yaml_ng::from_str("= coalesce(name.first, $fallback) == $who").unwrap();
+        assert!(actual.value().is_none());
+        assert_eq!(actual.symbol(), Some("fallback, who"));
+        let context =
+            serde_yaml_ng::from_str::<serde_yaml_ng::Mapping>("{ fallback: '=lower(nick)', who: { str: ovechkin } }")
+                .unwrap();
+        actual.insert_value_from_context(&context).unwrap();
+        assert_eq!(
+            actual.value().unwrap(),
+            &coalesce(&[
+                col("name").struct_().field_by_name("first"),
+                col("nick").str().to_lowercase()
+            ])
+            .eq(lit("ovechkin"))
+        );
+        let mut missing: PolarsExprKeyword = serde_yaml_ng::from_str("=a + $b").unwrap();
+        assert!(
+            missing
+                .insert_value_from_context(&serde_yaml_ng::Mapping::new())
+                .is_err()
+        );
+        let error = serde_yaml_ng::from_str::<PolarsExprKeyword>("=lower(name) == 'ovechkin").unwrap_err();
+        assert!(error.to_string().contains("unterminated string at offset 15"));
+    }
+
+    #[test]
+    fn pl_expr_keyword_column_name_de() {
+        // without `=`, strings are column paths however they are spelled
+        for name in [
+            "pts/gp",
+            "save%",
+            "w-l (ot)",
+            "Goals and Assists",
+            "a + b",
+            "coalesce(a, b)",
+        ] {
+            let actual: PolarsExprKeyword = serde_yaml_ng::from_str(&format!("'{}'", name)).unwrap();
+            assert_eq!(actual, PolarsExprKeyword::with_value(col(name)), "{}", name);
+        }
+    }
+
     #[test]
     fn valid_hash_str_keyword() {
         let mut map = HashMap::<StrKeyword, usize>::new();
@@ -5,6 +5,7 @@ pub enum MergeTypeEnum {
     Insert,
     Replace,
     MakeNext,
+    Upsert,
 }
 
 impl<'de> Deserialize<'de> for MergeTypeEnum {
@@ -20,6 +21,7 @@ impl<'de> Deserialize<'de> for MergeTypeEnum {
             "make next" => Ok(MergeTypeEnum::MakeNext),
             "make_next" => Ok(MergeTypeEnum::MakeNext),
             "makenext" => Ok(MergeTypeEnum::MakeNext),
+            "upsert" => Ok(MergeTypeEnum::Upsert),
             s => Err(de::Error::custom(format!("Unknown sink merge_type: {}", s))),
         }
     }
@@ -39,6 +41,8 @@ mod tests {
             MergeTypeEnum::MakeNext,
             MergeTypeEnum::MakeNext,
             MergeTypeEnum::MakeNext,
+            MergeTypeEnum::Upsert,
+            MergeTypeEnum::Upsert,
         ]
         .into_iter()
         .collect::<Vec<_>>()
@@ -54,6 +58,8 @@ mod tests {
             "make_next",
             "Make Next",
             "MakeNext",
+            "upsert",
+            "UPSERT",
         ]
         .into_iter()
         .collect::<Vec<_>>()
@@ -61,11 +67,16 @@ mod tests {
 
     #[test]
     fn valid_merge_type_ser() {
-        let actual_str = [MergeTypeEnum::Insert, MergeTypeEnum::Replace, MergeTypeEnum::MakeNext]
-            .iter()
-            .map(|f| serde_yaml_ng::to_string(f).map(|x| x.trim().to_owned()).unwrap())
-            .collect::<Vec<_>>();
-        assert_eq!(actual_str, vec!["Insert", "Replace", "MakeNext"]);
+        let actual_str = [
+            MergeTypeEnum::Insert,
+            MergeTypeEnum::Replace,
+            MergeTypeEnum::MakeNext,
+            MergeTypeEnum::Upsert,
+        ]
+        .iter()
+        .map(|f| serde_yaml_ng::to_string(f).map(|x| x.trim().to_owned()).unwrap())
+        .collect::<Vec<_>>();
+        assert_eq!(actual_str, vec!["Insert", "Replace", "MakeNext", "Upsert"]);
     }
 
     #[test]
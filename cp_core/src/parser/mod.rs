@@ -3,8 +3,10 @@ pub mod common;
 pub mod connection;
 pub mod dtype;
 pub mod expr;
+pub mod filter;
 pub mod filter_fields;
 pub mod http;
+pub mod inline;
 pub mod jtype;
 pub mod keyword;
 pub mod logger;
@@ -13,3 +15,4 @@ pub mod model;
 pub mod run_mode;
 pub mod sql_connection;
 pub mod task_type;
+pub mod template;
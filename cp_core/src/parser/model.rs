@@ -25,9 +25,59 @@ impl<'de> Deserialize<'de> for ModelConstraint {
     }
 }
 
+/// What a stage does with rows that violate its model's constraints.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+pub enum ConstraintPolicy {
+    Error,
+    Warn,
+    DropViolatingRows,
+}
+
+impl<'de> Deserialize<'de> for ConstraintPolicy {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: serde::Deserializer<'de>,
+    {
+        let s = String::deserialize(deserializer)?.to_lowercase();
+        match s.as_str() {
+            "error" => Ok(ConstraintPolicy::Error),
+            "warn" => Ok(ConstraintPolicy::Warn),
+            "drop_violating_rows" => Ok(ConstraintPolicy::DropViolatingRows),
+            "drop violating rows" => Ok(ConstraintPolicy::DropViolatingRows),
+            "drop" => Ok(ConstraintPolicy::DropViolatingRows),
+            s => Err(de::Error::custom(format!("Unknown constraint_policy: {}", s))),
+        }
+    }
+}
+
+/// What a source does when the schema it reads drifts from its model.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+pub enum SchemaDriftPolicy {
+    Error,
+    Warn,
+    Ignore,
+    Evolve,
+}
+
+impl<'de> Deserialize<'de> for SchemaDriftPolicy {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: serde::Deserializer<'de>,
+    {
+        let s = String::deserialize(deserializer)?.to_lowercase();
+        match s.as_str() {
+            "error" => Ok(SchemaDriftPolicy::Error),
+            "warn" => Ok(SchemaDriftPolicy::Warn),
+            "ignore" => Ok(SchemaDriftPolicy::Ignore),
+            "evolve" => Ok(SchemaDriftPolicy::Evolve),
+            s => Err(de::Error::custom(format!("Unknown on_schema_drift: {}", s))),
+        }
+    }
+}
+
 #[cfg(test)]
 mod tests {
-    use super::ModelConstraint;
+    use super::{ConstraintPolicy, ModelConstraint, SchemaDriftPolicy};
 
     fn example_constraint() -> Vec<ModelConstraint> {
         [
@@ -72,4 +122,41 @@ mod tests {
             .collect::<Vec<_>>();
         assert_eq!(actual_contraint, example_constraint());
     }
+
+    #[test]
+    fn valid_constraint_policy_de() {
+        let actual = ["error", "WARN", "drop_violating_rows", "Drop Violating Rows", "drop"]
+            .iter()
+            .map(|x| serde_yaml_ng::from_str::<ConstraintPolicy>(x).unwrap())
+            .collect::<Vec<_>>();
+        assert_eq!(
+            actual,
+            vec![
+                ConstraintPolicy::Error,
+                ConstraintPolicy::Warn,
+                ConstraintPolicy::DropViolatingRows,
+                ConstraintPolicy::DropViolatingRows,
+                ConstraintPolicy::DropViolatingRows,
+            ]
+        );
+        assert!(serde_yaml_ng::from_str::<ConstraintPolicy>("ignore").is_err());
+    }
+
+    #[test]
+    fn valid_schema_drift_policy_de() {
+        let actual = ["error", "Warn", "IGNORE", "evolve"]
+            .iter()
+            .map(|x| serde_yaml_ng::from_str::<SchemaDriftPolicy>(x).unwrap())
+            .collect::<Vec<_>>();
+        assert_eq!(
+            actual,
+            vec![
+                SchemaDriftPolicy::Error,
+                SchemaDriftPolicy::Warn,
+                SchemaDriftPolicy::Ignore,
+                SchemaDriftPolicy::Evolve,
+            ]
+        );
+        assert!(serde_yaml_ng::from_str::<SchemaDriftPolicy>("drop").is_err());
+    }
 }
@@ -0,0 +1,205 @@
+use serde_yaml_ng::Value;
+
+use crate::{
+    context::envvar::get_env_var_str,
+    util::error::{CpError, CpResult},
+};
+
+const TEMPLATE_OPEN: &str = "{{";
+const TEMPLATE_CLOSE: &str = "}}";
+const TEMPLATE_RAW_FILTER: &str = "raw";
+
+/// Quotes a scalar as a SQL literal: strings are single-quoted with embedded quotes doubled,
+/// numbers and booleans are written as is, and lists become `(a, b, ..)` for use with `IN`.
+fn sql_literal(symbol: &str, value: &Value) -> CpResult<String> {
+    match value {
+        Value::Null => Ok("NULL".to_owned()),
+        Value::Bool(x) => Ok(x.to_string()),
+        Value::Number(x) => Ok(x.to_string()),
+        Value::String(x) => Ok(format!("'{}'", x.replace('\'', "''"))),
+        Value::Sequence(xs) => Ok(format!(
+            "({})",
+            xs.iter()
+                .map(|x| sql_literal(symbol, x))
+                .collect::<CpResult<Vec<_>>>()?
+                .join(", ")
+        )),
+        x => Err(CpError::ConfigError(
+            "invalid template value",
+            format!("value of `{}` cannot be written as a sql literal: {:?}", symbol, x),
+        )),
+    }
+}
+
+fn raw_string(symbol: &str, value: &Value) -> CpResult<String> {
+    match value {
+        Value::Null => Ok("".to_owned()),
+        Value::Bool(x) => Ok(x.to_string()),
+        Value::Number(x) => Ok(x.to_string()),
+        Value::String(x) => Ok(x.to_owned()),
+        x => Err(CpError::ConfigError(
+            "invalid template value",
+            format!("value of `{}` is not a scalar: {:?}", symbol, x),
+        )),
+    }
+}
+
+/// Looks up the symbol in the stage context, falling back to the environment variables
+/// (e.g. `REF_DATE`) which are stored as yaml.
+fn template_value(symbol: &str, context: &serde_yaml_ng::Mapping) -> CpResult<Value> {
+    if let Some(x) = context.get(symbol) {
+        return Ok(x.clone());
+    }
+    match get_env_var_str(symbol) {
+        Ok(x) => Ok(serde_yaml_ng::from_str::<Value>(&x).unwrap_or(Value::String(x))),
+        Err(_) => Err(CpError::ConfigError(
+            "value not found for variable",
+            format!(
+                "value of `{}` not found in context or environment: {:?}",
+                symbol, context
+            ),
+        )),
+    }
+}
+
+/// Replaces every `{{ $symbol }}` in the template with its value from the context (or environment),
+/// written as a quoted sql literal. `{{ $symbol | raw }}` inserts the value as is, unescaped, e.g.
+/// for table or column names, so it must not come from untrusted input. Templates inside single
+/// quoted string literals are left untouched.
+pub fn render_sql_template(template: &str, context: &serde_yaml_ng::Mapping) -> CpResult<String> {
+    let mut rendered = String::with_capacity(template.len());
+    let mut rest = template;
+    while let Some(start) = find_template(rest) {
+        rendered.push_str(&rest[..start]);
+        let after_open = &rest[start + TEMPLATE_OPEN.len()..];
+        let end = match after_open.find(TEMPLATE_CLOSE) {
+            Some(x) => x,
+            None => {
+                return Err(CpError::ConfigError(
+                    "unterminated template",
+                    format!("missing `{}` in: {}", TEMPLATE_CLOSE, template),
+                ));
+            }
+        };
+        let inner = after_open[..end].trim();
+        let (symbol, filter) = match inner.split_once('|') {
+            Some((symbol, filter)) => (symbol.trim(), Some(filter.trim())),
+            None => (inner, None),
+        };
+        let symbol = symbol.strip_prefix('$').unwrap_or(symbol).trim();
+        if symbol.is_empty() {
+            return Err(CpError::ConfigError(
+                "invalid template",
+                format!("empty symbol in: {}", template),
+            ));
+        }
+        let value = template_value(symbol, context)?;
+        let replacement = match filter {
+            None => sql_literal(symbol, &value)?,
+            Some(TEMPLATE_RAW_FILTER) => raw_string(symbol, &value)?,
+            Some(x) => {
+                return Err(CpError::ConfigError(
+                    "invalid template",
+                    format!("unrecognized filter `{}` for `{}`", x, symbol),
+                ));
+            }
+        };
+        rendered.push_str(&replacement);
+        rest = &after_open[end + TEMPLATE_CLOSE.len()..];
+    }
+    rendered.push_str(rest);
+    Ok(rendered)
+}
+
+/// Byte offset of the next template opening outside of a quoted string literal. Templates always
+/// start outside of literals, and an escaped `''` quote closes and reopens the literal.
+fn find_template(sql: &str) -> Option<usize> {
+    let mut quoted = false;
+    for (i, c) in sql.char_indices() {
+        if c == '\'' {
+            quoted = !quoted;
+        } else if !quoted && sql[i..].starts_with(TEMPLATE_OPEN) {
+            return Some(i);
+        }
+    }
+    None
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{context::envvar::EnvironmentVariableRegistry, util::common::yaml_from_str};
+
+    use super::render_sql_template;
+
+    fn context() -> serde_yaml_ng::Mapping {
+        yaml_from_str(
+            "
+name: O'Connor
+game: 3
+active: true
+ids: [1, 2]
+table: players
+",
+        )
+        .unwrap()
+        .as_mapping()
+        .unwrap()
+        .clone()
+    }
+
+    #[test]
+    fn valid_render_sql_template() {
+        let actual = render_sql_template(
+            "SELECT * FROM {{ $table | raw }} WHERE name = {{ $name }} AND game >= {{$game}} AND active = {{ active }} AND id IN {{ $ids }}",
+            &context(),
+        )
+        .unwrap();
+        assert_eq!(
+            actual,
+            "SELECT * FROM players WHERE name = 'O''Connor' AND game >= 3 AND active = true AND id IN (1, 2)"
+        );
+        assert_eq!(render_sql_template("SELECT 1", &context()).unwrap(), "SELECT 1");
+    }
+
+    #[test]
+    fn valid_render_sql_template_quoted() {
+        let actual = render_sql_template(
+            "SELECT * FROM t WHERE note = '{{ not a var }}' AND tag = 'it''s {{ x }}' AND name = {{ $name }}",
+            &context(),
+        )
+        .unwrap();
+        assert_eq!(
+            actual,
+            "SELECT * FROM t WHERE note = '{{ not a var }}' AND tag = 'it''s {{ x }}' AND name = 'O''Connor'"
+        );
+    }
+
+    #[test]
+    fn valid_render_sql_template_raw_unescaped() {
+        // raw values are inserted as is, their quotes included
+        let actual = render_sql_template("SELECT * FROM t WHERE name = {{ $name | raw }}", &context());
+        assert_eq!(actual.unwrap(), "SELECT * FROM t WHERE name = O'Connor");
+    }
+
+    #[test]
+    fn valid_render_sql_template_env() {
+        let mut ev = EnvironmentVariableRegistry::new();
+        ev.set_str("CP_TEST_TEMPLATE_DATE", "2024-04-01".to_owned()).unwrap();
+        let actual = render_sql_template("SELECT * FROM t WHERE dt = {{ $CP_TEST_TEMPLATE_DATE }}", &context());
+        ev.pop("CP_TEST_TEMPLATE_DATE").unwrap();
+        assert_eq!(actual.unwrap(), "SELECT * FROM t WHERE dt = '2024-04-01'");
+    }
+
+    #[test]
+    fn invalid_render_sql_template() {
+        for template in [
+            "SELECT {{ $missing }}",
+            "SELECT {{ $name ",
+            "SELECT {{ }}",
+            "SELECT {{ $name | upper }}",
+            "SELECT {{ $ids | raw }}",
+        ] {
+            assert!(render_sql_template(template, &context()).is_err(), "{}", template);
+        }
+    }
+}
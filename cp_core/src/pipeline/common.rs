@@ -220,6 +220,10 @@ mod tests {
 ",
             )
             .unwrap(),
+            model: None,
+            constraint_policy: None,
+            routes: None,
+            otherwise: None,
         });
         transform_registry.insert(RootTransformConfig {
             label: "merge_px".to_owned(),
@@ -239,6 +243,10 @@ mod tests {
 ",
             )
             .unwrap(),
+            model: None,
+            constraint_policy: None,
+            routes: None,
+            otherwise: None,
         });
         let mut source_registry = SourceRegistry::new();
         source_registry.insert(SourceGroupConfig {
@@ -259,6 +267,8 @@ mod tests {
 ",
             )
             .unwrap(),
+            constraint_policy: None,
+            rejects: None,
         });
         let connection_registry = ConnectionRegistry::new();
         let mut sink_registry = SinkRegistry::new();
@@ -277,6 +287,8 @@ mod tests {
 ",
             )
             .unwrap(),
+            constraint_policy: None,
+            rejects: None,
         });
         let mut request_registry = RequestRegistry::new();
         request_registry.insert(RequestGroupConfig {
@@ -383,7 +395,7 @@ stages:
         price: f64,
     }
 
-    fn mock_server(server: &MockServer) -> Vec<Mock> {
+    fn mock_server(server: &MockServer) -> Vec<Mock<'_>> {
         DummyData::json_instrument_prices()
             .iter()
             .map(|j| {
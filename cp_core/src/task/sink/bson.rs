@@ -0,0 +1,392 @@
+use std::{fs::OpenOptions, io::BufWriter, path::PathBuf, str::FromStr, sync::Arc};
+
+use async_trait::async_trait;
+use polars::{
+    frame::DataFrame,
+    prelude::{Expr, IntoLazy},
+};
+
+use crate::{
+    model::common::{ModelConfig, ModelFields},
+    model_emplace,
+    parser::{keyword::Keyword, merge_type::MergeTypeEnum},
+    pipeline::context::{DefaultPipelineContext, PipelineContext},
+    util::{
+        bson::{df_to_bson, write_bson_documents},
+        common::{get_full_path, get_utc_time_str_now, rng_str},
+        error::{CpError, CpResult},
+    },
+};
+
+use super::{
+    common::{Sink, SinkConfig},
+    config::BsonSinkConfig,
+};
+
+pub struct BsonSink {
+    merge_type: MergeTypeEnum,
+    filepath: PathBuf,
+    schema: Option<Vec<Expr>>,
+}
+
+impl BsonSink {
+    pub fn new(filepath: &str, merge_type: Option<MergeTypeEnum>) -> Self {
+        Self {
+            filepath: std::path::PathBuf::from_str(filepath).expect("bad filepath"),
+            merge_type: merge_type.unwrap_or(MergeTypeEnum::Replace),
+            schema: None,
+        }
+    }
+
+    pub fn with_schema(mut self, columns: Vec<Expr>) -> Self {
+        let _ = self.schema.insert(columns);
+        self
+    }
+}
+
+#[async_trait]
+impl Sink for BsonSink {
+    fn connection_type(&self) -> &str {
+        "bson"
+    }
+
+    async fn fetch(&self, dataframe: DataFrame, ctx: Arc<DefaultPipelineContext>) -> CpResult<()> {
+        self.run(dataframe, ctx)
+    }
+
+    fn run(&self, dataframe: DataFrame, ctx: Arc<DefaultPipelineContext>) -> CpResult<()> {
+        let filepath = match self.merge_type {
+            MergeTypeEnum::Replace => OpenOptions::new()
+                .write(true)
+                .create(true)
+                .truncate(true)
+                .open(&self.filepath)?,
+            // bson documents are written back to back, so appending keeps the file readable
+            MergeTypeEnum::Insert => OpenOptions::new().append(true).truncate(false).open(&self.filepath)?,
+            MergeTypeEnum::MakeNext => {
+                let mut fp = self.filepath.clone();
+                fp.set_file_name(format!(
+                    "{}_{}.bson",
+                    fp.file_stem().expect("filename").to_str().unwrap_or("_"),
+                    get_utc_time_str_now()
+                ));
+                if std::fs::exists(&fp)? {
+                    fp.set_file_name(format!(
+                        "{}_{}.bson",
+                        fp.file_stem().expect("filename").to_str().unwrap_or("_"),
+                        rng_str(6)
+                    ));
+                }
+                OpenOptions::new().write(true).create(true).truncate(true).open(fp)?
+            }
+            MergeTypeEnum::Upsert => {
+                return Err(CpError::TaskError(
+                    "BsonSink",
+                    "upsert merge_type is not supported for file sinks".to_owned(),
+                ));
+            }
+        };
+        let df_to_write = if let Some(schema) = &self.schema {
+            dataframe.lazy().select(schema.clone()).collect()?
+        } else {
+            dataframe
+        };
+        if ctx.is_executing_sink() {
+            write_bson_documents(BufWriter::new(filepath), &df_to_bson(&df_to_write)?)?;
+        } else {
+            let path_details = filepath.metadata()?;
+            log::info!(
+                "[no-execute-sink] Completed writing to {:?}: {:?}",
+                path_details,
+                df_to_write
+            );
+        }
+        Ok(())
+    }
+}
+
+impl SinkConfig for BsonSinkConfig {
+    fn emplace(&mut self, ctx: &DefaultPipelineContext, context: &serde_yaml_ng::Mapping) -> CpResult<()> {
+        self.bson.filepath.insert_value_from_context(context)?;
+        model_emplace!(self.bson, ctx, context);
+        Ok(())
+    }
+
+    fn model_fields(&self) -> Option<&ModelFields> {
+        self.bson.model_fields.as_ref()
+    }
+
+    fn validate(&self) -> Vec<CpError> {
+        let mut errors = vec![];
+        match self.bson.filepath.value() {
+            Some(_) => {}
+            None => errors.push(CpError::SymbolMissingValueError(
+                "filepath",
+                self.bson.filepath.symbol().unwrap_or("?").to_owned(),
+            )),
+        }
+        if self.bson.merge_type == MergeTypeEnum::Upsert {
+            errors.push(CpError::ConfigValidationError(
+                "sink[bson].merge_type",
+                "upsert is not supported for bson, use insert, replace or next".to_owned(),
+            ));
+        }
+        errors
+    }
+
+    fn transform(&self) -> Box<dyn Sink> {
+        let fp = match get_full_path(self.bson.filepath.value().expect("filepath"), false) {
+            Ok(x) => x,
+            Err(e) => panic!("bad filepath `{:?}`: {}", self.bson.filepath.value(), e),
+        };
+        let schema = self.bson.model_fields.as_ref().map(|x| {
+            ModelConfig {
+                label: "".to_string(),
+                fields: x.clone(),
+            }
+            .columns()
+            .expect("failed to build schema")
+        });
+        Box::new(BsonSink {
+            filepath: fp,
+            merge_type: self.bson.merge_type,
+            schema,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{io::BufReader, str::FromStr, sync::Arc};
+
+    use bson::{Bson, Decimal128, doc};
+    use polars::{
+        df,
+        frame::DataFrame,
+        prelude::{DataType, IntoLazy, col},
+    };
+
+    use crate::{
+        async_st,
+        context::model::ModelRegistry,
+        model::common::{ModelConfig, ModelFieldInfo, ModelFields},
+        parser::{
+            dtype::DType,
+            keyword::{Keyword, ModelFieldKeyword, StrKeyword},
+            merge_type::MergeTypeEnum,
+        },
+        pipeline::context::DefaultPipelineContext,
+        task::sink::{
+            common::{Sink, SinkConfig},
+            config::{BsonSinkConfig, LocalFileSinkConfig},
+        },
+        util::{
+            bson::{bson_to_df, read_bson_documents},
+            common::rng_str,
+            test::assert_frame_equal,
+            tmp::TempFile,
+        },
+    };
+
+    use super::BsonSink;
+
+    fn example() -> DataFrame {
+        df!(
+            "a" => [-1, 1, 3, 5, 6],
+            "b" => ["z", "a", "j", "i", "c"],
+        )
+        .unwrap()
+    }
+
+    fn example2() -> DataFrame {
+        df!(
+            "a" => [-1, 1, 3, 5, 6, -1, 1, 3, 5, 6],
+            "b" => ["z", "a", "j", "i", "c", "z", "a", "j", "i", "c"],
+        )
+        .unwrap()
+    }
+
+    fn read(filepath: &str) -> DataFrame {
+        let documents = read_bson_documents(BufReader::new(std::fs::File::open(filepath).unwrap())).unwrap();
+        bson_to_df(&documents, None).unwrap()
+    }
+
+    #[test]
+    fn valid_bson_sink() {
+        let expected = example();
+        let tmp = TempFile::default();
+        let bson_sink = BsonSink::new(&tmp.filepath, None);
+        let ctx = Arc::new(DefaultPipelineContext::new().with_executing_sink(true));
+        bson_sink.run(expected.clone(), ctx).unwrap();
+        assert_frame_equal(read(&tmp.filepath), expected);
+        assert_eq!(bson_sink.connection_type(), "bson");
+    }
+
+    #[test]
+    fn valid_bson_sink_async() {
+        let expected = example();
+        let tmp = TempFile::default();
+        let bson_sink = BsonSink::new(&tmp.filepath, None);
+        let ctx = Arc::new(DefaultPipelineContext::new().with_executing_sink(true));
+        async_st!(async || {
+            bson_sink.fetch(expected.clone(), ctx).await.unwrap();
+            assert_frame_equal(read(&tmp.filepath), expected);
+        });
+    }
+
+    #[test]
+    fn valid_bson_sink_bson_types() {
+        let tmp = TempFile::default();
+        let frame = df!(
+            "at" => ["2023-11-14T22:13:20"],
+            "px" => ["100.005"],
+        )
+        .unwrap()
+        .lazy()
+        .with_columns([
+            col("at").str().to_datetime(None, None, Default::default(), col("at")),
+            col("px").cast(DataType::Decimal(None, None)),
+        ])
+        .collect()
+        .unwrap();
+        let bson_sink = BsonSink::new(&tmp.filepath, None);
+        let ctx = Arc::new(DefaultPipelineContext::new().with_executing_sink(true));
+        bson_sink.run(frame, ctx).unwrap();
+        let documents = read_bson_documents(BufReader::new(tmp.get().unwrap())).unwrap();
+        assert_eq!(
+            documents,
+            vec![doc! {
+                "at": bson::DateTime::from_millis(1_700_000_000_000),
+                "px": Bson::Decimal128(Decimal128::from_str("100.005").unwrap()),
+            }]
+        );
+    }
+
+    fn get_node(
+        merge_type: MergeTypeEnum,
+        tmp: TempFile,
+    ) -> (
+        Arc<DefaultPipelineContext>,
+        serde_yaml_ng::Mapping,
+        Box<dyn Sink>,
+        TempFile,
+    ) {
+        let mut source_config = BsonSinkConfig {
+            bson: LocalFileSinkConfig {
+                filepath: StrKeyword::with_symbol("sample"),
+                merge_type,
+                model: None,
+                model_fields: None,
+            },
+        };
+        let ctx = Arc::new(DefaultPipelineContext::new().with_executing_sink(true));
+        let config = format!("sample: {}", &tmp.filepath);
+        let context = serde_yaml_ng::from_str::<serde_yaml_ng::Mapping>(config.as_str()).unwrap();
+        let _ = source_config.emplace(&ctx, &context);
+        let errors = source_config.validate();
+        assert!(errors.is_empty());
+        (ctx, context, source_config.transform(), tmp)
+    }
+
+    #[test]
+    fn valid_bson_sink_config_to_bson_sink_model_fields() {
+        let tmp = TempFile::default();
+        let mut source_config = BsonSinkConfig {
+            bson: LocalFileSinkConfig {
+                filepath: StrKeyword::with_value(tmp.filepath.clone()),
+                merge_type: MergeTypeEnum::Replace,
+                model: Some(StrKeyword::with_value("test".to_owned())),
+                model_fields: None,
+            },
+        };
+        let mut model_registry = ModelRegistry::new();
+        model_registry.insert(ModelConfig {
+            label: "test".to_string(),
+            fields: ModelFields::from([(
+                StrKeyword::with_value("a".to_owned()),
+                ModelFieldKeyword::with_value(ModelFieldInfo::with_dtype(DType(DataType::Int64))),
+            )]),
+        });
+        let ctx = Arc::new(
+            DefaultPipelineContext::new()
+                .with_executing_sink(true)
+                .with_model_registry(model_registry),
+        );
+        let context = serde_yaml_ng::from_str::<serde_yaml_ng::Mapping>("{}").unwrap();
+        let _ = source_config.emplace(&ctx, &context);
+        let errors = source_config.validate();
+        assert!(errors.is_empty());
+        let actual_node = source_config.transform();
+        actual_node.run(example(), ctx.clone()).unwrap();
+        assert_frame_equal(
+            read(&tmp.filepath),
+            example()
+                .lazy()
+                .select(&[col("a").cast(DataType::Int64)])
+                .collect()
+                .unwrap(),
+        );
+    }
+
+    #[test]
+    fn valid_bson_sink_config_to_bson_sink_exec_mode_off() {
+        let tmp = TempFile::default();
+        let mut source_config = BsonSinkConfig {
+            bson: LocalFileSinkConfig {
+                filepath: StrKeyword::with_value(tmp.filepath.clone()),
+                merge_type: MergeTypeEnum::Replace,
+                model: None,
+                model_fields: None,
+            },
+        };
+        let ctx = Arc::new(DefaultPipelineContext::new().with_executing_sink(false));
+        let context = serde_yaml_ng::from_str::<serde_yaml_ng::Mapping>("{}").unwrap();
+        let _ = source_config.emplace(&ctx, &context);
+        let errors = source_config.validate();
+        assert!(errors.is_empty());
+        let actual_node = source_config.transform();
+        actual_node.run(example(), ctx.clone()).unwrap();
+        assert_eq!(std::fs::metadata(&tmp.filepath).unwrap().len(), 0);
+    }
+
+    #[test]
+    fn valid_bson_sink_config_to_bson_sink_replace() {
+        let expected = example();
+        let tmp = TempFile::default();
+        let (ctx, _, actual_node, tmp) = get_node(MergeTypeEnum::Replace, tmp);
+        actual_node.run(expected.clone(), ctx.clone()).unwrap();
+        actual_node.run(expected.clone(), ctx.clone()).unwrap();
+        assert_frame_equal(read(&tmp.filepath), expected);
+    }
+
+    #[test]
+    fn valid_bson_sink_config_to_bson_sink_insert() {
+        let tmp = TempFile::default();
+        let (ctx, _, actual_node, tmp) = get_node(MergeTypeEnum::Insert, tmp);
+        actual_node.run(example(), ctx.clone()).unwrap();
+        actual_node.run(example(), ctx.clone()).unwrap();
+        assert_frame_equal(read(&tmp.filepath), example2());
+    }
+
+    #[test]
+    fn valid_bson_sink_config_to_bson_sink_make_next() {
+        let expected = example();
+        let dir = format!("/tmp/capport_testing/{}", rng_str(5));
+        {
+            std::fs::create_dir_all(&dir).unwrap();
+            let tmp = TempFile::default_in_dir(&dir, "bson").unwrap();
+            let (ctx, _, actual_node, _) = get_node(MergeTypeEnum::MakeNext, tmp);
+            let files_created = 3i32;
+            for _ in 0..files_created {
+                actual_node.run(expected.clone(), ctx.clone()).unwrap();
+            }
+            let count = std::fs::read_dir(&dir).unwrap().fold(0i32, |idx, file| {
+                let buffer = file.unwrap().path();
+                assert_frame_equal(read(buffer.to_str().unwrap()), expected.clone());
+                idx + 1
+            });
+            assert_eq!(count, files_created);
+        }
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+}
@@ -1,17 +1,22 @@
-use std::{io::Read, sync::Arc};
+use std::{
+    io::Read,
+    sync::{Arc, OnceLock},
+};
 
 use async_trait::async_trait;
-use inserter_x::clickhouse::ClickhouseInserter;
+use inserter_x::{clickhouse::ClickhouseInserter, common::CreateCmd};
 use polars::{
     frame::DataFrame,
-    prelude::{Expr, IntoLazy, col},
+    prelude::{DataType, Expr, IntoLazy, Schema, col},
 };
 use reqwest::header::HeaderMap;
 
 use crate::{
+    model::{common::ModelFields, drift::SchemaDrift},
     parser::{
         keyword::{Keyword, StrKeyword},
         merge_type::MergeTypeEnum,
+        model::SchemaDriftPolicy,
     },
     pipeline::context::DefaultPipelineContext,
     util::{
@@ -33,6 +38,74 @@ pub struct ClickhouseSink {
     create_table_if_not_exists: bool,
     columns: Vec<Expr>,
     headers: HeaderMap,
+    drift: Option<SchemaDrift>,
+    // adds the model fields missing from an existing table, with `on_schema_drift: evolve`
+    alter_queries: Vec<String>,
+    // set once the alter queries succeeded, so that they are sent on the first write only
+    altered: OnceLock<()>,
+}
+
+/// The clickhouse type `inserter_x` creates a column of the dtype with
+fn clickhouse_type(dtype: &DataType) -> CpResult<String> {
+    let nullable = |dtype: &DataType| -> CpResult<String> {
+        let inner = clickhouse_type(dtype)?;
+        Ok(if dtype.is_nested() {
+            inner
+        } else {
+            format!("Nullable({})", inner)
+        })
+    };
+    Ok(match dtype {
+        DataType::Null => "Null".to_owned(),
+        DataType::Boolean => "Bool".to_owned(),
+        DataType::Int8 => "Int8".to_owned(),
+        DataType::Int16 => "Int16".to_owned(),
+        DataType::Int32 => "Int32".to_owned(),
+        DataType::Int64 => "Int64".to_owned(),
+        DataType::UInt8 => "UInt8".to_owned(),
+        DataType::UInt16 => "UInt16".to_owned(),
+        DataType::UInt32 => "UInt32".to_owned(),
+        DataType::UInt64 => "UInt64".to_owned(),
+        DataType::Float32 => "Float32".to_owned(),
+        DataType::Float64 => "Float64".to_owned(),
+        DataType::Decimal(precision, scale) => format!("Decimal({}, {})", precision.unwrap_or(38), scale.unwrap_or(0)),
+        DataType::String => "String".to_owned(),
+        DataType::Date => "Date32".to_owned(),
+        DataType::Datetime(_, _) => "DateTime64".to_owned(),
+        DataType::Time => "Timestamp".to_owned(),
+        DataType::List(inner) | DataType::Array(inner, _) => format!("Array({})", nullable(inner)?),
+        DataType::Struct(fields) => format!(
+            "Tuple({})",
+            fields
+                .iter()
+                .map(|x| nullable(x.dtype()))
+                .collect::<CpResult<Vec<_>>>()?
+                .join(",")
+        ),
+        x => {
+            return Err(CpError::ConfigError(
+                "clickhouse column",
+                format!("no clickhouse type for dtype {:?}", x),
+            ));
+        }
+    })
+}
+
+/// `ALTER TABLE .. ADD COLUMN IF NOT EXISTS ..` for every column of the schema, typed and made
+/// nullable as in the create query of the inserter
+fn alter_queries(inserter: &ClickhouseInserter, schema: &Schema) -> CpResult<Vec<String>> {
+    let table = match &inserter.db_name {
+        Some(db_name) => format!("{}.{}", db_name, inserter.table_name),
+        None => inserter.table_name.clone(),
+    };
+    schema
+        .iter()
+        .map(|(name, dtype)| {
+            let nullable = !inserter.not_null.contains(name.as_str()) && !dtype.is_nested();
+            let column = ClickhouseInserter::field(name, nullable, Some(&clickhouse_type(dtype)?), None);
+            Ok(format!("ALTER TABLE {} ADD COLUMN IF NOT EXISTS {}", table, column))
+        })
+        .collect()
 }
 
 impl ClickhouseSink {
@@ -60,6 +133,9 @@ impl ClickhouseSink {
             create_table_if_not_exists,
             columns,
             headers: HeaderMap::new(),
+            drift: None,
+            alter_queries: vec![],
+            altered: OnceLock::new(),
         }
     }
 }
@@ -107,6 +183,9 @@ impl Sink for ClickhouseSink {
     }
     fn run(&self, frame: DataFrame, _ctx: Arc<DefaultPipelineContext>) -> CpResult<()> {
         let client = reqwest::blocking::Client::new();
+        if let Some(drift) = &self.drift {
+            drift.check(&frame.schema())?;
+        }
         let lf = frame.lazy();
         let final_frame = (if self.strict {
             lf.select(&self.columns)
@@ -129,6 +208,18 @@ impl Sink for ClickhouseSink {
             log::debug!("Table update: {}", create);
             parse_clickhouse_sync!(request);
         }
+        if self.altered.get().is_none() {
+            for alter in &self.alter_queries {
+                let request = client
+                    .post(&self.uri)
+                    .query(&[("query", alter)])
+                    .headers(self.headers.clone())
+                    .header("Content-Length", 0);
+                log::debug!("Table update: {}", alter);
+                parse_clickhouse_sync!(request);
+            }
+            let _ = self.altered.set(());
+        }
         let insert = self.inserter.get_insert_query()?;
         log::debug!("Inserting: {}", insert);
         let body = self.inserter.get_arrow_body(&final_frame)?;
@@ -143,6 +234,9 @@ impl Sink for ClickhouseSink {
     }
     async fn fetch(&self, frame: DataFrame, _ctx: Arc<DefaultPipelineContext>) -> CpResult<()> {
         let client = reqwest::Client::new();
+        if let Some(drift) = &self.drift {
+            drift.check(&frame.schema())?;
+        }
         let lf = frame.lazy();
         let final_frame = (if self.strict {
             lf.select(&self.columns)
@@ -160,6 +254,19 @@ impl Sink for ClickhouseSink {
                 .header("Content-Length", 0);
             parse_clickhouse_async!(request);
         }
+        if self.altered.get().is_none() {
+            for alter in &self.alter_queries {
+                client
+                    .post(&self.uri)
+                    .query(&[("query", alter)])
+                    .headers(self.headers.clone())
+                    .header("Content-Length", 0)
+                    .send()
+                    .await?
+                    .error_for_status()?;
+            }
+            let _ = self.altered.set(());
+        }
         let insert = self.inserter.get_insert_query()?;
         let body = self.inserter.get_arrow_body(&final_frame)?;
         let request = client
@@ -178,6 +285,7 @@ fn from_merge_type(merge_type: MergeTypeEnum) -> (&'static str, &'static str) {
         MergeTypeEnum::Replace => ("ReplacingMergeTree", "CREATE OR REPLACE TABLE"),
         MergeTypeEnum::Insert => ("MergeTree", "CREATE TABLE"),
         MergeTypeEnum::MakeNext => ("MergeTree", "CREATE OR REPLACE TABLE"),
+        MergeTypeEnum::Upsert => unreachable!("upsert is rejected by ClickhouseSinkConfig::validate"),
     }
 }
 
@@ -247,28 +355,21 @@ impl ClickhouseTableOptions {
 impl SinkConfig for ClickhouseSinkConfig {
     fn emplace(&mut self, ctx: &DefaultPipelineContext, context: &serde_yaml_ng::Mapping) -> CpResult<()> {
         self.clickhouse.emplace(ctx, context, "http://")?;
-        if let Some(url) = self.clickhouse.url.take() {
-            let mut parts: Vec<&str> = url
-                .value()
-                .expect("source[clickhouse].url")
-                .split_terminator("/")
-                .collect();
-            if parts.len() > 3 {
-                if let Some(db_name) = parts.pop() {
-                    if let Some(options) = self.options.as_mut() {
-                        if options.db_name.is_none() {
-                            let _ = options.db_name.insert(StrKeyword::with_value(db_name.to_string()));
-                        }
-                    }
-                }
-            }
-            let _ = self.clickhouse.url.insert(StrKeyword::with_value(parts.join("/")));
-        };
+        if let Some(db_name) = self.clickhouse.split_db_name()
+            && let Some(options) = self.options.as_mut()
+            && options.db_name.is_none()
+        {
+            let _ = options.db_name.insert(StrKeyword::with_value(db_name));
+        }
         if let Some(options) = self.options.as_mut() {
             options.emplace(context)?;
         }
         Ok(())
     }
+    fn model_fields(&self) -> Option<&ModelFields> {
+        self.clickhouse.model_fields.as_ref()
+    }
+
     fn validate(&self) -> Vec<CpError> {
         let mut errors = vec![];
         // Url is mandatory
@@ -280,10 +381,10 @@ impl SinkConfig for ClickhouseSinkConfig {
                 .expect("source[clickhouse].url not provided or deduced"),
             "source[clickhouse].url"
         );
-        if let Some(options) = &self.options {
-            if let Some(db_name) = &options.db_name {
-                valid_or_insert_error!(errors, db_name, "source[clickhouse].db_name");
-            }
+        if let Some(options) = &self.options
+            && let Some(db_name) = &options.db_name
+        {
+            valid_or_insert_error!(errors, db_name, "source[clickhouse].db_name");
         }
         if let Some(output) = &self.clickhouse.output {
             log::warn!("Output {:?} ignored in ClickhouseSinkConfig", output);
@@ -297,6 +398,12 @@ impl SinkConfig for ClickhouseSinkConfig {
                 "Missing table merge_type, please declare".to_owned(),
             ));
         }
+        if self.clickhouse.merge_type == Some(MergeTypeEnum::Upsert) {
+            errors.push(CpError::ConfigValidationError(
+                "source[clickhouse].merge_type",
+                "upsert is not supported for clickhouse, use insert, replace or next".to_owned(),
+            ));
+        }
         // Model is mandatory
         let model_fields = self
             .clickhouse
@@ -345,6 +452,27 @@ impl SinkConfig for ClickhouseSinkConfig {
                 ch
             };
         }
+        let inserter = ch
+            .with_schema_from_cols(empty_df.get_columns())
+            .expect("bad schema (ClickhouseInserter)")
+            .build_queries()
+            .expect("bad queries (ClickhouseInserter)");
+        let policy = self.options.as_ref().and_then(|x| x.on_schema_drift);
+        let alter_queries = match policy {
+            Some(SchemaDriftPolicy::Evolve) => {
+                alter_queries(&inserter, &schema).expect("bad alter queries (ClickhouseInserter)")
+            }
+            _ => vec![],
+        };
+        let drift = policy
+            .zip(self.clickhouse.model_fields.as_ref())
+            .map(|(policy, fields)| {
+                SchemaDrift::new(
+                    self.clickhouse.table.value().expect("source[clickhouse].table"),
+                    fields,
+                    policy,
+                )
+            });
         Box::new(ClickhouseSink {
             uri: self
                 .clickhouse
@@ -353,11 +481,7 @@ impl SinkConfig for ClickhouseSinkConfig {
                 .map(|x| x.value().unwrap())
                 .expect("source[clickhouse].uri(val)")
                 .to_string(),
-            inserter: ch
-                .with_schema_from_cols(empty_df.get_columns())
-                .expect("bad schema (ClickhouseInserter)")
-                .build_queries()
-                .expect("bad queries (ClickhouseInserter)"),
+            inserter,
             columns,
             strict: self.clickhouse.strict.unwrap_or(true),
             create_table_if_not_exists: self
@@ -366,6 +490,9 @@ impl SinkConfig for ClickhouseSinkConfig {
                 .map(|x| x.create_table_if_not_exists.unwrap_or(false))
                 .unwrap_or(false),
             headers: HeaderMap::new(),
+            drift,
+            alter_queries,
+            altered: OnceLock::new(),
         })
     }
 }
@@ -378,7 +505,7 @@ mod tests {
     use polars::{
         frame::DataFrame,
         io::SerReader,
-        prelude::{CsvParseOptions, CsvReadOptions},
+        prelude::{CsvParseOptions, CsvReadOptions, DataType, Field, Schema},
     };
 
     use crate::{
@@ -398,7 +525,7 @@ mod tests {
         },
     };
 
-    use super::ClickhouseSink;
+    use super::{ClickhouseSink, alter_queries};
 
     fn get_example_frame() -> DataFrame {
         let my_file = std::path::PathBuf::from_str("../config/canadian_players.csv").unwrap();
@@ -496,6 +623,7 @@ birth_state_province_code: str
                 merge_type: Some(MergeTypeEnum::Replace),
                 output: None,
                 strict: Some(true),
+                on_schema_drift: None,
             },
             options: Some(ClickhouseTableOptions {
                 db_name: None,
@@ -503,6 +631,7 @@ birth_state_province_code: str
                 primary_key: vec![StrKeyword::with_value("id".to_owned())],
                 not_null: None,
                 create_table_if_not_exists: Some(true),
+                on_schema_drift: None,
             }),
         };
         let ctx = Arc::new(DefaultPipelineContext::new().with_executing_sink(false));
@@ -513,6 +642,46 @@ birth_state_province_code: str
         // TODO: check against clickhouse that nothing was pushed
     }
 
+    #[test]
+    fn valid_clickhouse_sink_alter_queries() {
+        let inserter = ClickhouseInserter::default("players")
+            .with_dbname("db")
+            .with_not_null(vec!["id".to_owned()]);
+        let schema = Schema::from_iter([
+            Field::new("id".into(), DataType::Int64),
+            Field::new(
+                "pos".into(),
+                DataType::Struct(vec![
+                    Field::new("team".into(), DataType::String),
+                    Field::new("line".into(), DataType::Int8),
+                ]),
+            ),
+            Field::new("games".into(), DataType::List(Box::new(DataType::Date))),
+            Field::new("salary".into(), DataType::Decimal(Some(18), Some(2))),
+        ]);
+        let alters = alter_queries(&inserter, &schema).unwrap();
+        assert_eq!(
+            alters,
+            vec![
+                "ALTER TABLE db.players ADD COLUMN IF NOT EXISTS id Int64 NOT NULL",
+                "ALTER TABLE db.players ADD COLUMN IF NOT EXISTS pos Tuple(Nullable(String),Nullable(Int8)) NOT NULL",
+                "ALTER TABLE db.players ADD COLUMN IF NOT EXISTS games Array(Nullable(Date32)) NOT NULL",
+                "ALTER TABLE db.players ADD COLUMN IF NOT EXISTS salary Decimal(18, 2)  NULL",
+            ]
+        );
+        // the columns are added as the create query declares them
+        let inserter = inserter
+            .with_schema_from_cols(DataFrame::empty_with_schema(&schema).get_columns())
+            .unwrap()
+            .build_queries()
+            .unwrap();
+        let create = inserter.get_create_query().unwrap();
+        for alter in alters {
+            let column = alter.trim_start_matches("ALTER TABLE db.players ADD COLUMN IF NOT EXISTS ");
+            assert!(create.contains(column), "{} in {}", column, create);
+        }
+    }
+
     #[test]
     fn invalid_clickhouse_configs() {
         let cfg = "
@@ -537,6 +706,10 @@ options:
                 .unwrap();
         let good_errors = good_cfg.validate();
         assert!(good_errors.is_empty());
+        let upsert_cfg =
+            serde_yaml_ng::from_str::<ClickhouseSinkConfig>(cfg.replace("{merge_type}", "merge_type: upsert").as_str())
+                .unwrap();
+        assert_eq!(upsert_cfg.validate().len(), 1);
     }
 
     fn get_node(
@@ -570,6 +743,7 @@ options:
                 merge_type: Some(merge_type),
                 env_connection: Some(StrKeyword::with_value("ch".to_owned())),
                 strict: Some(true),
+                on_schema_drift: None,
             },
             options: Some(ClickhouseTableOptions {
                 db_name: None,
@@ -577,6 +751,7 @@ options:
                 primary_key: vec![StrKeyword::with_value("id".to_owned())],
                 not_null: Some(vec![StrKeyword::with_value("birthdate".to_owned())]),
                 create_table_if_not_exists: Some(true),
+                on_schema_drift: None,
             }),
         };
         let ctx = Arc::new(
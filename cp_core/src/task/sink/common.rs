@@ -1,7 +1,10 @@
 use std::sync::{Arc, atomic::AtomicUsize};
 
 use async_trait::async_trait;
-use polars::{frame::DataFrame, prelude::LazyFrame};
+use polars::{
+    frame::DataFrame,
+    prelude::{IntoLazy, LazyFrame, SortMultipleOptions, UnionArgs, UniqueKeepStrategy, concat},
+};
 
 use crate::{
     ctx_run_n_async, ctx_run_n_threads,
@@ -9,6 +12,11 @@ use crate::{
         common::{FrameAsyncListenHandle, FrameListenHandle, FrameUpdate, FrameUpdateType},
         polars::PolarsAsyncListenHandle,
     },
+    model::{
+        common::ModelFields,
+        constraint::ModelConstraintCheck,
+        reject::{ERROR_COLUMN, ModelRejects},
+    },
     parser::{keyword::Keyword, merge_type::MergeTypeEnum},
     pipeline::context::{DefaultPipelineContext, PipelineContext},
     task::stage::{Stage, StageTaskConfig},
@@ -17,7 +25,13 @@ use crate::{
     valid_or_insert_error,
 };
 
-use super::config::{ClickhouseSinkConfig, CsvSinkConfig, JsonSinkConfig, SinkGroupConfig};
+use super::config::{
+    BsonSinkConfig, ClickhouseSinkConfig, CsvSinkConfig, JsonSinkConfig, MySqlSinkConfig, ParquetSinkConfig,
+    PostgresSinkConfig, SinkGroupConfig, SqliteSinkConfig,
+};
+
+/// Input row of the rejected rows, to write a row rejected by several sinks once
+const REJECTS_ROW_INDEX: &str = "__rejects_row";
 
 /// Base sink trait. Importantly, certain sinks may have dependencies as well.
 /// If it receives a termination signal, it is the sink type's responsibility to clean up and
@@ -30,14 +44,84 @@ pub trait Sink {
     async fn fetch(&self, frame: DataFrame, ctx: Arc<DefaultPipelineContext>) -> CpResult<()>;
 }
 
-pub struct BoxedSink(Box<dyn Sink>);
+pub struct BoxedSink(pub(crate) Box<dyn Sink>);
 
 pub trait SinkConfig {
     fn emplace(&mut self, ctx: &DefaultPipelineContext, context: &serde_yaml_ng::Mapping) -> CpResult<()>;
+    /// The model fields after emplacing, if the sink has a model
+    fn model_fields(&self) -> Option<&ModelFields>;
     fn validate(&self) -> Vec<CpError>;
     fn transform(&self) -> Box<dyn Sink>;
 }
 
+/// Checks frames against the sink's model constraints before they are written.
+struct ConstrainedSink {
+    sink: BoxedSink,
+    check: ModelConstraintCheck,
+}
+
+#[async_trait]
+impl Sink for ConstrainedSink {
+    fn connection_type(&self) -> &str {
+        self.sink.0.connection_type()
+    }
+    fn run(&self, frame: DataFrame, ctx: Arc<DefaultPipelineContext>) -> CpResult<()> {
+        self.sink.0.run(self.check.enforce(frame)?, ctx)
+    }
+    async fn fetch(&self, frame: DataFrame, ctx: Arc<DefaultPipelineContext>) -> CpResult<()> {
+        self.sink.0.fetch(self.check.enforce(frame)?, ctx).await
+    }
+}
+
+/// Splits the frames of a group by the model of each sink, writing the rows rejected by any sink
+/// once to a `rejects` sink. A row rejected by several sinks for the same reason is written once.
+struct SinkRejects {
+    label: String,
+    sink: Arc<BoxedSink>,
+    // one per sink of the group, None for sinks without a model
+    splits: Vec<Option<ModelRejects>>,
+}
+
+impl SinkRejects {
+    /// The frame of each sink, and the rejected rows if any
+    fn split(&self, frame: DataFrame) -> CpResult<(Vec<DataFrame>, Option<DataFrame>)> {
+        let indexed = frame.with_row_index(REJECTS_ROW_INDEX.into(), None)?;
+        let mut frames = vec![];
+        let mut rejected = vec![];
+        for split in &self.splits {
+            match split {
+                Some(split) => {
+                    let (accepted, rejects) = split.split(indexed.clone())?;
+                    frames.push(accepted);
+                    rejected.extend(rejects.map(|x| x.lazy()));
+                }
+                None => frames.push(frame.clone()),
+            }
+        }
+        if rejected.is_empty() {
+            return Ok((frames, None));
+        }
+        let rejected = concat(rejected, UnionArgs::default())?
+            .unique_stable(
+                Some(vec![REJECTS_ROW_INDEX.into(), ERROR_COLUMN.into()]),
+                UniqueKeepStrategy::First,
+            )
+            .sort(
+                [REJECTS_ROW_INDEX],
+                SortMultipleOptions::default().with_maintain_order(true),
+            )
+            .drop([REJECTS_ROW_INDEX])
+            .collect()?;
+        log::warn!(
+            "Rejected {} rows of `{}`, writing them via {}",
+            rejected.height(),
+            self.label,
+            self.sink.0.connection_type()
+        );
+        Ok((frames, Some(rejected)))
+    }
+}
+
 /// We NEVER modify the individual Sink instantiations after initialization.
 /// Hence the Box<dyn Sink> is safe to access in parallel
 unsafe impl Send for BoxedSink {}
@@ -52,6 +136,7 @@ pub struct SinkGroup {
     label: String,
     max_threads: usize,
     sinks: Vec<BoxedSink>,
+    rejects: Option<SinkRejects>,
 }
 
 impl SinkGroup {
@@ -61,8 +146,21 @@ impl SinkGroup {
             label: label.to_owned(),
             max_threads,
             sinks: sinks.into_iter().map(BoxedSink).collect::<Vec<BoxedSink>>(),
+            rejects: None,
+        }
+    }
+
+    /// The frame of each sink, and the rows rejected by their models if the group has a `rejects` sink
+    fn split(&self, frame: DataFrame) -> CpResult<(Vec<DataFrame>, Option<DataFrame>)> {
+        match &self.rejects {
+            Some(rejects) => rejects.split(frame),
+            None => Ok((vec![frame; self.sinks.len()], None)),
         }
     }
+
+    fn rejects_sink(&self) -> &BoxedSink {
+        &self.rejects.as_ref().expect("rejects").sink
+    }
 }
 
 impl Stage for SinkGroup {
@@ -76,8 +174,12 @@ impl Stage for SinkGroup {
             }
         };
         log::info!("INPUT `{}`: {:?}", &self.label, dataframe);
-        for sink in &self.sinks {
-            sink.0.run(dataframe.clone(), ctx.clone())?;
+        let (frames, rejected) = self.split(dataframe)?;
+        if let Some(rejected) = rejected {
+            self.rejects_sink().0.run(rejected, ctx.clone())?;
+        }
+        for (sink, frame) in self.sinks.iter().zip(frames) {
+            sink.0.run(frame, ctx.clone())?;
             log::info!(
                 "Success pushing frame update via {}: {}",
                 sink.0.connection_type(),
@@ -98,24 +200,34 @@ impl Stage for SinkGroup {
         let label = self.label.as_str();
         let mut listen = ctx.get_listener(result_name, label)?;
         let update = listen.force_listen();
-        let mut dataframe: Option<DataFrame> = None;
+        let mut frames: Vec<Option<DataFrame>> = vec![None; self.sinks.len()];
         {
             let fread = update.frame.read()?;
-            match fread.clone().collect() {
-                Ok(x) => {
-                    let _ = dataframe.insert(x);
+            match fread
+                .clone()
+                .collect()
+                .map_err(CpError::from)
+                .and_then(|x| self.split(x))
+            {
+                Ok((split, rejected)) => {
+                    frames = split.into_iter().map(Some).collect();
+                    if let Some(rejected) = rejected
+                        && let Err(e) = self.rejects_sink().0.run(rejected, ctx.clone())
+                    {
+                        log::error!("Failed to write rejected rows of `{}`: {}", result_name, e);
+                    }
                 }
                 Err(e) => log::error!("Bad result, skipping sink stage: {}", e),
             }
         }
+        let tasks = self.sinks.iter().zip(frames).collect::<Vec<_>>();
         ctx_run_n_threads!(
             self.max_threads,
-            self.sinks.as_slice(),
-            move |(sinks, ictx, dfh): (_, Arc<DefaultPipelineContext>, Option<DataFrame>)| {
-                for sink in sinks {
-                    let s: &BoxedSink = sink;
+            tasks.as_slice(),
+            move |(tasks, ictx): (&[(&BoxedSink, Option<DataFrame>)], Arc<DefaultPipelineContext>)| {
+                for (s, frame) in tasks {
                     let sink = &s.0;
-                    let result = match dfh.clone() {
+                    let result = match frame.clone() {
                         Some(val) => sink.run(val, ictx.clone()),
                         None => Err(CpError::PipelineError(
                             "no valid result found",
@@ -136,8 +248,7 @@ impl Stage for SinkGroup {
                     };
                 }
             },
-            ctx,
-            dataframe
+            ctx
         );
         Ok(())
     }
@@ -153,25 +264,31 @@ impl Stage for SinkGroup {
         loop {
             // unsafe hack to get past the borrow checker
             let update: FrameUpdate<LazyFrame> = unsafe { (*lp).listen().await? };
+            // the frame is collected and split once for all sinks
+            let mut frames: Vec<Option<DataFrame>> = vec![None; self.sinks.len()];
+            if update.info.msg_type == FrameUpdateType::Replace {
+                let collected = update.frame.read()?.clone().collect();
+                match collected.map_err(CpError::from).and_then(|x| self.split(x)) {
+                    Ok((split, rejected)) => {
+                        frames = split.into_iter().map(Some).collect();
+                        if let Some(rejected) = rejected
+                            && let Err(e) = self.rejects_sink().0.fetch(rejected, ctx.clone()).await
+                        {
+                            log::error!("Failed to write rejected rows of `{}`: {}", result_name, e);
+                        }
+                    }
+                    Err(e) => log::error!("Bad result, skipping sink stage: {}", e),
+                }
+            }
+            let tasks = self.sinks.iter().zip(frames).collect::<Vec<_>>();
             ctx_run_n_async!(
                 label,
-                &self.sinks,
-                async |bsink: &BoxedSink, ctx: Arc<DefaultPipelineContext>| {
+                &tasks,
+                async |(bsink, frame): &(&BoxedSink, Option<DataFrame>), ctx: Arc<DefaultPipelineContext>| {
                     let sink = &bsink.0;
-                    let upd = update.clone();
-                    match upd.info.msg_type {
+                    match update.info.msg_type {
                         FrameUpdateType::Replace => {
-                            let mut dataframe: Option<DataFrame> = None;
-                            {
-                                let fread = update.frame.read()?;
-                                match fread.clone().collect() {
-                                    Ok(x) => {
-                                        let _ = dataframe.insert(x);
-                                    }
-                                    Err(e) => log::error!("Bad result, skipping sink stage: {}", e),
-                                }
-                            }
-                            if let Some(frame) = dataframe.take() {
+                            if let Some(frame) = frame.clone() {
                                 match sink.fetch(frame, ctx.clone()).await {
                                     Ok(_) => {
                                         log::info!(
@@ -217,26 +334,47 @@ impl Stage for SinkGroup {
     }
 }
 
+/// Parses a single sink config, e.g. an entry of `sinks` or a `rejects` target
+pub fn parse_sink_config(value: &serde_yaml_ng::Value) -> CpResult<Box<dyn SinkConfig>> {
+    let config = try_deserialize_stage!(
+        value,
+        dyn SinkConfig,
+        CsvSinkConfig,
+        ClickhouseSinkConfig,
+        JsonSinkConfig,
+        ParquetSinkConfig,
+        BsonSinkConfig,
+        SqliteSinkConfig,
+        PostgresSinkConfig,
+        MySqlSinkConfig
+    );
+    config.ok_or_else(|| {
+        CpError::ConfigError(
+            "Sink config parsing error",
+            format!("Failed to parse sink config: {:?}", value),
+        )
+    })
+}
+
+/// Builds the sink receiving the rejected rows of a stage
+pub fn parse_rejects_sink(
+    value: &serde_yaml_ng::Value,
+    ctx: &DefaultPipelineContext,
+    context: &serde_yaml_ng::Mapping,
+) -> Result<Arc<BoxedSink>, Vec<CpError>> {
+    let mut config = parse_sink_config(value).map_err(|e| vec![e])?;
+    config.emplace(ctx, context).map_err(|e| vec![e])?;
+    let errors = config.validate();
+    if errors.is_empty() {
+        Ok(Arc::new(BoxedSink(config.transform())))
+    } else {
+        Err(errors)
+    }
+}
+
 impl SinkGroupConfig {
     fn parse_subsinks(&self) -> Vec<Result<Box<dyn SinkConfig>, CpError>> {
-        self.sinks
-            .iter()
-            .map(|transform| {
-                let config = try_deserialize_stage!(
-                    transform,
-                    dyn SinkConfig,
-                    CsvSinkConfig,
-                    ClickhouseSinkConfig,
-                    JsonSinkConfig
-                );
-                config.ok_or_else(|| {
-                    CpError::ConfigError(
-                        "Sink config parsing error",
-                        format!("Failed to parse sink config: {:?}", transform),
-                    )
-                })
-            })
-            .collect()
+        self.sinks.iter().map(parse_sink_config).collect()
     }
 }
 
@@ -244,6 +382,24 @@ impl StageTaskConfig<SinkGroup> for SinkGroupConfig {
     fn parse(&self, ctx: &DefaultPipelineContext, context: &serde_yaml_ng::Mapping) -> Result<SinkGroup, Vec<CpError>> {
         let mut subsinks = vec![];
         let mut errors = vec![];
+        let mut input = self.input.clone();
+        let _ = input.insert_value_from_context(context);
+        valid_or_insert_error!(errors, input, "sink.input");
+        if self.constraint_policy.is_some() && self.rejects.is_some() {
+            errors.push(CpError::ConfigValidationError(
+                "sink.constraint_policy",
+                "constraint_policy cannot be combined with rejects, which diverts the violating rows".to_owned(),
+            ));
+        }
+        let rejects = match self.rejects.as_ref().map(|x| parse_rejects_sink(x, ctx, context)) {
+            Some(Ok(x)) => Some(x),
+            Some(Err(errs)) => {
+                errors.extend(errs);
+                None
+            }
+            None => None,
+        };
+        let mut splits = vec![];
         for result in self.parse_subsinks() {
             match result {
                 Ok(mut config) => {
@@ -252,7 +408,37 @@ impl StageTaskConfig<SinkGroup> for SinkGroupConfig {
                     }
                     let errs = config.validate();
                     if errs.is_empty() {
-                        subsinks.push(BoxedSink(config.transform()));
+                        let sink = config.transform();
+                        if rejects.is_some() {
+                            match config
+                                .model_fields()
+                                .map(|x| ModelRejects::new(&self.label, x))
+                                .transpose()
+                            {
+                                Ok(split) => splits.push(split),
+                                Err(e) => errors.push(e),
+                            }
+                            subsinks.push(BoxedSink(sink));
+                            continue;
+                        }
+                        let check = self
+                            .constraint_policy
+                            .zip(config.model_fields())
+                            .and_then(|(policy, fields)| {
+                                let frame = format!(
+                                    "{} ({})",
+                                    input.value().map_or("?", |x| x.as_str()),
+                                    sink.connection_type()
+                                );
+                                ModelConstraintCheck::new(&frame, fields, policy)
+                            });
+                        subsinks.push(BoxedSink(match check {
+                            Some(check) => Box::new(ConstrainedSink {
+                                sink: BoxedSink(sink),
+                                check,
+                            }),
+                            None => sink,
+                        }));
                     } else {
                         errors.extend(errs);
                     }
@@ -260,15 +446,17 @@ impl StageTaskConfig<SinkGroup> for SinkGroupConfig {
                 Err(e) => errors.push(e),
             }
         }
-        let mut input = self.input.clone();
-        let _ = input.insert_value_from_context(context);
-        valid_or_insert_error!(errors, input, "sink.input");
         if errors.is_empty() {
             Ok(SinkGroup {
                 label: self.label.clone(),
                 result_name: input.value().expect("sink.input").to_owned(),
                 max_threads: self.max_threads,
                 sinks: subsinks,
+                rejects: rejects.map(|sink| SinkRejects {
+                    label: self.label.clone(),
+                    sink,
+                    splits,
+                }),
             })
         } else {
             Err(errors)
@@ -454,6 +642,8 @@ mod tests {
             input: StrKeyword::with_symbol("input"),
             max_threads: 1,
             sinks: configs,
+            constraint_policy: None,
+            rejects: None,
         };
         let mut model_registry = ModelRegistry::new();
         model_registry.insert(ModelConfig {
@@ -486,6 +676,8 @@ mod tests {
                 input: StrKeyword::with_value("input".to_owned()),
                 max_threads: 1,
                 sinks: configs,
+                constraint_policy: None,
+                rejects: None,
             };
             let mut model_registry = ModelRegistry::new();
             model_registry.insert(ModelConfig {
@@ -525,6 +717,8 @@ fp2: {}
             input: StrKeyword::with_value("SAMPLE".to_owned()),
             max_threads: 1,
             sinks: configs,
+            constraint_policy: None,
+            rejects: None,
         };
         let ctx = Arc::new(DefaultPipelineContext::with_results(&["SAMPLE"], 1).with_executing_sink(true));
         let mut bcast = ctx.get_broadcast("SAMPLE", "main").unwrap();
@@ -537,4 +731,162 @@ fp2: {}
             assert_frame_equal(reader.finish().unwrap(), default_next());
         }
     }
+
+    #[test]
+    fn valid_sink_group_constraint_policy() {
+        let configs_str = "
+- csv:
+    filepath: $fp
+    merge_type: insert
+    model_fields:
+        a:
+            dtype: int64
+            constraints: [primary]
+        b: str
+";
+        let frame = || df!( "a" => [1i64, 2, 2, 3], "b" => ["p", "q", "r", "s"] ).unwrap();
+        let group = |policy: &str| SinkGroupConfig {
+            label: "save_sample".to_owned(),
+            input: StrKeyword::with_value("SAMPLE".to_owned()),
+            max_threads: 1,
+            sinks: serde_yaml_ng::from_str::<Vec<serde_yaml_ng::Value>>(configs_str).unwrap(),
+            constraint_policy: Some(serde_yaml_ng::from_str(policy).unwrap()),
+            rejects: None,
+        };
+        let tmp = TempFile::default();
+        let context = serde_yaml_ng::from_str::<serde_yaml_ng::Mapping>(&format!("fp: {}", &tmp.filepath)).unwrap();
+        let ctx = Arc::new(DefaultPipelineContext::with_results(&["SAMPLE"], 1).with_executing_sink(true));
+        ctx.insert_result("SAMPLE", frame().lazy()).unwrap();
+        let actual = group("error").parse(&ctx, &context).unwrap();
+        assert!(actual.linear(ctx.clone()).is_err());
+        assert_eq!(tmp.get().unwrap().metadata().unwrap().len(), 0);
+
+        let actual = group("drop_violating_rows").parse(&ctx, &context).unwrap();
+        actual.linear(ctx.clone()).unwrap();
+        let written = CsvReader::new(tmp.get().unwrap()).finish().unwrap();
+        assert_frame_equal(written, df!( "a" => [1i64, 3], "b" => ["p", "s"] ).unwrap());
+    }
+
+    #[test]
+    fn valid_sink_group_rejects() {
+        let configs_str = "
+- csv:
+    filepath: $fp
+    merge_type: insert
+    model_fields:
+        a:
+            dtype: int64
+            constraints: [primary]
+        b: str
+";
+        let tmp = TempFile::default();
+        let tmp_rejects = TempFile::default();
+        let context_str = format!("{{fp: {}, rejects_fp: {}}}", &tmp.filepath, &tmp_rejects.filepath);
+        let context = serde_yaml_ng::from_str::<serde_yaml_ng::Mapping>(&context_str).unwrap();
+        let sgconfig = SinkGroupConfig {
+            label: "save_sample".to_owned(),
+            input: StrKeyword::with_value("SAMPLE".to_owned()),
+            max_threads: 1,
+            sinks: serde_yaml_ng::from_str::<Vec<serde_yaml_ng::Value>>(configs_str).unwrap(),
+            constraint_policy: None,
+            rejects: Some(serde_yaml_ng::from_str("{csv: {filepath: $rejects_fp, merge_type: insert}}").unwrap()),
+        };
+        let ctx = Arc::new(DefaultPipelineContext::with_results(&["SAMPLE"], 1).with_executing_sink(true));
+        let mut bcast = ctx.get_broadcast("SAMPLE", "main").unwrap();
+        bcast
+            .broadcast(
+                df!( "a" => ["1", "x", "2", "2", "3"], "b" => ["p", "q", "r", "s", "t"] )
+                    .unwrap()
+                    .lazy(),
+            )
+            .unwrap();
+        let invalid = SinkGroupConfig {
+            constraint_policy: Some(serde_yaml_ng::from_str("error").unwrap()),
+            ..sgconfig.clone()
+        };
+        assert!(invalid.parse(&ctx, &context).is_err());
+        let actual = sgconfig.parse(&ctx, &context).unwrap();
+        actual.linear(ctx.clone()).unwrap();
+        let written = CsvReader::new(tmp.get().unwrap()).finish().unwrap();
+        assert_frame_equal(written, df!( "a" => [1i64, 3], "b" => ["p", "t"] ).unwrap());
+        let rejected = CsvReader::new(tmp_rejects.get().unwrap()).finish().unwrap();
+        assert_frame_equal(
+            rejected,
+            df!(
+                "a" => ["x", "2", "2"],
+                "b" => ["q", "r", "s"],
+                "_error" => ["cast(a): not a valid i64", "primary(a): duplicate", "primary(a): duplicate"],
+                "_stage" => ["save_sample", "save_sample", "save_sample"],
+            )
+            .unwrap(),
+        );
+    }
+
+    #[test]
+    fn valid_sink_group_rejects_two_sinks() {
+        let configs_str = "
+- csv:
+    filepath: $fp1
+    merge_type: insert
+    model_fields:
+        a:
+            dtype: int64
+            constraints: [primary]
+        b: str
+- csv:
+    filepath: $fp2
+    merge_type: insert
+    model_fields:
+        a: int64
+";
+        for threads in [1, 2] {
+            let tmp1 = TempFile::default();
+            let tmp2 = TempFile::default();
+            let tmp_rejects = TempFile::default();
+            let context_str = format!(
+                "{{fp1: {}, fp2: {}, rejects_fp: {}}}",
+                &tmp1.filepath, &tmp2.filepath, &tmp_rejects.filepath
+            );
+            let context = serde_yaml_ng::from_str::<serde_yaml_ng::Mapping>(&context_str).unwrap();
+            let sgconfig = SinkGroupConfig {
+                label: "save_sample".to_owned(),
+                input: StrKeyword::with_value("SAMPLE".to_owned()),
+                max_threads: threads,
+                sinks: serde_yaml_ng::from_str::<Vec<serde_yaml_ng::Value>>(configs_str).unwrap(),
+                constraint_policy: None,
+                rejects: Some(serde_yaml_ng::from_str("{csv: {filepath: $rejects_fp, merge_type: insert}}").unwrap()),
+            };
+            let ctx = Arc::new(DefaultPipelineContext::with_results(&["SAMPLE"], 1).with_executing_sink(true));
+            let mut bcast = ctx.get_broadcast("SAMPLE", "main").unwrap();
+            bcast
+                .broadcast(
+                    df!( "a" => ["1", "x", "2", "2", "3"], "b" => ["p", "q", "r", "s", "t"] )
+                        .unwrap()
+                        .lazy(),
+                )
+                .unwrap();
+            let actual = sgconfig.parse(&ctx, &context).unwrap();
+            if threads == 1 {
+                actual.linear(ctx.clone()).unwrap();
+            } else {
+                actual.sync_exec(ctx.clone()).unwrap();
+            }
+            let written = CsvReader::new(tmp1.get().unwrap()).finish().unwrap();
+            assert_frame_equal(written, df!( "a" => [1i64, 3], "b" => ["p", "t"] ).unwrap());
+            let written = CsvReader::new(tmp2.get().unwrap()).finish().unwrap();
+            assert_frame_equal(written, df!( "a" => [1i64, 2, 2, 3] ).unwrap());
+            // the cast error is shared by both sinks, and written once
+            let rejected = CsvReader::new(tmp_rejects.get().unwrap()).finish().unwrap();
+            assert_frame_equal(
+                rejected,
+                df!(
+                    "a" => ["x", "2", "2"],
+                    "b" => ["q", "r", "s"],
+                    "_error" => ["cast(a): not a valid i64", "primary(a): duplicate", "primary(a): duplicate"],
+                    "_stage" => ["save_sample", "save_sample", "save_sample"],
+                )
+                .unwrap(),
+            );
+        }
+    }
 }
@@ -2,7 +2,12 @@ use serde::Deserialize;
 
 use crate::{
     model::common::ModelFields,
-    parser::{keyword::StrKeyword, merge_type::MergeTypeEnum, sql_connection::SqlConnection},
+    parser::{
+        keyword::{IntKeyword, StrKeyword},
+        merge_type::MergeTypeEnum,
+        model::{ConstraintPolicy, SchemaDriftPolicy},
+        sql_connection::SqlConnection,
+    },
 };
 
 #[derive(Debug, Clone, PartialEq, Eq, Deserialize)]
@@ -11,6 +16,10 @@ pub struct SinkGroupConfig {
     pub input: StrKeyword,
     pub max_threads: usize,
     pub sinks: Vec<serde_yaml_ng::Value>,
+    // checks frames against each sink's model constraints before writing
+    pub constraint_policy: Option<ConstraintPolicy>,
+    // sink receiving the rows that fail a model cast or constraint, with `_error` and `_stage` columns
+    pub rejects: Option<serde_yaml_ng::Value>,
 }
 
 #[derive(Debug, Clone, PartialEq, Eq, Deserialize)]
@@ -21,6 +30,17 @@ pub struct LocalFileSinkConfig {
     pub model_fields: Option<ModelFields>,
 }
 
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize)]
+pub struct _ParquetSinkConfig {
+    pub filepath: StrKeyword,
+    pub merge_type: MergeTypeEnum,
+    pub model: Option<StrKeyword>,
+    pub model_fields: Option<ModelFields>,
+    // one of uncompressed, snappy, gzip, lzo, brotli, zstd (default), lz4
+    pub compression: Option<StrKeyword>,
+    pub compression_level: Option<IntKeyword>,
+}
+
 #[derive(Debug, Clone, PartialEq, Eq, Deserialize)]
 pub struct ClickhouseTableOptions {
     pub order_by: Vec<StrKeyword>,
@@ -28,6 +48,8 @@ pub struct ClickhouseTableOptions {
     pub not_null: Option<Vec<StrKeyword>>,
     pub db_name: Option<StrKeyword>,
     pub create_table_if_not_exists: Option<bool>,
+    // compares frames against the model before inserting, `evolve` adds new model fields to the table
+    pub on_schema_drift: Option<SchemaDriftPolicy>,
 }
 
 #[derive(Debug, Clone, PartialEq, Eq, Deserialize)]
@@ -36,6 +58,29 @@ pub struct ClickhouseSinkConfig {
     pub options: Option<ClickhouseTableOptions>,
 }
 
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize)]
+pub struct SqlTableOptions {
+    pub create_table_if_not_exists: Option<bool>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize)]
+pub struct SqliteSinkConfig {
+    pub sqlite: SqlConnection,
+    pub options: Option<SqlTableOptions>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize)]
+pub struct PostgresSinkConfig {
+    pub postgres: SqlConnection,
+    pub options: Option<SqlTableOptions>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize)]
+pub struct MySqlSinkConfig {
+    pub mysql: SqlConnection,
+    pub options: Option<SqlTableOptions>,
+}
+
 #[derive(Debug, Clone, PartialEq, Eq, Deserialize)]
 pub struct JsonSinkConfig {
     pub json: LocalFileSinkConfig,
@@ -46,6 +91,16 @@ pub struct CsvSinkConfig {
     pub csv: LocalFileSinkConfig,
 }
 
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize)]
+pub struct BsonSinkConfig {
+    pub bson: LocalFileSinkConfig,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize)]
+pub struct ParquetSinkConfig {
+    pub parquet: _ParquetSinkConfig,
+}
+
 #[cfg(test)]
 mod tests {
 
@@ -59,7 +114,7 @@ mod tests {
             merge_type::MergeTypeEnum,
             sql_connection::SqlConnection,
         },
-        task::sink::config::{CsvSinkConfig, JsonSinkConfig},
+        task::sink::config::{BsonSinkConfig, CsvSinkConfig, JsonSinkConfig},
     };
 
     use super::{ClickhouseSinkConfig, ClickhouseTableOptions, LocalFileSinkConfig};
@@ -93,6 +148,7 @@ options:
             )])),
             strict: None,
             merge_type: Some(MergeTypeEnum::Insert),
+            on_schema_drift: None,
         };
         let options = ClickhouseTableOptions {
             order_by: vec![StrKeyword::with_value("first".to_owned())],
@@ -103,6 +159,7 @@ options:
             not_null: None,
             db_name: None,
             create_table_if_not_exists: None,
+            on_schema_drift: None,
         };
         let expected = ClickhouseSinkConfig {
             clickhouse,
@@ -183,4 +240,21 @@ options:
             );
         }
     }
+
+    #[test]
+    fn valid_sink_config_bson() {
+        let configs = get_configs()
+            .iter()
+            .map(|c| c.replace("{}", "bson"))
+            .collect::<Vec<String>>();
+        let locals = get_locals();
+        for i in 0..2 {
+            assert_eq!(
+                BsonSinkConfig {
+                    bson: locals[i].clone()
+                },
+                serde_yaml_ng::from_str::<BsonSinkConfig>(&configs[i]).unwrap()
+            );
+        }
+    }
 }
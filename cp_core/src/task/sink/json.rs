@@ -8,7 +8,7 @@ use polars::{
 };
 
 use crate::{
-    model::common::ModelConfig,
+    model::common::{ModelConfig, ModelFields},
     model_emplace,
     parser::{keyword::Keyword, merge_type::MergeTypeEnum},
     pipeline::context::{DefaultPipelineContext, PipelineContext},
@@ -78,6 +78,12 @@ impl Sink for JsonSink {
                 }
                 OpenOptions::new().write(true).create(true).truncate(true).open(fp)?
             }
+            MergeTypeEnum::Upsert => {
+                return Err(CpError::TaskError(
+                    "JsonSink",
+                    "upsert merge_type is not supported for file sinks".to_owned(),
+                ));
+            }
         };
         let mut df_to_write = if let Some(schema) = &self.schema {
             dataframe.lazy().select(schema.clone()).collect()?
@@ -106,6 +112,10 @@ impl SinkConfig for JsonSinkConfig {
         Ok(())
     }
 
+    fn model_fields(&self) -> Option<&ModelFields> {
+        self.json.model_fields.as_ref()
+    }
+
     fn validate(&self) -> Vec<CpError> {
         let mut errors = vec![];
         match self.json.filepath.value() {
@@ -115,6 +125,12 @@ impl SinkConfig for JsonSinkConfig {
                 self.json.filepath.symbol().unwrap_or("?").to_owned(),
             )),
         }
+        if self.json.merge_type == MergeTypeEnum::Upsert {
+            errors.push(CpError::ConfigValidationError(
+                "sink[json].merge_type",
+                "upsert is not supported for json, use insert, replace or next".to_owned(),
+            ));
+        }
         errors
     }
 
@@ -1,5 +1,9 @@
+pub mod bson;
 pub mod clickhouse;
 pub mod common;
 pub mod config;
 pub mod csv;
 pub mod json;
+pub mod parquet;
+pub mod sql;
+pub mod sqlite;
@@ -0,0 +1,458 @@
+use std::{fs::File, path::PathBuf, str::FromStr, sync::Arc};
+
+use async_trait::async_trait;
+use polars::{
+    frame::DataFrame,
+    io::SerReader,
+    prelude::{BrotliLevel, Expr, GzipLevel, IntoLazy, ParquetCompression, ParquetReader, ParquetWriter, ZstdLevel},
+};
+
+use crate::{
+    model::common::{ModelConfig, ModelFields},
+    model_emplace,
+    parser::{keyword::Keyword, merge_type::MergeTypeEnum},
+    pipeline::context::{DefaultPipelineContext, PipelineContext},
+    util::{
+        common::{get_full_path, get_utc_time_str_now, rng_str},
+        error::{CpError, CpResult},
+    },
+};
+
+use super::{
+    common::{Sink, SinkConfig},
+    config::ParquetSinkConfig,
+};
+
+pub struct ParquetSink {
+    merge_type: MergeTypeEnum,
+    filepath: PathBuf,
+    compression: ParquetCompression,
+    schema: Option<Vec<Expr>>,
+}
+
+/// Parses the compression codec and its optional level, e.g. `zstd` with level `3`.
+pub fn parse_compression(compression: &str, level: Option<i64>) -> CpResult<ParquetCompression> {
+    let codec = match (compression.to_lowercase().as_str(), level) {
+        ("uncompressed", None) => ParquetCompression::Uncompressed,
+        ("snappy", None) => ParquetCompression::Snappy,
+        ("lzo", None) => ParquetCompression::Lzo,
+        ("lz4", None) => ParquetCompression::Lz4Raw,
+        ("gzip", level) => ParquetCompression::Gzip(match level {
+            Some(x) => Some(GzipLevel::try_new(x.try_into().unwrap_or(u8::MAX))?),
+            None => None,
+        }),
+        ("brotli", level) => ParquetCompression::Brotli(match level {
+            Some(x) => Some(BrotliLevel::try_new(x.try_into().unwrap_or(u32::MAX))?),
+            None => None,
+        }),
+        ("zstd", level) => ParquetCompression::Zstd(match level {
+            Some(x) => Some(ZstdLevel::try_new(x.try_into().unwrap_or(i32::MAX))?),
+            None => None,
+        }),
+        (name, Some(x)) if ["uncompressed", "snappy", "lzo", "lz4"].contains(&name) => {
+            return Err(CpError::ConfigError(
+                "invalid parquet compression level",
+                format!("`{}` does not take a compression level, found {}", name, x),
+            ));
+        }
+        (name, _) => {
+            return Err(CpError::ConfigError(
+                "invalid parquet compression",
+                format!(
+                    "`{}` is not one of uncompressed, snappy, gzip, lzo, brotli, zstd, lz4",
+                    name
+                ),
+            ));
+        }
+    };
+    Ok(codec)
+}
+
+impl ParquetSink {
+    pub fn new(filepath: &str, merge_type: Option<MergeTypeEnum>) -> Self {
+        Self {
+            filepath: std::path::PathBuf::from_str(filepath).expect("bad filepath"),
+            merge_type: merge_type.unwrap_or(MergeTypeEnum::Replace),
+            compression: ParquetCompression::default(),
+            schema: None,
+        }
+    }
+
+    pub fn with_schema(mut self, columns: Vec<Expr>) -> Self {
+        let _ = self.schema.insert(columns);
+        self
+    }
+
+    pub fn with_compression(mut self, compression: ParquetCompression) -> Self {
+        self.compression = compression;
+        self
+    }
+}
+
+#[async_trait]
+impl Sink for ParquetSink {
+    fn connection_type(&self) -> &str {
+        "parquet"
+    }
+
+    async fn fetch(&self, dataframe: DataFrame, ctx: Arc<DefaultPipelineContext>) -> CpResult<()> {
+        self.run(dataframe, ctx)
+    }
+
+    fn run(&self, dataframe: DataFrame, ctx: Arc<DefaultPipelineContext>) -> CpResult<()> {
+        let mut df_to_write = if let Some(schema) = &self.schema {
+            dataframe.lazy().select(schema.clone()).collect()?
+        } else {
+            dataframe
+        };
+        let filepath = match self.merge_type {
+            MergeTypeEnum::Replace => self.filepath.clone(),
+            MergeTypeEnum::Insert => {
+                // parquet files cannot be appended to, so the existing rows are read and rewritten
+                if std::fs::exists(&self.filepath)? && std::fs::metadata(&self.filepath)?.len() > 0 {
+                    let mut existing = ParquetReader::new(File::open(&self.filepath)?).finish()?;
+                    existing.vstack_mut(&df_to_write)?;
+                    df_to_write = existing;
+                }
+                self.filepath.clone()
+            }
+            MergeTypeEnum::MakeNext => {
+                let mut fp = self.filepath.clone();
+                fp.set_file_name(format!(
+                    "{}_{}.parquet",
+                    fp.file_stem().expect("filename").to_str().unwrap_or("_"),
+                    get_utc_time_str_now()
+                ));
+                if std::fs::exists(&fp)? {
+                    fp.set_file_name(format!(
+                        "{}_{}.parquet",
+                        fp.file_stem().expect("filename").to_str().unwrap_or("_"),
+                        rng_str(6)
+                    ));
+                }
+                fp
+            }
+            MergeTypeEnum::Upsert => {
+                return Err(CpError::TaskError(
+                    "ParquetSink",
+                    "upsert merge_type is not supported for file sinks".to_owned(),
+                ));
+            }
+        };
+        if ctx.is_executing_sink() {
+            let writer = ParquetWriter::new(File::create(&filepath)?).with_compression(self.compression);
+            writer.finish(&mut df_to_write)?;
+        } else {
+            log::info!(
+                "[no-execute-sink] Completed writing to {:?}: {:?}",
+                filepath,
+                df_to_write
+            );
+        }
+        Ok(())
+    }
+}
+
+impl SinkConfig for ParquetSinkConfig {
+    fn emplace(&mut self, ctx: &DefaultPipelineContext, context: &serde_yaml_ng::Mapping) -> CpResult<()> {
+        self.parquet.filepath.insert_value_from_context(context)?;
+        if let Some(mut compression) = self.parquet.compression.take() {
+            compression.insert_value_from_context(context)?;
+            let _ = self.parquet.compression.insert(compression);
+        }
+        if let Some(mut level) = self.parquet.compression_level.take() {
+            level.insert_value_from_context(context)?;
+            let _ = self.parquet.compression_level.insert(level);
+        }
+        model_emplace!(self.parquet, ctx, context);
+        Ok(())
+    }
+
+    fn model_fields(&self) -> Option<&ModelFields> {
+        self.parquet.model_fields.as_ref()
+    }
+
+    fn validate(&self) -> Vec<CpError> {
+        let mut errors = vec![];
+        match self.parquet.filepath.value() {
+            Some(_) => {}
+            None => errors.push(CpError::SymbolMissingValueError(
+                "filepath",
+                self.parquet.filepath.symbol().unwrap_or("?").to_owned(),
+            )),
+        }
+        let compression = self.parquet.compression.as_ref().map(|x| x.value());
+        let level = self.parquet.compression_level.as_ref().map(|x| x.value());
+        match (compression, level) {
+            (Some(None), _) => errors.push(CpError::SymbolMissingValueError(
+                "compression",
+                self.parquet
+                    .compression
+                    .as_ref()
+                    .and_then(|x| x.symbol())
+                    .unwrap_or("?")
+                    .to_owned(),
+            )),
+            (_, Some(None)) => errors.push(CpError::SymbolMissingValueError(
+                "compression_level",
+                self.parquet
+                    .compression_level
+                    .as_ref()
+                    .and_then(|x| x.symbol())
+                    .unwrap_or("?")
+                    .to_owned(),
+            )),
+            (None, Some(_)) => errors.push(CpError::ConfigValidationError(
+                "sink[parquet].compression_level",
+                "compression_level requires a compression".to_owned(),
+            )),
+            (Some(Some(compression)), level) => {
+                if let Err(e) = parse_compression(compression, level.flatten().copied()) {
+                    errors.push(e);
+                }
+            }
+            (None, None) => {}
+        }
+        if self.parquet.merge_type == MergeTypeEnum::Upsert {
+            errors.push(CpError::ConfigValidationError(
+                "sink[parquet].merge_type",
+                "upsert is not supported for parquet, use insert, replace or next".to_owned(),
+            ));
+        }
+        errors
+    }
+
+    fn transform(&self) -> Box<dyn Sink> {
+        let fp = match get_full_path(self.parquet.filepath.value().expect("filepath"), false) {
+            Ok(x) => x,
+            Err(e) => panic!("bad filepath `{:?}`: {}", self.parquet.filepath.value(), e),
+        };
+        if self.parquet.merge_type == MergeTypeEnum::Insert {
+            log::warn!("INSERT merge_type can be costly for parquet: {:?}", &fp);
+        }
+        let compression = match self.parquet.compression.as_ref() {
+            Some(compression) => parse_compression(
+                compression.value().expect("compression"),
+                self.parquet
+                    .compression_level
+                    .as_ref()
+                    .map(|x| *x.value().expect("compression_level")),
+            )
+            .expect("bad compression"),
+            None => ParquetCompression::default(),
+        };
+        let schema = self.parquet.model_fields.as_ref().map(|x| {
+            ModelConfig {
+                label: "".to_string(),
+                fields: x.clone(),
+            }
+            .columns()
+            .expect("failed to build schema")
+        });
+        Box::new(ParquetSink {
+            filepath: fp,
+            merge_type: self.parquet.merge_type,
+            compression,
+            schema,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Arc;
+
+    use polars::{
+        df,
+        frame::DataFrame,
+        io::SerReader,
+        prelude::{DataType, IntoLazy, ParquetCompression, ParquetReader, ZstdLevel, col},
+    };
+
+    use crate::{
+        async_st,
+        context::model::ModelRegistry,
+        model::common::{ModelConfig, ModelFieldInfo, ModelFields},
+        parser::{
+            dtype::DType,
+            keyword::{Keyword, ModelFieldKeyword, StrKeyword},
+            merge_type::MergeTypeEnum,
+        },
+        pipeline::context::DefaultPipelineContext,
+        task::sink::{
+            common::{Sink, SinkConfig},
+            config::{_ParquetSinkConfig, ParquetSinkConfig},
+        },
+        util::{common::rng_str, test::assert_frame_equal, tmp::TempFile},
+    };
+
+    use super::{ParquetSink, parse_compression};
+
+    fn example() -> DataFrame {
+        df!(
+            "a" => [-1, 1, 3, 5, 6],
+            "b" => ["z", "a", "j", "i", "c"],
+        )
+        .unwrap()
+    }
+
+    fn read(filepath: &str) -> DataFrame {
+        ParquetReader::new(std::fs::File::open(filepath).unwrap())
+            .finish()
+            .unwrap()
+    }
+
+    fn get_node(
+        merge_type: MergeTypeEnum,
+        tmp: TempFile,
+        config: &str,
+    ) -> (Arc<DefaultPipelineContext>, Box<dyn Sink>, TempFile) {
+        let mut sink_config: ParquetSinkConfig = serde_yaml_ng::from_str(config).unwrap();
+        sink_config.parquet.merge_type = merge_type;
+        let ctx = Arc::new(DefaultPipelineContext::new().with_executing_sink(true));
+        let context = format!("sample: {}\ncodec: zstd\nlevel: 3", &tmp.filepath);
+        let context = serde_yaml_ng::from_str::<serde_yaml_ng::Mapping>(context.as_str()).unwrap();
+        sink_config.emplace(&ctx, &context).unwrap();
+        assert!(sink_config.validate().is_empty());
+        (ctx, sink_config.transform(), tmp)
+    }
+
+    const CONFIG: &str = "
+parquet:
+    filepath: $sample
+    merge_type: replace
+    compression: $codec
+    compression_level: $level
+";
+
+    #[test]
+    fn valid_parquet_sink_async() {
+        let expected = example();
+        let tmp = TempFile::default();
+        let parquet_sink = ParquetSink::new(&tmp.filepath, None);
+        let ctx = Arc::new(DefaultPipelineContext::new().with_executing_sink(true));
+        async_st!(async || {
+            parquet_sink.fetch(expected.clone(), ctx).await.unwrap();
+            assert_frame_equal(read(&tmp.filepath), expected);
+            assert_eq!(parquet_sink.connection_type(), "parquet");
+        });
+    }
+
+    #[test]
+    fn valid_parquet_sink_config_to_parquet_sink_replace() {
+        let (ctx, actual_node, tmp) = get_node(MergeTypeEnum::Replace, TempFile::default(), CONFIG);
+        actual_node.run(example(), ctx.clone()).unwrap();
+        actual_node.run(example(), ctx.clone()).unwrap();
+        assert_frame_equal(read(&tmp.filepath), example());
+    }
+
+    #[test]
+    fn valid_parquet_sink_config_to_parquet_sink_insert() {
+        let (ctx, actual_node, tmp) = get_node(MergeTypeEnum::Insert, TempFile::default(), CONFIG);
+        actual_node.run(example(), ctx.clone()).unwrap();
+        actual_node.run(example(), ctx.clone()).unwrap();
+        assert_frame_equal(read(&tmp.filepath), example().vstack(&example()).unwrap());
+    }
+
+    #[test]
+    fn valid_parquet_sink_config_to_parquet_sink_make_next() {
+        let expected = example();
+        let dir = format!("/tmp/capport_testing/{}", rng_str(5));
+        {
+            std::fs::create_dir_all(&dir).unwrap();
+            let tmp = TempFile::default_in_dir(&dir, "parquet").unwrap();
+            let (ctx, actual_node, _) = get_node(MergeTypeEnum::MakeNext, tmp, CONFIG);
+            let files_created = 3i32;
+            for _ in 0..files_created {
+                actual_node.run(expected.clone(), ctx.clone()).unwrap();
+            }
+            let count = std::fs::read_dir(&dir).unwrap().fold(0i32, |idx, file| {
+                let actual = read(file.unwrap().path().to_str().unwrap());
+                assert_frame_equal(actual, expected.clone());
+                idx + 1
+            });
+            assert_eq!(count, files_created);
+        }
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn valid_parquet_sink_config_to_parquet_sink_model_fields() {
+        let tmp = TempFile::default();
+        let mut sink_config = ParquetSinkConfig {
+            parquet: _ParquetSinkConfig {
+                filepath: StrKeyword::with_value(tmp.filepath.clone()),
+                merge_type: MergeTypeEnum::Replace,
+                model: Some(StrKeyword::with_value("test".to_owned())),
+                model_fields: None,
+                compression: None,
+                compression_level: None,
+            },
+        };
+        let mut model_registry = ModelRegistry::new();
+        model_registry.insert(ModelConfig {
+            label: "test".to_string(),
+            fields: ModelFields::from([(
+                StrKeyword::with_value("a".to_owned()),
+                ModelFieldKeyword::with_value(ModelFieldInfo::with_dtype(DType(DataType::Int8))),
+            )]),
+        });
+        let ctx = Arc::new(
+            DefaultPipelineContext::new()
+                .with_executing_sink(true)
+                .with_model_registry(model_registry),
+        );
+        let context = serde_yaml_ng::from_str::<serde_yaml_ng::Mapping>("{}").unwrap();
+        sink_config.emplace(&ctx, &context).unwrap();
+        assert!(sink_config.validate().is_empty());
+        sink_config.transform().run(example(), ctx.clone()).unwrap();
+        let expected = example()
+            .lazy()
+            .select(&[col("a").cast(DataType::Int8)])
+            .collect()
+            .unwrap();
+        assert_frame_equal(read(&tmp.filepath), expected);
+    }
+
+    #[test]
+    fn valid_parquet_sink_exec_mode_off() {
+        let tmp = TempFile::default();
+        let parquet_sink = ParquetSink::new(&tmp.filepath, None);
+        let ctx = Arc::new(DefaultPipelineContext::new().with_executing_sink(false));
+        parquet_sink.run(example(), ctx).unwrap();
+        assert_eq!(std::fs::metadata(&tmp.filepath).unwrap().len(), 0);
+    }
+
+    #[test]
+    fn valid_invalid_parse_compression() {
+        assert_eq!(
+            parse_compression("ZSTD", Some(3)).unwrap(),
+            ParquetCompression::Zstd(Some(ZstdLevel::try_new(3).unwrap()))
+        );
+        assert_eq!(parse_compression("snappy", None).unwrap(), ParquetCompression::Snappy);
+        assert_eq!(parse_compression("lz4", None).unwrap(), ParquetCompression::Lz4Raw);
+        assert!(parse_compression("snappy", Some(1)).is_err());
+        assert!(parse_compression("gzip", Some(100)).is_err());
+        assert!(parse_compression("zstd", Some(i64::MAX)).is_err());
+        assert!(parse_compression("bzip2", None).is_err());
+        let config: ParquetSinkConfig =
+            serde_yaml_ng::from_str("parquet: { filepath: a, merge_type: insert, compression_level: 3 }").unwrap();
+        assert_eq!(config.validate().len(), 1);
+        assert!(
+            serde_yaml_ng::from_str::<ParquetSinkConfig>(
+                "parquet: { filepath: a, merge_type: insert, compression: zstd, compression_level: fast }"
+            )
+            .is_err()
+        );
+    }
+
+    #[test]
+    fn valid_parquet_sink_compression_level_from_context() {
+        let mut sink_config: ParquetSinkConfig = serde_yaml_ng::from_str(CONFIG).unwrap();
+        let context =
+            serde_yaml_ng::from_str::<serde_yaml_ng::Mapping>("{ sample: a.parquet, codec: gzip, level: 9 }").unwrap();
+        sink_config.emplace(&DefaultPipelineContext::new(), &context).unwrap();
+        assert!(sink_config.validate().is_empty());
+        assert_eq!(sink_config.parquet.compression_level.unwrap().value(), Some(&9));
+    }
+}
@@ -0,0 +1,523 @@
+use std::{path::PathBuf, sync::Arc};
+
+use async_trait::async_trait;
+use polars::{
+    frame::DataFrame,
+    prelude::{AnyValue, DataType, Expr, IntoLazy, Schema},
+};
+use rusqlite::{Connection, params_from_iter, types::Value};
+
+use crate::{
+    model::common::ModelFields,
+    parser::{keyword::Keyword, merge_type::MergeTypeEnum},
+    pipeline::context::{DefaultPipelineContext, PipelineContext},
+    util::{
+        common::{date_to_iso_str, datetime_to_iso_str, get_utc_time_ident_now, time_to_iso_str},
+        error::{CpError, CpResult},
+    },
+    valid_or_insert_error,
+};
+
+use super::{
+    common::{Sink, SinkConfig},
+    config::SqliteSinkConfig,
+};
+
+pub struct SqliteSink {
+    filepath: PathBuf,
+    table: String,
+    merge_type: MergeTypeEnum,
+    create_table_if_not_exists: bool,
+    strict: bool,
+    columns: Option<Vec<Expr>>,
+    primary_keys: Vec<String>,
+}
+
+fn quote_ident(ident: &str) -> String {
+    format!("\"{}\"", ident.replace('"', "\"\""))
+}
+
+/// The declared column type, chosen so that the sqlite source reads the column back with the same dtype.
+fn sqlite_type(dtype: &DataType) -> &'static str {
+    match dtype {
+        DataType::Boolean => "BOOLEAN",
+        x if x.is_integer() => "INTEGER",
+        x if x.is_float() => "REAL",
+        DataType::Date => "DATE",
+        DataType::Datetime(_, _) => "DATETIME",
+        DataType::Time => "TIME",
+        DataType::Binary => "BLOB",
+        _ => "TEXT",
+    }
+}
+
+/// Converts a polars value into a sqlite value. Temporal values are written as iso-8601 text
+/// (datetimes in utc), the format sqlite's date and time functions expect.
+fn sqlite_value(value: AnyValue) -> Value {
+    match value {
+        AnyValue::Null => Value::Null,
+        AnyValue::Boolean(x) => Value::Integer(x as i64),
+        AnyValue::Int8(x) => Value::Integer(x as i64),
+        AnyValue::Int16(x) => Value::Integer(x as i64),
+        AnyValue::Int32(x) => Value::Integer(x as i64),
+        AnyValue::Int64(x) => Value::Integer(x),
+        AnyValue::UInt8(x) => Value::Integer(x as i64),
+        AnyValue::UInt16(x) => Value::Integer(x as i64),
+        AnyValue::UInt32(x) => Value::Integer(x as i64),
+        AnyValue::UInt64(x) => i64::try_from(x).map_or(Value::Real(x as f64), Value::Integer),
+        AnyValue::Float32(x) => Value::Real(x as f64),
+        AnyValue::Float64(x) => Value::Real(x),
+        AnyValue::Binary(x) => Value::Blob(x.to_vec()),
+        AnyValue::BinaryOwned(x) => Value::Blob(x),
+        AnyValue::Date(days) => date_to_iso_str(days).map_or(Value::Null, Value::Text),
+        AnyValue::Datetime(x, unit, _) | AnyValue::DatetimeOwned(x, unit, _) => {
+            datetime_to_iso_str(x, unit).map_or(Value::Null, Value::Text)
+        }
+        AnyValue::Time(ns) => time_to_iso_str(ns).map_or(Value::Null, Value::Text),
+        x => match x.get_str() {
+            Some(s) => Value::Text(s.to_owned()),
+            None => Value::Text(x.to_string()),
+        },
+    }
+}
+
+fn create_query(table: &str, schema: &Schema, primary_keys: &[String]) -> String {
+    let mut fields = schema
+        .iter()
+        .map(|(name, dtype)| format!("{} {}", quote_ident(name), sqlite_type(dtype)))
+        .collect::<Vec<_>>();
+    if !primary_keys.is_empty() {
+        let keys = primary_keys.iter().map(|x| quote_ident(x)).collect::<Vec<_>>();
+        fields.push(format!("PRIMARY KEY ({})", keys.join(", ")));
+    }
+    format!(
+        "CREATE TABLE IF NOT EXISTS {} ({})",
+        quote_ident(table),
+        fields.join(", ")
+    )
+}
+
+/// With `upsert_keys`, rows conflicting on those keys update the remaining columns in place.
+fn insert_query(table: &str, schema: &Schema, upsert_keys: Option<&[String]>) -> String {
+    let names = schema.iter_names().map(|x| quote_ident(x)).collect::<Vec<_>>();
+    let params = (1..=names.len()).map(|x| format!("?{}", x)).collect::<Vec<_>>();
+    let mut query = format!(
+        "INSERT INTO {} ({}) VALUES ({})",
+        quote_ident(table),
+        names.join(", "),
+        params.join(", ")
+    );
+    if let Some(keys) = upsert_keys {
+        let updates = schema
+            .iter_names()
+            .filter(|x| !keys.iter().any(|key| key == x.as_str()))
+            .map(|x| format!("{0} = excluded.{0}", quote_ident(x)))
+            .collect::<Vec<_>>();
+        let keys = keys.iter().map(|x| quote_ident(x)).collect::<Vec<_>>();
+        if updates.is_empty() {
+            query.push_str(&format!(" ON CONFLICT ({}) DO NOTHING", keys.join(", ")));
+        } else {
+            query.push_str(&format!(
+                " ON CONFLICT ({}) DO UPDATE SET {}",
+                keys.join(", "),
+                updates.join(", ")
+            ));
+        }
+    }
+    query
+}
+
+impl SqliteSink {
+    pub fn new(filepath: PathBuf, table: &str, merge_type: MergeTypeEnum) -> Self {
+        Self {
+            filepath,
+            table: table.to_owned(),
+            merge_type,
+            create_table_if_not_exists: false,
+            strict: true,
+            columns: None,
+            primary_keys: vec![],
+        }
+    }
+
+    pub fn with_columns(mut self, columns: Vec<Expr>, strict: bool) -> Self {
+        let _ = self.columns.insert(columns);
+        self.strict = strict;
+        self
+    }
+
+    pub fn with_primary_keys(mut self, primary_keys: Vec<String>) -> Self {
+        self.primary_keys = primary_keys;
+        self
+    }
+
+    pub fn with_create_table(mut self, create_table_if_not_exists: bool) -> Self {
+        self.create_table_if_not_exists = create_table_if_not_exists;
+        self
+    }
+
+    fn write(&self, frame: &DataFrame) -> CpResult<()> {
+        let mut conn = Connection::open(&self.filepath)?;
+        let tx = conn.transaction()?;
+        let schema = frame.schema();
+        let table = match self.merge_type {
+            MergeTypeEnum::MakeNext => format!("{}_{}", &self.table, get_utc_time_ident_now()),
+            _ => self.table.clone(),
+        };
+        if self.create_table_if_not_exists || self.merge_type == MergeTypeEnum::MakeNext {
+            let create = create_query(&table, &schema, &self.primary_keys);
+            log::debug!("Table update: {}", create);
+            tx.execute(&create, [])?;
+        }
+        if self.merge_type == MergeTypeEnum::Replace {
+            tx.execute(&format!("DELETE FROM {}", quote_ident(&table)), [])?;
+        }
+        {
+            let upsert_keys = (self.merge_type == MergeTypeEnum::Upsert).then_some(self.primary_keys.as_slice());
+            let insert = insert_query(&table, &schema, upsert_keys);
+            log::debug!("Inserting: {}", insert);
+            let mut statement = tx.prepare(&insert)?;
+            let columns = frame.get_columns();
+            for idx in 0..frame.height() {
+                let row = columns
+                    .iter()
+                    .map(|column| column.get(idx).map(sqlite_value))
+                    .collect::<Result<Vec<_>, _>>()?;
+                statement.execute(params_from_iter(row))?;
+            }
+        }
+        tx.commit()?;
+        Ok(())
+    }
+}
+
+#[async_trait]
+impl Sink for SqliteSink {
+    fn connection_type(&self) -> &str {
+        "sqlite"
+    }
+
+    async fn fetch(&self, dataframe: DataFrame, ctx: Arc<DefaultPipelineContext>) -> CpResult<()> {
+        self.run(dataframe, ctx)
+    }
+
+    fn run(&self, dataframe: DataFrame, ctx: Arc<DefaultPipelineContext>) -> CpResult<()> {
+        let df_to_write = match &self.columns {
+            Some(columns) if self.strict => dataframe.lazy().select(columns.clone()).collect()?,
+            Some(columns) => dataframe.lazy().with_columns(columns.clone()).collect()?,
+            None => dataframe,
+        };
+        if ctx.is_executing_sink() {
+            self.write(&df_to_write)?;
+        } else {
+            log::info!(
+                "[no-execute-sink] Completed writing to {:?} ({}): {:?}",
+                &self.filepath,
+                &self.table,
+                df_to_write
+            );
+        }
+        Ok(())
+    }
+}
+
+impl SinkConfig for SqliteSinkConfig {
+    fn emplace(&mut self, ctx: &DefaultPipelineContext, context: &serde_yaml_ng::Mapping) -> CpResult<()> {
+        self.sqlite.emplace(ctx, context, "sqlite://")?;
+        self.sqlite.emplace_sqlite_url(false)
+    }
+
+    fn model_fields(&self) -> Option<&ModelFields> {
+        self.sqlite.model_fields.as_ref()
+    }
+
+    fn validate(&self) -> Vec<CpError> {
+        let mut errors = vec![];
+        match &self.sqlite.url {
+            Some(url) => valid_or_insert_error!(errors, url, "sink[sqlite].url"),
+            None => errors.push(CpError::ConfigValidationError(
+                "sink[sqlite].url",
+                "Missing database filepath, please declare".to_owned(),
+            )),
+        }
+        valid_or_insert_error!(errors, self.sqlite.table, "sink[sqlite].table");
+        if let Some(output) = &self.sqlite.output {
+            log::warn!("Output {:?} ignored in SqliteSinkConfig", output);
+        }
+        if let Some(sql) = &self.sqlite.sql {
+            log::warn!("Sql {:?} ignored in SqliteSinkConfig", sql);
+        }
+        if self.sqlite.merge_type.is_none() {
+            errors.push(CpError::ConfigValidationError(
+                "sink[sqlite].merge_type",
+                "Missing table merge_type, please declare".to_owned(),
+            ));
+        }
+        if let Some(model_fields) = &self.sqlite.model_fields {
+            for (key_kw, field_kw) in model_fields.iter() {
+                valid_or_insert_error!(errors, key_kw, "sink[sqlite].model.key");
+                valid_or_insert_error!(errors, field_kw, "sink[sqlite].model.field");
+            }
+        }
+        if self.sqlite.merge_type == Some(MergeTypeEnum::Upsert) && self.sqlite.primary_keys().is_empty() {
+            errors.push(CpError::ConfigValidationError(
+                "sink[sqlite].merge_type",
+                "Upsert requires model fields with a primary constraint".to_owned(),
+            ));
+        }
+        errors
+    }
+
+    fn transform(&self) -> Box<dyn Sink> {
+        let url = self
+            .sqlite
+            .url
+            .as_ref()
+            .and_then(|x| x.value())
+            .expect("sink[sqlite].url");
+        let filepath = PathBuf::from(url.strip_prefix("sqlite://").unwrap_or(url));
+        let mut sink = SqliteSink::new(
+            filepath,
+            self.sqlite.table.value().expect("sink[sqlite].table"),
+            self.sqlite.merge_type.expect("sink[sqlite].merge_type"),
+        )
+        .with_primary_keys(self.sqlite.primary_keys())
+        .with_create_table(
+            self.options
+                .as_ref()
+                .and_then(|x| x.create_table_if_not_exists)
+                .unwrap_or(false),
+        );
+        if let Some(columns) = self.sqlite.columns() {
+            sink = sink.with_columns(columns, self.sqlite.strict.unwrap_or(true));
+        }
+        Box::new(sink)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{
+        path::{Path, PathBuf},
+        sync::Arc,
+    };
+
+    use polars::{
+        df,
+        frame::DataFrame,
+        prelude::{DataType, IntoLazy, TimeUnit, col},
+    };
+    use rusqlite::Connection;
+
+    use crate::{
+        async_st,
+        parser::{keyword::Keyword, merge_type::MergeTypeEnum},
+        pipeline::context::DefaultPipelineContext,
+        task::{
+            sink::{
+                common::{Sink, SinkConfig},
+                config::SqliteSinkConfig,
+            },
+            source::{common::SourceConfig, config::SqliteSourceConfig},
+        },
+        util::{common::rng_str, test::assert_frame_equal},
+    };
+
+    use super::SqliteSink;
+
+    fn example() -> DataFrame {
+        df!(
+            "id" => [1i64, 2, 3],
+            "name" => ["Connor", "Sidney", "Nathan"],
+            "birthdate" => ["1997-01-13", "1987-08-07", "1995-09-01"],
+            "updated" => ["2023-11-14T22:13:20", "2023-11-14T22:13:21", "2023-11-14T22:13:22"],
+        )
+        .unwrap()
+        .lazy()
+        .with_columns([
+            col("birthdate").cast(DataType::Date),
+            col("updated").cast(DataType::Datetime(TimeUnit::Milliseconds, None)),
+        ])
+        .collect()
+        .unwrap()
+    }
+
+    fn db_path() -> PathBuf {
+        let dir = std::env::temp_dir().join("capport_testing");
+        std::fs::create_dir_all(&dir).unwrap();
+        dir.join(format!("{}.db", rng_str(8)))
+    }
+
+    fn count(path: &Path, table: &str) -> i64 {
+        Connection::open(path)
+            .unwrap()
+            .query_row(&format!("SELECT COUNT(*) FROM \"{}\"", table), [], |row| row.get(0))
+            .unwrap()
+    }
+
+    fn config(path: &Path, merge_type: &str) -> SqliteSinkConfig {
+        serde_yaml_ng::from_str::<SqliteSinkConfig>(&format!(
+            "
+sqlite:
+    url: {}
+    table: players
+    merge_type: {}
+    model_fields:
+        id: int64
+        name: str
+        birthdate: date
+        updated: datetime_utc
+options:
+    create_table_if_not_exists: true
+",
+            path.to_str().unwrap(),
+            merge_type
+        ))
+        .unwrap()
+    }
+
+    fn get_node(path: &Path, merge_type: &str) -> (Arc<DefaultPipelineContext>, Box<dyn Sink>) {
+        let mut config = config(path, merge_type);
+        let ctx = DefaultPipelineContext::new().with_executing_sink(true);
+        config.emplace(&ctx, &serde_yaml_ng::Mapping::new()).unwrap();
+        assert!(config.validate().is_empty());
+        (Arc::new(ctx), config.transform())
+    }
+
+    #[test]
+    fn valid_sqlite_sink_and_source_round_trip() {
+        let path = db_path();
+        let (ctx, sink) = get_node(&path, "replace");
+        assert_eq!(sink.connection_type(), "sqlite");
+        sink.run(example(), ctx.clone()).unwrap();
+        let mut source_config = serde_yaml_ng::from_str::<SqliteSourceConfig>(&format!(
+            "
+sqlite:
+    url: sqlite://{}
+    table: players
+    output: PLAYERS
+    model_fields:
+        id: int64
+        name: str
+        birthdate: date
+        updated: datetime_utc
+",
+            path.to_str().unwrap()
+        ))
+        .unwrap();
+        source_config.emplace(&ctx, &serde_yaml_ng::Mapping::new()).unwrap();
+        assert!(source_config.validate().is_empty());
+        let source = source_config.transform();
+        let actual = source.run(ctx.clone()).unwrap().collect().unwrap();
+        let expected = example()
+            .lazy()
+            .with_column(col("updated").cast(DataType::Datetime(TimeUnit::Milliseconds, Some("UTC".into()))))
+            .collect()
+            .unwrap();
+        assert_frame_equal(actual, expected);
+        std::fs::remove_file(&path).unwrap();
+    }
+
+    #[test]
+    fn valid_sqlite_sink_replace_insert() {
+        let path = db_path();
+        let (ctx, replace) = get_node(&path, "replace");
+        replace.run(example(), ctx.clone()).unwrap();
+        replace.run(example(), ctx.clone()).unwrap();
+        assert_eq!(count(&path, "players"), 3);
+        let (ctx, insert) = get_node(&path, "insert");
+        async_st!(async || {
+            insert.fetch(example(), ctx.clone()).await.unwrap();
+        });
+        assert_eq!(count(&path, "players"), 6);
+        std::fs::remove_file(&path).unwrap();
+    }
+
+    #[test]
+    fn valid_sqlite_sink_upsert() {
+        let path = db_path();
+        let mut config = serde_yaml_ng::from_str::<SqliteSinkConfig>(&format!(
+            "
+sqlite:
+    url: {}
+    table: players
+    merge_type: upsert
+    model_fields:
+        id:
+            dtype: int64
+            constraints: [primary]
+        name: str
+options:
+    create_table_if_not_exists: true
+",
+            path.to_str().unwrap()
+        ))
+        .unwrap();
+        let ctx = DefaultPipelineContext::new().with_executing_sink(true);
+        config.emplace(&ctx, &serde_yaml_ng::Mapping::new()).unwrap();
+        assert!(config.validate().is_empty());
+        let (ctx, sink) = (Arc::new(ctx), config.transform());
+        sink.run(example(), ctx.clone()).unwrap();
+        let updated = df!("id" => [2i64, 4], "name" => ["Sid", "Cale"]).unwrap();
+        sink.run(updated, ctx.clone()).unwrap();
+        assert_eq!(count(&path, "players"), 4);
+        let name: String = Connection::open(&path)
+            .unwrap()
+            .query_row("SELECT name FROM players WHERE id = 2", [], |row| row.get(0))
+            .unwrap();
+        assert_eq!(name, "Sid");
+        config.sqlite.model_fields = None;
+        assert_eq!(config.validate().len(), 1);
+        std::fs::remove_file(&path).unwrap();
+    }
+
+    #[test]
+    fn valid_sqlite_sink_make_next() {
+        let path = db_path();
+        let sink = SqliteSink::new(path.clone(), "players", MergeTypeEnum::MakeNext);
+        let ctx = Arc::new(DefaultPipelineContext::new().with_executing_sink(true));
+        sink.run(example(), ctx.clone()).unwrap();
+        std::thread::sleep(std::time::Duration::from_millis(5));
+        sink.run(example(), ctx.clone()).unwrap();
+        let conn = Connection::open(&path).unwrap();
+        let mut statement = conn
+            .prepare("SELECT name FROM sqlite_master WHERE type = 'table' AND name LIKE 'players_%'")
+            .unwrap();
+        let tables = statement
+            .query_map([], |row| row.get::<_, String>(0))
+            .unwrap()
+            .collect::<Result<Vec<_>, _>>()
+            .unwrap();
+        assert_eq!(tables.len(), 2);
+        for table in tables {
+            assert_eq!(count(&path, &table), 3);
+        }
+        std::fs::remove_file(&path).unwrap();
+    }
+
+    #[test]
+    fn valid_sqlite_sink_exec_mode_off() {
+        let path = db_path();
+        let mut config = config(&path, "replace");
+        let ctx = DefaultPipelineContext::new().with_executing_sink(false);
+        config.emplace(&ctx, &serde_yaml_ng::Mapping::new()).unwrap();
+        config.transform().run(example(), Arc::new(ctx)).unwrap();
+        assert!(!path.exists());
+    }
+
+    #[test]
+    fn invalid_sqlite_sink_config() {
+        let config = serde_yaml_ng::from_str::<SqliteSinkConfig>(
+            "
+sqlite:
+    url: $db
+    table: players
+options:
+    create_table_if_not_exists: true
+",
+        )
+        .unwrap();
+        assert_eq!(config.validate().len(), 2);
+        assert_eq!(config.sqlite.table.value().unwrap(), "players");
+    }
+}
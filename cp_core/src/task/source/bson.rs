@@ -0,0 +1,278 @@
+use std::{fs::File, io::BufReader, path::PathBuf, str::FromStr, sync::Arc};
+
+use async_trait::async_trait;
+use polars::prelude::{IntoLazy, LazyFrame, Schema};
+
+use crate::{
+    model::common::{ModelConfig, ModelFields},
+    model_emplace,
+    parser::{keyword::Keyword, model::SchemaDriftPolicy},
+    pipeline::context::{DefaultPipelineContext, PipelineContext},
+    util::{
+        bson::{bson_to_df, read_bson_documents},
+        common::get_full_path,
+        error::{CpError, CpResult},
+    },
+    valid_or_insert_error,
+};
+
+use super::{
+    common::{Source, SourceConfig},
+    config::BsonSourceConfig,
+};
+
+pub struct BsonSource {
+    filepath: PathBuf,
+    output: String,
+    schema: Option<Arc<Schema>>,
+}
+
+impl BsonSource {
+    pub fn new(filepath: &str, output: &str) -> Self {
+        Self {
+            filepath: std::path::PathBuf::from_str(filepath).expect("bad filepath"),
+            output: output.to_owned(),
+            schema: None,
+        }
+    }
+
+    pub fn and_schema(mut self, schema: Schema) -> Self {
+        let _ = self.schema.insert(Arc::new(schema));
+        self
+    }
+}
+
+#[async_trait]
+impl Source for BsonSource {
+    fn connection_type(&self) -> &str {
+        "bson"
+    }
+
+    fn name(&self) -> &str {
+        self.output.as_str()
+    }
+
+    async fn fetch(&self, ctx: Arc<DefaultPipelineContext>) -> CpResult<LazyFrame> {
+        self.run(ctx)
+    }
+
+    fn run(&self, _ctx: Arc<DefaultPipelineContext>) -> CpResult<LazyFrame> {
+        // Reopens files every run
+        if !self.filepath.exists() {
+            return Err(CpError::ConfigError(
+                "File not found",
+                self.filepath.to_str().unwrap().to_owned(),
+            ));
+        }
+        let documents = read_bson_documents(BufReader::new(File::open(&self.filepath)?))?;
+        Ok(bson_to_df(&documents, self.schema.as_deref())?.lazy())
+    }
+}
+
+impl SourceConfig for BsonSourceConfig {
+    fn emplace(&mut self, ctx: &DefaultPipelineContext, context: &serde_yaml_ng::Mapping) -> CpResult<()> {
+        self.bson.filepath.insert_value_from_context(context)?;
+        self.bson.output.insert_value_from_context(context)?;
+        model_emplace!(self.bson, ctx, context);
+        Ok(())
+    }
+    fn model_fields(&self) -> Option<&ModelFields> {
+        self.bson.model_fields.as_ref()
+    }
+    fn schema_drift_policy(&self) -> Option<SchemaDriftPolicy> {
+        self.bson.on_schema_drift
+    }
+
+    fn take_model_fields(&mut self) -> Option<ModelFields> {
+        self.bson.model_fields.take()
+    }
+
+    fn validate(&self) -> Vec<CpError> {
+        let mut errors = vec![];
+        valid_or_insert_error!(errors, self.bson.filepath, "source[bson].filepath");
+        valid_or_insert_error!(errors, self.bson.output, "source[bson].output");
+        if let Some(model_fields) = &self.bson.model_fields {
+            for (key_kw, field_kw) in model_fields.iter() {
+                valid_or_insert_error!(errors, key_kw, "source[bson].model.key");
+                valid_or_insert_error!(errors, field_kw, "source[bson].model.field");
+            }
+        }
+        errors
+    }
+
+    fn transform(&self) -> Box<dyn Source> {
+        // By here the model_fields should be completely populated.
+        let schema = self.bson.model_fields.as_ref().map(|x| {
+            ModelConfig {
+                label: "".to_string(),
+                fields: x.clone(),
+            }
+            .schema()
+            .expect("failed to build schema")
+        });
+
+        let filepath = get_full_path(self.bson.filepath.value().expect("filepath"), true).expect("bad filepath");
+
+        Box::new(BsonSource {
+            filepath,
+            output: self.bson.output.value().expect("output").to_owned(),
+            schema: schema.map(Arc::new),
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{str::FromStr, sync::Arc};
+
+    use bson::{DateTime, Decimal128, Document, doc, oid::ObjectId};
+    use polars::{
+        df,
+        frame::DataFrame,
+        prelude::{DataType, TimeUnit},
+    };
+
+    use crate::{
+        async_st,
+        context::model::ModelRegistry,
+        model::common::{ModelConfig, ModelFieldInfo, ModelFields},
+        parser::{
+            dtype::DType,
+            keyword::{Keyword, ModelFieldKeyword, StrKeyword},
+        },
+        pipeline::context::DefaultPipelineContext,
+        task::source::{
+            common::{Source, SourceConfig},
+            config::{BsonSourceConfig, LocalFileSourceConfig},
+        },
+        util::{bson::write_bson_documents, test::assert_frame_equal, tmp::TempFile},
+    };
+
+    use super::BsonSource;
+
+    fn example_docs() -> Vec<Document> {
+        vec![
+            doc! { "a": -1, "b": "z" },
+            doc! { "a": 1, "b": "a" },
+            doc! { "a": 3, "b": "j" },
+            doc! { "a": 5, "b": "i" },
+            doc! { "a": 6, "b": "c" },
+        ]
+    }
+
+    fn example() -> DataFrame {
+        df!(
+            "a" => [-1, 1, 3, 5, 6],
+            "b" => ["z", "a", "j", "i", "c"],
+        )
+        .unwrap()
+    }
+
+    fn example_model() -> ModelConfig {
+        ModelConfig {
+            label: "S".to_string(),
+            fields: ModelFields::from([
+                (
+                    StrKeyword::with_value("a".to_owned()),
+                    ModelFieldKeyword::with_value(ModelFieldInfo::with_dtype(DType(DataType::Int32))),
+                ),
+                (
+                    StrKeyword::with_value("b".to_owned()),
+                    ModelFieldKeyword::with_value(ModelFieldInfo::with_dtype(DType(DataType::String))),
+                ),
+            ]),
+        }
+    }
+
+    #[test]
+    fn valid_bson_source() {
+        let tmp = TempFile::default();
+        write_bson_documents(tmp.get_mut().unwrap(), &example_docs()).unwrap();
+        let model_schema = example_model().schema().unwrap();
+        let bson_source = BsonSource::new(&tmp.filepath, "_sample").and_schema(model_schema);
+        let ctx = Arc::new(DefaultPipelineContext::new());
+        let result = bson_source.run(ctx).unwrap();
+        assert_frame_equal(result.collect().unwrap(), example());
+        assert_eq!(bson_source.name(), "_sample");
+        assert_eq!(bson_source.connection_type(), "bson");
+    }
+
+    #[test]
+    fn valid_bson_source_async() {
+        let tmp = TempFile::default();
+        write_bson_documents(tmp.get_mut().unwrap(), &example_docs()).unwrap();
+        let bson_source = BsonSource::new(&tmp.filepath, "_sample");
+        let ctx = Arc::new(DefaultPipelineContext::new());
+        async_st!(async || {
+            let result = bson_source.fetch(ctx).await.unwrap();
+            assert_frame_equal(result.collect().unwrap(), example());
+        });
+    }
+
+    #[test]
+    fn valid_bson_source_bson_types() {
+        let tmp = TempFile::default();
+        let oid = ObjectId::from_str("65f1a2b3c4d5e6f708091a2b").unwrap();
+        write_bson_documents(
+            tmp.get_mut().unwrap(),
+            &[doc! {
+                "_id": oid,
+                "at": DateTime::from_millis(1_700_000_000_000),
+                "px": Decimal128::from_str("100.005").unwrap(),
+            }],
+        )
+        .unwrap();
+        let mut source_config = serde_yaml_ng::from_str::<BsonSourceConfig>(&format!(
+            "
+bson:
+    filepath: {}
+    output: _sample
+    model_fields:
+        _id: objectid
+        at: datetime_utc
+        px: decimal128
+",
+            &tmp.filepath
+        ))
+        .unwrap();
+        let ctx = Arc::new(DefaultPipelineContext::new());
+        source_config.emplace(&ctx, &serde_yaml_ng::Mapping::new()).unwrap();
+        assert!(source_config.validate().is_empty());
+        let actual = source_config.transform().run(ctx).unwrap().collect().unwrap();
+        assert_eq!(
+            actual.column("_id").unwrap().str().unwrap().get(0),
+            Some("65f1a2b3c4d5e6f708091a2b")
+        );
+        assert_eq!(
+            actual.column("at").unwrap().dtype(),
+            &DataType::Datetime(TimeUnit::Milliseconds, Some("UTC".into()))
+        );
+        assert_eq!(actual.column("px").unwrap().get(0).unwrap().to_string(), "100.005");
+    }
+
+    #[test]
+    fn valid_bson_source_config_to_bson_source() {
+        let tmp = TempFile::default();
+        write_bson_documents(tmp.get_mut().unwrap(), &example_docs()).unwrap();
+        let mut source_config = BsonSourceConfig {
+            bson: LocalFileSourceConfig {
+                filepath: StrKeyword::with_value(tmp.filepath.clone()),
+                output: StrKeyword::with_value("_sample".to_owned()),
+                model_fields: None,
+                model: Some(StrKeyword::with_value("S".to_owned())),
+                on_schema_drift: None,
+            },
+        };
+        let mut model_reg = ModelRegistry::new();
+        model_reg.insert(example_model());
+        let ctx = Arc::new(DefaultPipelineContext::new().with_model_registry(model_reg));
+        let mapping = serde_yaml_ng::Mapping::new();
+        let _ = source_config.emplace(&ctx, &mapping);
+        let errors = source_config.validate();
+        assert!(errors.is_empty());
+        assert_eq!(source_config.bson.model_fields.clone().unwrap(), example_model().fields);
+        let actual_node = source_config.transform();
+        let result = actual_node.run(ctx.clone()).unwrap();
+        assert_frame_equal(result.collect().unwrap(), example());
+    }
+}
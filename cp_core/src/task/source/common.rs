@@ -1,13 +1,23 @@
 use std::sync::Arc;
 
 use async_trait::async_trait;
-use polars::prelude::LazyFrame;
+use polars::prelude::{DataFrame, Expr, IntoLazy, LazyFrame};
 
 use crate::{
     ctx_run_n_async, ctx_run_n_threads,
     frame::common::{FrameAsyncBroadcastHandle, FrameBroadcastHandle, FrameUpdateType},
+    model::{
+        common::{ModelConfig, ModelFields},
+        constraint::ModelConstraintCheck,
+        drift::SchemaDrift,
+        reject::ModelRejects,
+    },
+    parser::model::SchemaDriftPolicy,
     pipeline::context::{DefaultPipelineContext, PipelineContext},
-    task::stage::{Stage, StageTaskConfig},
+    task::{
+        sink::common::{BoxedSink, parse_rejects_sink},
+        stage::{Stage, StageTaskConfig},
+    },
     try_deserialize_stage,
     util::{
         common::format_schema,
@@ -16,7 +26,8 @@ use crate::{
 };
 
 use super::config::{
-    CsvSourceConfig, HttpSourceConfig, JsonSourceConfig, MySqlSourceConfig, PostgresSourceConfig, SourceGroupConfig,
+    BsonSourceConfig, ClickhouseSourceConfig, CsvSourceConfig, HttpSourceConfig, JsonSourceConfig, MongoSourceConfig,
+    MySqlSourceConfig, ParquetSourceConfig, PostgresSourceConfig, SourceGroupConfig, SqliteSourceConfig,
 };
 
 /// Base source trait. Importantly, certain sources may have dependencies as well.
@@ -34,8 +45,137 @@ pub struct BoxedSource(Box<dyn Source>);
 
 pub trait SourceConfig {
     fn emplace(&mut self, ctx: &DefaultPipelineContext, context: &serde_yaml_ng::Mapping) -> CpResult<()>;
+    /// The model fields after emplacing, if the source has a model
+    fn model_fields(&self) -> Option<&ModelFields>;
+    /// How the schema as read is compared against the model, if the source has a model
+    fn schema_drift_policy(&self) -> Option<SchemaDriftPolicy>;
+    /// Removes the model, so that the source yields frames as read
+    fn take_model_fields(&mut self) -> Option<ModelFields>;
     fn validate(&self) -> Vec<CpError>;
     fn transform(&self) -> Box<dyn Source>;
+    /// Pushes a limit of `rows` rows into the query of the source after emplacing, for sources that
+    /// query a database. Other sources read everything they would otherwise.
+    fn limit(&mut self, _rows: u32) {}
+}
+
+/// Renames, fills defaults and casts the frames of a source read without its model, for models
+/// whose fields declare a `source` or `default`.
+struct ProjectedSource {
+    source: BoxedSource,
+    columns: Vec<Expr>,
+}
+
+#[async_trait]
+impl Source for ProjectedSource {
+    fn connection_type(&self) -> &str {
+        self.source.0.connection_type()
+    }
+    fn name(&self) -> &str {
+        self.source.0.name()
+    }
+    fn run(&self, ctx: Arc<DefaultPipelineContext>) -> CpResult<LazyFrame> {
+        Ok(self.source.0.run(ctx)?.select(self.columns.clone()))
+    }
+    async fn fetch(&self, ctx: Arc<DefaultPipelineContext>) -> CpResult<LazyFrame> {
+        Ok(self.source.0.fetch(ctx).await?.select(self.columns.clone()))
+    }
+}
+
+/// Checks the schema of the frames of a source read without its model against the model.
+struct DriftingSource {
+    source: BoxedSource,
+    drift: SchemaDrift,
+}
+
+#[async_trait]
+impl Source for DriftingSource {
+    fn connection_type(&self) -> &str {
+        self.source.0.connection_type()
+    }
+    fn name(&self) -> &str {
+        self.source.0.name()
+    }
+    fn run(&self, ctx: Arc<DefaultPipelineContext>) -> CpResult<LazyFrame> {
+        let mut frame = self.source.0.run(ctx)?;
+        self.drift.check(frame.collect_schema()?.as_ref())?;
+        Ok(frame)
+    }
+    async fn fetch(&self, ctx: Arc<DefaultPipelineContext>) -> CpResult<LazyFrame> {
+        let mut frame = self.source.0.fetch(ctx).await?;
+        self.drift.check(frame.collect_schema()?.as_ref())?;
+        Ok(frame)
+    }
+}
+
+/// Checks the frames of a source against its model's constraints.
+struct ConstrainedSource {
+    source: BoxedSource,
+    check: ModelConstraintCheck,
+}
+
+#[async_trait]
+impl Source for ConstrainedSource {
+    fn connection_type(&self) -> &str {
+        self.source.0.connection_type()
+    }
+    fn name(&self) -> &str {
+        self.source.0.name()
+    }
+    fn run(&self, ctx: Arc<DefaultPipelineContext>) -> CpResult<LazyFrame> {
+        let frame = self.source.0.run(ctx)?.collect()?;
+        Ok(self.check.enforce(frame)?.lazy())
+    }
+    async fn fetch(&self, ctx: Arc<DefaultPipelineContext>) -> CpResult<LazyFrame> {
+        let frame = self.source.0.fetch(ctx).await?.collect()?;
+        Ok(self.check.enforce(frame)?.lazy())
+    }
+}
+
+/// Casts the frames of a source read without its model, diverting the rows failing a cast or
+/// constraint to a `rejects` sink.
+struct RejectingSource {
+    source: BoxedSource,
+    split: ModelRejects,
+    rejects: Arc<BoxedSink>,
+}
+
+impl RejectingSource {
+    fn split(&self, frame: DataFrame) -> CpResult<(DataFrame, Option<DataFrame>)> {
+        let (accepted, rejected) = self.split.split(frame)?;
+        if let Some(rejected) = &rejected {
+            log::warn!(
+                "Rejected {} rows of `{}`, writing them via {}",
+                rejected.height(),
+                self.source.0.name(),
+                self.rejects.0.connection_type()
+            );
+        }
+        Ok((accepted, rejected))
+    }
+}
+
+#[async_trait]
+impl Source for RejectingSource {
+    fn connection_type(&self) -> &str {
+        self.source.0.connection_type()
+    }
+    fn name(&self) -> &str {
+        self.source.0.name()
+    }
+    fn run(&self, ctx: Arc<DefaultPipelineContext>) -> CpResult<LazyFrame> {
+        let (accepted, rejected) = self.split(self.source.0.run(ctx.clone())?.collect()?)?;
+        if let Some(rejected) = rejected {
+            self.rejects.0.run(rejected, ctx)?;
+        }
+        Ok(accepted.lazy())
+    }
+    async fn fetch(&self, ctx: Arc<DefaultPipelineContext>) -> CpResult<LazyFrame> {
+        let (accepted, rejected) = self.split(self.source.0.fetch(ctx.clone()).await?.collect()?)?;
+        if let Some(rejected) = rejected {
+            self.rejects.0.fetch(rejected, ctx).await?;
+        }
+        Ok(accepted.lazy())
+    }
 }
 
 /// We NEVER modify the individual Source instantiations after initialization.
@@ -205,6 +345,20 @@ impl Stage for SourceGroup {
     }
 }
 
+/// The source config without its `model` and `model_fields`, so that the source reads frames as they are
+fn without_model(config: &serde_yaml_ng::Value) -> serde_yaml_ng::Value {
+    let mut config = config.clone();
+    if let Some(mapping) = config.as_mapping_mut() {
+        for (_, inner) in mapping.iter_mut() {
+            if let Some(inner) = inner.as_mapping_mut() {
+                let _ = inner.remove("model");
+                let _ = inner.remove("model_fields");
+            }
+        }
+    }
+    config
+}
+
 impl SourceGroupConfig {
     fn parse_subsources(&self) -> Vec<Result<Box<dyn SourceConfig>, CpError>> {
         self.sources
@@ -217,7 +371,12 @@ impl SourceGroupConfig {
                     CsvSourceConfig,
                     HttpSourceConfig,
                     MySqlSourceConfig,
-                    PostgresSourceConfig
+                    PostgresSourceConfig,
+                    ParquetSourceConfig,
+                    MongoSourceConfig,
+                    BsonSourceConfig,
+                    ClickhouseSourceConfig,
+                    SqliteSourceConfig
                 );
                 config.ok_or_else(|| {
                     CpError::ConfigError(
@@ -228,6 +387,125 @@ impl SourceGroupConfig {
             })
             .collect()
     }
+
+    /// Builds the source of a validated config, applying its model beyond what the source itself reads:
+    /// schema drift, renamed or defaulted fields, rejects and constraints all need the frames as read.
+    fn wrap_source(
+        &self,
+        mut config: Box<dyn SourceConfig>,
+        drift: Option<SchemaDriftPolicy>,
+        rejects: Option<&Arc<BoxedSink>>,
+    ) -> CpResult<Box<dyn Source>> {
+        let drift = drift.filter(|x| *x != SchemaDriftPolicy::Ignore);
+        let raw = rejects.is_some() || drift.is_some() || config.model_fields().is_some_and(|x| x.is_projected());
+        let fields = match raw {
+            true => config.take_model_fields(),
+            false => None,
+        };
+        let mut source = config.transform();
+        let Some(fields) = fields else {
+            let check = self
+                .constraint_policy
+                .zip(config.model_fields())
+                .and_then(|(policy, fields)| ModelConstraintCheck::new(source.name(), fields, policy));
+            return Ok(match check {
+                Some(check) => Box::new(ConstrainedSource {
+                    source: BoxedSource(source),
+                    check,
+                }),
+                None => source,
+            });
+        };
+        let mut passthrough = None;
+        if let Some(policy) = drift {
+            let drift = SchemaDrift::new(source.name(), &fields, policy);
+            if policy == SchemaDriftPolicy::Evolve {
+                let _ = passthrough.insert(drift.passthrough());
+            }
+            source = Box::new(DriftingSource {
+                source: BoxedSource(source),
+                drift,
+            });
+        }
+        if let Some(rejects) = rejects {
+            let mut split = ModelRejects::new(&self.label, &fields)?;
+            if let Some(passthrough) = passthrough {
+                split = split.with_passthrough(passthrough);
+            }
+            return Ok(Box::new(RejectingSource {
+                source: BoxedSource(source),
+                split,
+                rejects: rejects.clone(),
+            }));
+        }
+        let mut columns = ModelConfig {
+            label: source.name().to_owned(),
+            fields: fields.clone(),
+        }
+        .columns()?;
+        columns.extend(passthrough);
+        let source = Box::new(ProjectedSource {
+            source: BoxedSource(source),
+            columns,
+        });
+        Ok(
+            match self
+                .constraint_policy
+                .and_then(|policy| ModelConstraintCheck::new(source.name(), &fields, policy))
+            {
+                Some(check) => Box::new(ConstrainedSource {
+                    source: BoxedSource(source),
+                    check,
+                }),
+                None => source,
+            },
+        )
+    }
+
+    /// Reads up to `rows` rows of every source, ignoring their models, e.g. to infer models from.
+    /// Returns the frames by source output name. The limit is part of the query of database sources,
+    /// while files are scanned up to the limit where their format allows, and http and mongo sources
+    /// are read in full before the limit is applied.
+    pub fn sample(
+        &self,
+        ctx: Arc<DefaultPipelineContext>,
+        context: &serde_yaml_ng::Mapping,
+        rows: u32,
+    ) -> Result<Vec<(String, DataFrame)>, Vec<CpError>> {
+        let group = SourceGroupConfig {
+            sources: self.sources.iter().map(without_model).collect(),
+            constraint_policy: None,
+            rejects: None,
+            ..self.clone()
+        };
+        let mut samples = vec![];
+        let mut errors = vec![];
+        for result in group.parse_subsources() {
+            let mut config = match result {
+                Ok(x) => x,
+                Err(e) => {
+                    errors.push(e);
+                    continue;
+                }
+            };
+            if let Err(e) = config.emplace(&ctx, context) {
+                errors.push(e);
+                continue;
+            }
+            let errs = config.validate();
+            if !errs.is_empty() {
+                errors.extend(errs);
+                continue;
+            }
+            config.limit(rows);
+            let source = config.transform();
+            match source.run(ctx.clone()).and_then(|x| Ok(x.limit(rows).collect()?)) {
+                Ok(frame) => samples.push((source.name().to_owned(), frame)),
+                Err(e) => errors.push(e),
+            }
+        }
+        if errors.is_empty() { Ok(samples) } else { Err(errors) }
+    }
 }
 
 impl StageTaskConfig<SourceGroup> for SourceGroupConfig {
@@ -238,6 +516,20 @@ impl StageTaskConfig<SourceGroup> for SourceGroupConfig {
     ) -> Result<SourceGroup, Vec<CpError>> {
         let mut subsources = vec![];
         let mut errors = vec![];
+        if self.constraint_policy.is_some() && self.rejects.is_some() {
+            errors.push(CpError::ConfigValidationError(
+                "source.constraint_policy",
+                "constraint_policy cannot be combined with rejects, which diverts the violating rows".to_owned(),
+            ));
+        }
+        let rejects = match self.rejects.as_ref().map(|x| parse_rejects_sink(x, ctx, context)) {
+            Some(Ok(x)) => Some(x),
+            Some(Err(errs)) => {
+                errors.extend(errs);
+                None
+            }
+            None => None,
+        };
         for result in self.parse_subsources() {
             match result {
                 Ok(mut config) => {
@@ -246,7 +538,11 @@ impl StageTaskConfig<SourceGroup> for SourceGroupConfig {
                     }
                     let errs = config.validate();
                     if errs.is_empty() {
-                        subsources.push(BoxedSource(config.transform()));
+                        let drift = config.schema_drift_policy();
+                        match self.wrap_source(config, drift, rejects.as_ref()) {
+                            Ok(source) => subsources.push(BoxedSource(source)),
+                            Err(e) => errors.push(e),
+                        }
                     } else {
                         errors.extend(errs);
                     }
@@ -276,7 +572,7 @@ mod tests {
         frame::DataFrame,
         functions::concat_df_horizontal,
         io::SerWriter,
-        prelude::{DataType, IntoLazy, JsonWriter, LazyFrame, UnionArgs, concat_lf_horizontal},
+        prelude::{CsvReader, DataType, IntoLazy, JsonWriter, LazyFrame, SerReader, UnionArgs, concat_lf_horizontal},
     };
 
     use crate::{
@@ -520,6 +816,8 @@ mod tests {
             label: "".to_owned(),
             max_threads: 1,
             sources: configs,
+            constraint_policy: None,
+            rejects: None,
         };
         let mut model_registry = ModelRegistry::new();
         model_registry.insert(ModelConfig {
@@ -561,6 +859,8 @@ mod tests {
                 label: "".to_owned(),
                 max_threads: 1,
                 sources: configs,
+                constraint_policy: None,
+                rejects: None,
             };
             let mut model_registry = ModelRegistry::new();
             model_registry.insert(ModelConfig {
@@ -602,6 +902,8 @@ mod tests {
             label: "".to_owned(),
             max_threads: 1,
             sources: configs,
+            constraint_policy: None,
+            rejects: None,
         };
         let mut model_registry = ModelRegistry::new();
         model_registry.insert(ModelConfig {
@@ -624,4 +926,228 @@ mod tests {
         );
         assert_frame_equal(ctx.clone().extract_clone_result("SAMPLE3").unwrap(), default_next());
     }
+
+    #[test]
+    fn valid_source_group_rejects() {
+        let configs_str = "
+- json:
+    filepath: $fp
+    output: SAMPLE
+    model_fields:
+        a: int64
+        b: str
+";
+        let tmp = TempFile::default();
+        tmp.write_json(&mut df!( "a" => ["1", "x", "3"], "b" => ["p", "q", "r"] ).unwrap())
+            .unwrap();
+        let tmp_rejects = TempFile::default();
+        let context_str = format!("{{fp: {}, rejects_fp: {}}}", &tmp.filepath, &tmp_rejects.filepath);
+        let context = serde_yaml_ng::from_str::<serde_yaml_ng::Mapping>(&context_str).unwrap();
+        let sgconfig = SourceGroupConfig {
+            label: "load_sample".to_owned(),
+            max_threads: 1,
+            sources: serde_yaml_ng::from_str::<Vec<serde_yaml_ng::Value>>(configs_str).unwrap(),
+            constraint_policy: None,
+            rejects: Some(serde_yaml_ng::from_str("{csv: {filepath: $rejects_fp, merge_type: insert}}").unwrap()),
+        };
+        let ctx = Arc::new(DefaultPipelineContext::with_results(&["SAMPLE"], 1).with_executing_sink(true));
+        let actual = sgconfig.parse(&ctx, &context).unwrap();
+        actual.linear(ctx.clone()).unwrap();
+        assert_frame_equal(
+            ctx.extract_clone_result("SAMPLE").unwrap(),
+            df!( "a" => [1i64, 3], "b" => ["p", "r"] ).unwrap(),
+        );
+        let rejected = CsvReader::new(tmp_rejects.get().unwrap()).finish().unwrap();
+        assert_frame_equal(
+            rejected,
+            df!(
+                "a" => ["x"],
+                "b" => ["q"],
+                "_error" => ["cast(a): not a valid i64"],
+                "_stage" => ["load_sample"],
+            )
+            .unwrap(),
+        );
+    }
+
+    #[test]
+    fn valid_source_group_constraint_policy() {
+        let configs_str = "
+- json:
+    filepath: $fp
+    output: SAMPLE
+    model_fields:
+        a:
+            dtype: int64
+            constraints: [primary]
+        b: str
+";
+        let tmp = TempFile::default();
+        tmp.write_json(&mut df!( "a" => [1i64, 2, 2, 3], "b" => ["p", "q", "r", "s"] ).unwrap())
+            .unwrap();
+        let context_str = format!("{{fp: {}}}", &tmp.filepath);
+        let context = serde_yaml_ng::from_str::<serde_yaml_ng::Mapping>(&context_str).unwrap();
+        let group = |policy: &str| SourceGroupConfig {
+            label: "load_sample".to_owned(),
+            max_threads: 1,
+            sources: serde_yaml_ng::from_str::<Vec<serde_yaml_ng::Value>>(configs_str).unwrap(),
+            constraint_policy: Some(serde_yaml_ng::from_str(policy).unwrap()),
+            rejects: None,
+        };
+        let ctx = Arc::new(DefaultPipelineContext::with_results(&["SAMPLE"], 1));
+        assert!(
+            group("error")
+                .parse(&ctx, &context)
+                .unwrap()
+                .linear(ctx.clone())
+                .is_err()
+        );
+        let ctx = Arc::new(DefaultPipelineContext::with_results(&["SAMPLE"], 1));
+        group("drop_violating_rows")
+            .parse(&ctx, &context)
+            .unwrap()
+            .linear(ctx.clone())
+            .unwrap();
+        assert_frame_equal(
+            ctx.extract_clone_result("SAMPLE").unwrap(),
+            df!( "a" => [1i64, 3], "b" => ["p", "s"] ).unwrap(),
+        );
+    }
+
+    #[test]
+    fn valid_source_group_projected() {
+        let configs_str = "
+- json:
+    filepath: $fp
+    output: SAMPLE
+    model_fields:
+        player_id:
+            dtype: int64
+            source: playerId
+        first_name:
+            dtype: str
+            source: First Name
+            default: unknown
+        score:
+            dtype: double
+            default: 0
+";
+        let tmp = TempFile::default();
+        tmp.write_json(
+            &mut df!(
+                "playerId" => ["1", "2"],
+                "First Name" => [Some("p"), None],
+                "score" => [Some(1.5), None],
+            )
+            .unwrap(),
+        )
+        .unwrap();
+        let context_str = format!("{{fp: {}}}", &tmp.filepath);
+        let context = serde_yaml_ng::from_str::<serde_yaml_ng::Mapping>(&context_str).unwrap();
+        let sgconfig = SourceGroupConfig {
+            label: "load_sample".to_owned(),
+            max_threads: 1,
+            sources: serde_yaml_ng::from_str::<Vec<serde_yaml_ng::Value>>(configs_str).unwrap(),
+            constraint_policy: None,
+            rejects: None,
+        };
+        let ctx = Arc::new(DefaultPipelineContext::with_results(&["SAMPLE"], 1));
+        let actual = sgconfig.parse(&ctx, &context).unwrap();
+        actual.linear(ctx.clone()).unwrap();
+        assert_frame_equal(
+            ctx.extract_clone_result("SAMPLE").unwrap(),
+            df!(
+                "player_id" => [1i64, 2],
+                "first_name" => ["p", "unknown"],
+                "score" => [1.5, 0.0],
+            )
+            .unwrap(),
+        );
+    }
+
+    #[test]
+    fn valid_source_group_schema_drift() {
+        let tmp = TempFile::default();
+        tmp.write_json(&mut df!( "a" => [1i64, 2], "b" => ["p", "q"], "c" => [true, false] ).unwrap())
+            .unwrap();
+        let context_str = format!("{{fp: {}}}", &tmp.filepath);
+        let context = serde_yaml_ng::from_str::<serde_yaml_ng::Mapping>(&context_str).unwrap();
+        let configs_str = "
+- json:
+    filepath: $fp
+    output: SAMPLE
+    on_schema_drift: {policy}
+    model_fields:
+        a: int64
+        b: str
+";
+        let group = |policy: &str| SourceGroupConfig {
+            label: "load_sample".to_owned(),
+            max_threads: 1,
+            sources: serde_yaml_ng::from_str::<Vec<serde_yaml_ng::Value>>(&configs_str.replace("{policy}", policy))
+                .unwrap(),
+            constraint_policy: None,
+            rejects: None,
+        };
+        let ctx = Arc::new(DefaultPipelineContext::with_results(&["SAMPLE"], 1));
+        assert!(
+            group("error")
+                .parse(&ctx, &context)
+                .unwrap()
+                .linear(ctx.clone())
+                .is_err()
+        );
+        assert!(group("unknown").parse(&ctx, &context).is_err());
+
+        for (policy, expected) in [
+            ("warn", df!( "a" => [1i64, 2], "b" => ["p", "q"] ).unwrap()),
+            (
+                "evolve",
+                df!( "a" => [1i64, 2], "b" => ["p", "q"], "c" => [true, false] ).unwrap(),
+            ),
+        ] {
+            let ctx = Arc::new(DefaultPipelineContext::with_results(&["SAMPLE"], 1));
+            group(policy)
+                .parse(&ctx, &context)
+                .unwrap()
+                .linear(ctx.clone())
+                .unwrap();
+            assert_frame_equal(ctx.extract_clone_result("SAMPLE").unwrap(), expected);
+        }
+    }
+
+    #[test]
+    fn valid_source_group_rejects_schema_drift() {
+        let configs_str = "
+- json:
+    filepath: $fp
+    output: SAMPLE
+    on_schema_drift: evolve
+    model_fields:
+        a: int64
+        b: str
+";
+        let tmp = TempFile::default();
+        tmp.write_json(&mut df!( "a" => ["1", "x", "3"], "b" => ["p", "q", "r"], "c" => [true, false, true] ).unwrap())
+            .unwrap();
+        let tmp_rejects = TempFile::default();
+        let context_str = format!("{{fp: {}, rejects_fp: {}}}", &tmp.filepath, &tmp_rejects.filepath);
+        let context = serde_yaml_ng::from_str::<serde_yaml_ng::Mapping>(&context_str).unwrap();
+        let group = |policy: Option<&str>| SourceGroupConfig {
+            label: "load_sample".to_owned(),
+            max_threads: 1,
+            sources: serde_yaml_ng::from_str::<Vec<serde_yaml_ng::Value>>(configs_str).unwrap(),
+            constraint_policy: policy.map(|x| serde_yaml_ng::from_str(x).unwrap()),
+            rejects: Some(serde_yaml_ng::from_str("{csv: {filepath: $rejects_fp, merge_type: insert}}").unwrap()),
+        };
+        let ctx = Arc::new(DefaultPipelineContext::with_results(&["SAMPLE"], 1).with_executing_sink(true));
+        assert!(group(Some("error")).parse(&ctx, &context).is_err());
+        group(None).parse(&ctx, &context).unwrap().linear(ctx.clone()).unwrap();
+        assert_frame_equal(
+            ctx.extract_clone_result("SAMPLE").unwrap(),
+            df!( "a" => [1i64, 3], "b" => ["p", "r"], "c" => [true, true] ).unwrap(),
+        );
+        let rejected = CsvReader::new(tmp_rejects.get().unwrap()).finish().unwrap();
+        assert_eq!(rejected.height(), 1);
+    }
 }
@@ -2,7 +2,12 @@ use serde::Deserialize;
 
 use crate::{
     model::common::ModelFields,
-    parser::{http::HttpOptionsConfig, keyword::StrKeyword, sql_connection::SqlConnection},
+    parser::{
+        http::HttpOptionsConfig,
+        keyword::StrKeyword,
+        model::{ConstraintPolicy, SchemaDriftPolicy},
+        sql_connection::SqlConnection,
+    },
 };
 
 #[derive(Debug, Clone, PartialEq, Eq, Deserialize)]
@@ -10,6 +15,10 @@ pub struct SourceGroupConfig {
     pub label: String,
     pub max_threads: usize,
     pub sources: Vec<serde_yaml_ng::Value>,
+    // checks each source's frames against its model's constraints
+    pub constraint_policy: Option<ConstraintPolicy>,
+    // sink receiving the rows that fail a model cast or constraint, with `_error` and `_stage` columns
+    pub rejects: Option<serde_yaml_ng::Value>,
 }
 
 #[derive(Debug, Clone, PartialEq, Eq, Deserialize)]
@@ -20,6 +29,8 @@ pub struct LocalFileSourceConfig {
     pub model: Option<StrKeyword>,
     // holds a fully substituted ModelConfig
     pub model_fields: Option<ModelFields>,
+    // compares the schema as read against the model
+    pub on_schema_drift: Option<SchemaDriftPolicy>,
 }
 
 #[derive(Debug, Clone, PartialEq, Eq, Deserialize)]
@@ -30,6 +41,8 @@ pub struct _CsvSourceConfig {
     pub model: Option<StrKeyword>,
     // holds a fully substituted ModelConfig
     pub model_fields: Option<ModelFields>,
+    // compares the schema as read against the model
+    pub on_schema_drift: Option<SchemaDriftPolicy>,
     pub separator: Option<StrKeyword>,
 }
 
@@ -43,6 +56,16 @@ pub struct CsvSourceConfig {
     pub csv: _CsvSourceConfig,
 }
 
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize)]
+pub struct ParquetSourceConfig {
+    pub parquet: LocalFileSourceConfig,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize)]
+pub struct BsonSourceConfig {
+    pub bson: LocalFileSourceConfig,
+}
+
 #[derive(Debug, Clone, PartialEq, Eq, Deserialize)]
 pub struct PostgresSourceConfig {
     pub postgres: SqlConnection,
@@ -53,6 +76,16 @@ pub struct MySqlSourceConfig {
     pub mysql: SqlConnection,
 }
 
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize)]
+pub struct SqliteSourceConfig {
+    pub sqlite: SqlConnection,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize)]
+pub struct ClickhouseSourceConfig {
+    pub clickhouse: SqlConnection,
+}
+
 #[derive(Debug, Clone, PartialEq, Eq, Deserialize)]
 pub struct SingleLinkConfig {
     pub url: StrKeyword,
@@ -60,6 +93,8 @@ pub struct SingleLinkConfig {
     // todo: add specification of content-type and request type (optional)
     pub model: Option<StrKeyword>,
     pub model_fields: Option<ModelFields>,
+    // compares the schema as read against the model
+    pub on_schema_drift: Option<SchemaDriftPolicy>,
     pub options: Option<HttpOptionsConfig>,
 }
 
@@ -73,6 +108,8 @@ pub struct MongoConnection {
     pub projection: Option<mongodb::bson::Document>,
     pub model: Option<StrKeyword>,
     pub model_fields: Option<ModelFields>,
+    // compares the schema as read against the model
+    pub on_schema_drift: Option<SchemaDriftPolicy>,
 }
 
 #[derive(Debug, Clone, PartialEq, Eq, Deserialize)]
@@ -99,6 +136,7 @@ mod tests {
             dtype::DType,
             http::HttpOptionsConfig,
             keyword::{Keyword, ModelFieldKeyword, StrKeyword},
+            model::SchemaDriftPolicy,
         },
         task::source::config::{CsvSourceConfig, JsonSourceConfig},
     };
@@ -114,6 +152,7 @@ mod tests {
                 output: StrKeyword::with_symbol("output"),
                 model: Some(StrKeyword::with_value("test".to_string())),
                 model_fields: None,
+                on_schema_drift: None,
             },
             LocalFileSourceConfig {
                 filepath: StrKeyword::with_symbol("fp"),
@@ -123,18 +162,21 @@ mod tests {
                     StrKeyword::with_value("test".to_owned()),
                     ModelFieldKeyword::with_value(ModelFieldInfo::with_dtype(DType(DataType::Int8))),
                 )])),
+                on_schema_drift: None,
             },
             LocalFileSourceConfig {
                 filepath: StrKeyword::with_value("fp".to_string()),
                 output: StrKeyword::with_symbol("output"),
                 model: Some(StrKeyword::with_symbol("test")),
                 model_fields: None,
+                on_schema_drift: Some(SchemaDriftPolicy::Evolve),
             },
             LocalFileSourceConfig {
                 filepath: StrKeyword::with_value("fp".to_string()),
                 output: StrKeyword::with_value("output".to_string()),
                 model: Some(StrKeyword::with_symbol("test")),
                 model_fields: None,
+                on_schema_drift: None,
             },
             LocalFileSourceConfig {
                 filepath: StrKeyword::with_value("fp".to_string()),
@@ -150,6 +192,7 @@ mod tests {
                         ModelFieldKeyword::with_value(ModelFieldInfo::with_dtype(DType(DataType::String))),
                     ),
                 ])),
+                on_schema_drift: None,
             },
         ]
     }
@@ -161,6 +204,7 @@ mod tests {
             filepath: local.filepath,
             model: local.model,
             model_fields: local.model_fields,
+            on_schema_drift: local.on_schema_drift,
         }
     }
 
@@ -188,6 +232,7 @@ mod tests {
     filepath: fp
     output: $output
     model: $test
+    on_schema_drift: evolve
     {csv}
 ",
             "
@@ -281,7 +326,8 @@ http:
                             StrKeyword::with_symbol("test"),
                             ModelFieldKeyword::with_value(ModelFieldInfo::with_dtype(DType(DataType::Int8)))
                         )])),
-                        options: expected[i].clone()
+                        options: expected[i].clone(),
+                        on_schema_drift: None,
                     },
                 },
                 serde_yaml_ng::from_str::<HttpSourceConfig>(configs[i]).unwrap()
@@ -320,6 +366,7 @@ mongo:
                 model_fields: None,
                 find: doc! { "a": { "$ne" : "b" } },
                 projection: None,
+                on_schema_drift: None,
             },
             MongoConnection {
                 url: None,
@@ -330,6 +377,7 @@ mongo:
                 model_fields: None,
                 find: doc! {},
                 projection: Some(doc! { "id" : 1 }),
+                on_schema_drift: None,
             },
         ];
 
@@ -4,9 +4,9 @@ use async_trait::async_trait;
 use polars::prelude::{LazyCsvReader, LazyFileListReader, LazyFrame, Schema};
 
 use crate::{
-    model::common::ModelConfig,
+    model::common::{ModelConfig, ModelFields},
     model_emplace,
-    parser::keyword::Keyword,
+    parser::{keyword::Keyword, model::SchemaDriftPolicy},
     pipeline::context::{DefaultPipelineContext, PipelineContext},
     util::{
         common::get_full_path,
@@ -112,6 +112,17 @@ impl SourceConfig for CsvSourceConfig {
         model_emplace!(self.csv, ctx, context);
         Ok(())
     }
+    fn model_fields(&self) -> Option<&ModelFields> {
+        self.csv.model_fields.as_ref()
+    }
+    fn schema_drift_policy(&self) -> Option<SchemaDriftPolicy> {
+        self.csv.on_schema_drift
+    }
+
+    fn take_model_fields(&mut self) -> Option<ModelFields> {
+        self.csv.model_fields.take()
+    }
+
     fn validate(&self) -> Vec<CpError> {
         let mut errors = vec![];
         valid_or_insert_error!(errors, self.csv.filepath, "source[csv].filepath");
@@ -265,6 +276,7 @@ mod tests {
                 model_fields: None,
                 model: Some(StrKeyword::with_value("S".to_owned())),
                 separator: None,
+                on_schema_drift: None,
             },
         };
         let mut model_reg = ModelRegistry::new();
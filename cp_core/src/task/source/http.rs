@@ -8,9 +8,9 @@ use polars::{
 };
 
 use crate::{
-    model::common::ModelConfig,
+    model::common::{ModelConfig, ModelFields},
     model_emplace,
-    parser::keyword::Keyword,
+    parser::{keyword::Keyword, model::SchemaDriftPolicy},
     pipeline::context::{DefaultPipelineContext, PipelineContext},
     util::{
         common::str_json_to_df,
@@ -113,6 +113,17 @@ impl SourceConfig for HttpSourceConfig {
         model_emplace!(self.http, ctx, context);
         Ok(())
     }
+    fn model_fields(&self) -> Option<&ModelFields> {
+        self.http.model_fields.as_ref()
+    }
+    fn schema_drift_policy(&self) -> Option<SchemaDriftPolicy> {
+        self.http.on_schema_drift
+    }
+
+    fn take_model_fields(&mut self) -> Option<ModelFields> {
+        self.http.model_fields.take()
+    }
+
     fn validate(&self) -> Vec<CpError> {
         let mut errors = vec![];
         valid_or_insert_error!(errors, self.http.url, "source[http].url");
@@ -180,6 +191,7 @@ mod tests {
                 options: None,
                 model: None,
                 model_fields: None,
+                on_schema_drift: None,
             },
         };
         let context = serde_yaml_ng::from_str::<serde_yaml_ng::Mapping>("output: table").unwrap();
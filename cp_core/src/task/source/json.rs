@@ -4,9 +4,9 @@ use async_trait::async_trait;
 use polars::prelude::{LazyFileListReader, LazyFrame, LazyJsonLineReader, Schema};
 
 use crate::{
-    model::common::ModelConfig,
+    model::common::{ModelConfig, ModelFields},
     model_emplace,
-    parser::keyword::Keyword,
+    parser::{keyword::Keyword, model::SchemaDriftPolicy},
     pipeline::context::{DefaultPipelineContext, PipelineContext},
     util::{
         common::get_full_path,
@@ -79,6 +79,17 @@ impl SourceConfig for JsonSourceConfig {
         model_emplace!(self.json, ctx, context);
         Ok(())
     }
+    fn model_fields(&self) -> Option<&ModelFields> {
+        self.json.model_fields.as_ref()
+    }
+    fn schema_drift_policy(&self) -> Option<SchemaDriftPolicy> {
+        self.json.on_schema_drift
+    }
+
+    fn take_model_fields(&mut self) -> Option<ModelFields> {
+        self.json.model_fields.take()
+    }
+
     fn validate(&self) -> Vec<CpError> {
         let mut errors = vec![];
         valid_or_insert_error!(errors, self.json.filepath, "source[json].filepath");
@@ -213,6 +224,7 @@ mod tests {
                 output: StrKeyword::with_value("_sample".to_owned()),
                 model_fields: None,
                 model: Some(StrKeyword::with_value("S".to_owned())),
+                on_schema_drift: None,
             },
         };
         let mut model_reg = ModelRegistry::new();
@@ -1,6 +1,10 @@
+pub mod bson;
+pub mod clickhouse;
 pub mod common;
 pub mod config;
 pub mod csv;
 pub mod http;
 pub mod json;
+pub mod mongo;
+pub mod parquet;
 pub mod sql;
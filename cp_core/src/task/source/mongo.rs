@@ -1,17 +1,309 @@
 use std::sync::Arc;
 
-use polars::prelude::{Expr, LazyFrame};
+use async_trait::async_trait;
+use futures::TryStreamExt;
+use mongodb::{bson::Document, options::FindOptions};
+use polars::prelude::{IntoLazy, LazyFrame, Schema};
 
-use crate::{pipeline::context::DefaultPipelineContext, util::error::CpResult};
+use crate::{
+    db_url_emplace,
+    model::common::{ModelConfig, ModelFields},
+    model_emplace,
+    parser::{
+        keyword::{Keyword, StrKeyword},
+        model::SchemaDriftPolicy,
+    },
+    pipeline::context::{DefaultPipelineContext, PipelineContext},
+    util::{
+        bson::bson_to_df,
+        error::{CpError, CpResult},
+    },
+    valid_or_insert_error,
+};
 
-use super::common::Source;
+use super::{
+    common::{Source, SourceConfig},
+    config::MongoSourceConfig,
+};
 
-/// add this back to sink/mod.rs when ready
 pub struct MongoSource {
     uri: String,
-    find: mongodb::bson::Document,
-    projection: mongodb::bson::Document,
+    collection: String,
+    find: Document,
+    projection: Option<Document>,
     output: String,
-    strict: bool,
-    columns: Option<Vec<Expr>>,
+    schema: Option<Arc<Schema>>,
+}
+
+impl MongoSource {
+    fn options(&self) -> FindOptions {
+        let mut options = FindOptions::default();
+        options.projection = self.projection.clone();
+        options
+    }
+
+    fn no_default_database(&self) -> CpError {
+        CpError::ConfigError(
+            "Missing mongo database",
+            format!("No default database in uri for collection `{}`", &self.collection),
+        )
+    }
+
+    fn to_lazy(&self, documents: &[Document]) -> CpResult<LazyFrame> {
+        Ok(bson_to_df(documents, self.schema.as_deref())?.lazy())
+    }
+}
+
+#[async_trait]
+impl Source for MongoSource {
+    fn connection_type(&self) -> &str {
+        "mongo"
+    }
+
+    fn name(&self) -> &str {
+        self.output.as_str()
+    }
+
+    fn run(&self, _ctx: Arc<DefaultPipelineContext>) -> CpResult<LazyFrame> {
+        let client = mongodb::sync::Client::with_uri_str(&self.uri)?;
+        let db = client.default_database().ok_or_else(|| self.no_default_database())?;
+        let documents = db
+            .collection::<Document>(&self.collection)
+            .find(self.find.clone())
+            .with_options(self.options())
+            .run()?
+            .collect::<Result<Vec<_>, _>>()?;
+        self.to_lazy(&documents)
+    }
+
+    async fn fetch(&self, _ctx: Arc<DefaultPipelineContext>) -> CpResult<LazyFrame> {
+        let client = mongodb::Client::with_uri_str(&self.uri).await?;
+        let db = client.default_database().ok_or_else(|| self.no_default_database())?;
+        let documents = db
+            .collection::<Document>(&self.collection)
+            .find(self.find.clone())
+            .with_options(self.options())
+            .await?
+            .try_collect::<Vec<_>>()
+            .await?;
+        self.to_lazy(&documents)
+    }
+}
+
+impl SourceConfig for MongoSourceConfig {
+    fn emplace(&mut self, ctx: &DefaultPipelineContext, context: &serde_yaml_ng::Mapping) -> CpResult<()> {
+        db_url_emplace!(self.mongo, ctx, context, "mongodb://");
+        if let Some(mut output) = self.mongo.output.take() {
+            output.insert_value_from_context(context)?;
+            let _ = self.mongo.output.insert(output);
+        }
+        self.mongo.collection.insert_value_from_context(context)?;
+        model_emplace!(self.mongo, ctx, context);
+        Ok(())
+    }
+
+    fn model_fields(&self) -> Option<&ModelFields> {
+        self.mongo.model_fields.as_ref()
+    }
+    fn schema_drift_policy(&self) -> Option<SchemaDriftPolicy> {
+        self.mongo.on_schema_drift
+    }
+
+    fn take_model_fields(&mut self) -> Option<ModelFields> {
+        self.mongo.model_fields.take()
+    }
+
+    fn validate(&self) -> Vec<CpError> {
+        let mut errors = vec![];
+        match &self.mongo.url {
+            Some(url) => valid_or_insert_error!(errors, url, "source[mongo].url"),
+            None => errors.push(CpError::ConfigValidationError(
+                "source[mongo].url",
+                "Missing url or env_connection, please declare".to_owned(),
+            )),
+        }
+        if let Some(output) = &self.mongo.output {
+            valid_or_insert_error!(errors, output, "source[mongo].output");
+        } else {
+            errors.push(CpError::ConfigValidationError(
+                "source[mongo].output",
+                "Missing df output name, please declare".to_owned(),
+            ));
+        }
+        valid_or_insert_error!(errors, self.mongo.collection, "source[mongo].collection");
+        if let Some(model_fields) = &self.mongo.model_fields {
+            for (key_kw, field_kw) in model_fields.iter() {
+                valid_or_insert_error!(errors, key_kw, "source[mongo].model.key");
+                valid_or_insert_error!(errors, field_kw, "source[mongo].model.field");
+            }
+        }
+        errors
+    }
+
+    fn transform(&self) -> Box<dyn Source> {
+        // By here the model_fields should be completely populated.
+        let schema = self.mongo.model_fields.as_ref().map(|x| {
+            ModelConfig {
+                label: "".to_string(),
+                fields: x.clone(),
+            }
+            .schema()
+            .expect("failed to build schema")
+        });
+        Box::new(MongoSource {
+            uri: self
+                .mongo
+                .url
+                .as_ref()
+                .and_then(|x| x.value())
+                .expect("source[mongo].url")
+                .to_owned(),
+            collection: self
+                .mongo
+                .collection
+                .value()
+                .expect("source[mongo].collection")
+                .to_owned(),
+            find: self.mongo.find.clone(),
+            projection: self.mongo.projection.clone(),
+            output: self
+                .mongo
+                .output
+                .as_ref()
+                .and_then(|x| x.value())
+                .expect("source[mongo].output")
+                .to_owned(),
+            schema: schema.map(Arc::new),
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Arc;
+
+    use mongodb::bson::{DateTime, doc};
+    use polars::prelude::{DataType, TimeUnit};
+
+    use crate::{
+        async_st,
+        context::{connection::ConnectionRegistry, envvar::EnvironmentVariableRegistry, model::ModelRegistry},
+        parser::keyword::Keyword,
+        pipeline::context::DefaultPipelineContext,
+        task::source::{common::SourceConfig, config::MongoSourceConfig},
+        util::common::{create_config_pack, rng_str},
+    };
+
+    const MONGO_URL: &str = "mongodb://localhost:27017/capport";
+
+    fn config() -> &'static str {
+        "
+mongo:
+    env_connection: $conn
+    output: $output
+    collection: $collection
+    find:
+        team: NJD
+    projection:
+        _id: 0
+        name: 1
+        age: 1
+        joined: 1
+    model: player
+"
+    }
+
+    fn context(db_env: &str) -> DefaultPipelineContext {
+        let mut config_pack = create_config_pack([format!(
+            "
+connection:
+    local_mongo:
+        db_env: {}
+        port: 27017
+model:
+    player:
+        name: str
+        age: int64
+        joined: datetime_utc
+",
+            db_env
+        )]);
+        DefaultPipelineContext::new()
+            .with_connection_registry(ConnectionRegistry::from(&mut config_pack).unwrap())
+            .with_model_registry(ModelRegistry::from(&mut config_pack).unwrap())
+    }
+
+    #[test]
+    fn valid_mongo_src_config_emplace() {
+        let mut config = serde_yaml_ng::from_str::<MongoSourceConfig>(config()).unwrap();
+        assert_eq!(config.validate().len(), 3);
+        let mut ev = EnvironmentVariableRegistry::new();
+        ev.set_str("CP_TEST_MONGO_EMPLACE_DB", "capport".to_owned()).unwrap();
+        let ctx = context("CP_TEST_MONGO_EMPLACE_DB");
+        let mapping = serde_yaml_ng::from_str::<serde_yaml_ng::Mapping>(
+            "conn: local_mongo\noutput: PLAYERS\ncollection: players",
+        )
+        .unwrap();
+        config.emplace(&ctx, &mapping).unwrap();
+        assert!(config.validate().is_empty());
+        assert_eq!(config.mongo.url.as_ref().unwrap().value().unwrap(), MONGO_URL);
+        assert_eq!(config.mongo.model_fields.as_ref().unwrap().len(), 3);
+        let source = config.transform();
+        assert_eq!(source.name(), "PLAYERS");
+        assert_eq!(source.connection_type(), "mongo");
+    }
+
+    #[test]
+    fn invalid_mongo_src_config() {
+        let config = serde_yaml_ng::from_str::<MongoSourceConfig>(
+            "
+mongo:
+    collection: players
+    find: {}
+",
+        )
+        .unwrap();
+        assert_eq!(config.validate().len(), 2);
+    }
+
+    #[test]
+    fn valid_mongo_src_run_fetch() {
+        let collection = format!("players_{}", rng_str(6));
+        let client = mongodb::sync::Client::with_uri_str(MONGO_URL).unwrap();
+        let coll = client
+            .default_database()
+            .unwrap()
+            .collection::<mongodb::bson::Document>(&collection);
+        coll.insert_many(vec![
+            doc! { "name": "Jack", "age": 23, "team": "NJD", "joined": DateTime::from_millis(1_600_000_000_000) },
+            doc! { "name": "Nico", "age": 25, "team": "NJD" },
+            doc! { "name": "Auston", "age": 27, "team": "TOR" },
+        ])
+        .run()
+        .unwrap();
+
+        let mut config = serde_yaml_ng::from_str::<MongoSourceConfig>(config()).unwrap();
+        let mut ev = EnvironmentVariableRegistry::new();
+        ev.set_str("CP_TEST_MONGO_RUN_DB", "capport".to_owned()).unwrap();
+        let ctx = context("CP_TEST_MONGO_RUN_DB");
+        let mapping = serde_yaml_ng::from_str::<serde_yaml_ng::Mapping>(&format!(
+            "conn: local_mongo\noutput: PLAYERS\ncollection: {}",
+            &collection
+        ))
+        .unwrap();
+        config.emplace(&ctx, &mapping).unwrap();
+        let source = config.transform();
+        let ctx = Arc::new(ctx);
+        let actual = source.run(ctx.clone()).unwrap().collect().unwrap();
+        assert_eq!(actual.shape(), (2, 3));
+        assert_eq!(
+            actual.column("joined").unwrap().dtype(),
+            &DataType::Datetime(TimeUnit::Milliseconds, Some("UTC".into()))
+        );
+        async_st!(async || {
+            let fetched = source.fetch(ctx).await.unwrap().collect().unwrap();
+            assert_eq!(fetched, actual);
+        });
+        coll.drop().run().unwrap();
+    }
 }
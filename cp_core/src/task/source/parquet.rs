@@ -0,0 +1,280 @@
+use std::{path::PathBuf, str::FromStr, sync::Arc};
+
+use async_trait::async_trait;
+use polars::prelude::{Expr, LazyFrame, ScanArgsParquet, Schema, col};
+
+use crate::{
+    model::common::{ModelConfig, ModelFields},
+    model_emplace,
+    parser::{keyword::Keyword, model::SchemaDriftPolicy},
+    pipeline::context::{DefaultPipelineContext, PipelineContext},
+    util::{
+        common::get_full_path,
+        error::{CpError, CpResult},
+    },
+    valid_or_insert_error,
+};
+
+use super::{
+    common::{Source, SourceConfig},
+    config::ParquetSourceConfig,
+};
+
+pub struct ParquetSource {
+    filepath: PathBuf,
+    output: String,
+    schema: Option<Arc<Schema>>,
+}
+
+/// A filepath such as `data/*.parquet` is expanded to every matching file.
+fn is_glob(filepath: &std::path::Path) -> bool {
+    filepath.to_str().is_some_and(|x| x.contains(['*', '?', '[']))
+}
+
+impl ParquetSource {
+    pub fn new(filepath: &str, output: &str) -> Self {
+        Self {
+            filepath: std::path::PathBuf::from_str(filepath).expect("bad filepath"),
+            output: output.to_owned(),
+            schema: None,
+        }
+    }
+
+    pub fn and_schema(mut self, schema: Schema) -> Self {
+        let _ = self.schema.insert(Arc::new(schema));
+        self
+    }
+}
+
+#[async_trait]
+impl Source for ParquetSource {
+    fn connection_type(&self) -> &str {
+        "parquet"
+    }
+
+    fn name(&self) -> &str {
+        self.output.as_str()
+    }
+
+    async fn fetch(&self, ctx: Arc<DefaultPipelineContext>) -> CpResult<LazyFrame> {
+        self.run(ctx)
+    }
+
+    fn run(&self, _ctx: Arc<DefaultPipelineContext>) -> CpResult<LazyFrame> {
+        // Reopens files every run
+        if !is_glob(&self.filepath) && !self.filepath.exists() {
+            return Err(CpError::ConfigError(
+                "File not found",
+                self.filepath.to_str().unwrap().to_owned(),
+            ));
+        }
+        let args = ScanArgsParquet {
+            glob: true,
+            allow_missing_columns: self.schema.is_some(),
+            ..Default::default()
+        };
+        let lf = LazyFrame::scan_parquet(&self.filepath, args)?;
+        Ok(match &self.schema {
+            // parquet files carry their own schema, so the model only selects and casts
+            Some(schema) => lf.select(
+                schema
+                    .iter()
+                    .map(|(name, dtype)| col(name.clone()).cast(dtype.clone()))
+                    .collect::<Vec<Expr>>(),
+            ),
+            None => lf,
+        })
+    }
+}
+
+impl SourceConfig for ParquetSourceConfig {
+    fn emplace(&mut self, ctx: &DefaultPipelineContext, context: &serde_yaml_ng::Mapping) -> CpResult<()> {
+        self.parquet.filepath.insert_value_from_context(context)?;
+        self.parquet.output.insert_value_from_context(context)?;
+        model_emplace!(self.parquet, ctx, context);
+        Ok(())
+    }
+    fn model_fields(&self) -> Option<&ModelFields> {
+        self.parquet.model_fields.as_ref()
+    }
+    fn schema_drift_policy(&self) -> Option<SchemaDriftPolicy> {
+        self.parquet.on_schema_drift
+    }
+
+    fn take_model_fields(&mut self) -> Option<ModelFields> {
+        self.parquet.model_fields.take()
+    }
+
+    fn validate(&self) -> Vec<CpError> {
+        let mut errors = vec![];
+        valid_or_insert_error!(errors, self.parquet.filepath, "source[parquet].filepath");
+        valid_or_insert_error!(errors, self.parquet.output, "source[parquet].output");
+        if let Some(model_fields) = &self.parquet.model_fields {
+            for (key_kw, field_kw) in model_fields.iter() {
+                valid_or_insert_error!(errors, key_kw, "source[parquet].model.key");
+                valid_or_insert_error!(errors, field_kw, "source[parquet].model.field");
+            }
+        }
+        errors
+    }
+
+    fn transform(&self) -> Box<dyn Source> {
+        // By here the model_fields should be completely populated.
+        let schema = self.parquet.model_fields.as_ref().map(|x| {
+            ModelConfig {
+                label: "".to_string(),
+                fields: x.clone(),
+            }
+            .schema()
+            .expect("failed to build schema")
+        });
+
+        let filepath = get_full_path(self.parquet.filepath.value().expect("filepath"), true).expect("bad filepath");
+
+        Box::new(ParquetSource {
+            filepath,
+            output: self.parquet.output.value().expect("output").to_owned(),
+            schema: schema.map(Arc::new),
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Arc;
+
+    use polars::{
+        df,
+        frame::DataFrame,
+        prelude::{DataType, ParquetWriter},
+    };
+
+    use crate::{
+        async_st,
+        context::model::ModelRegistry,
+        model::common::{ModelConfig, ModelFieldInfo, ModelFields},
+        parser::{
+            dtype::DType,
+            keyword::{Keyword, ModelFieldKeyword, StrKeyword},
+        },
+        pipeline::context::DefaultPipelineContext,
+        task::source::{
+            common::{Source, SourceConfig},
+            config::{LocalFileSourceConfig, ParquetSourceConfig},
+        },
+        util::{common::rng_str, test::assert_frame_equal, tmp::TempFile},
+    };
+
+    use super::ParquetSource;
+
+    fn example() -> DataFrame {
+        df!(
+            "a" => [-1, 1, 3, 5, 6],
+            "b" => ["z", "a", "j", "i", "c"],
+        )
+        .unwrap()
+    }
+
+    fn example_model() -> ModelConfig {
+        ModelConfig {
+            label: "S".to_string(),
+            fields: ModelFields::from([
+                (
+                    StrKeyword::with_value("a".to_owned()),
+                    ModelFieldKeyword::with_value(ModelFieldInfo::with_dtype(DType(DataType::Int64))),
+                ),
+                (
+                    StrKeyword::with_value("b".to_owned()),
+                    ModelFieldKeyword::with_value(ModelFieldInfo::with_dtype(DType(DataType::String))),
+                ),
+            ]),
+        }
+    }
+
+    fn write_example(tmp: &TempFile, frame: &DataFrame) {
+        let buffer = tmp.get_mut().unwrap();
+        ParquetWriter::new(buffer).finish(&mut frame.clone()).unwrap();
+    }
+
+    #[test]
+    fn valid_parquet_source() {
+        let expected = example();
+        let tmp = TempFile::default();
+        write_example(&tmp, &expected);
+        let parquet_source = ParquetSource::new(&tmp.filepath, "_sample");
+        let ctx = Arc::new(DefaultPipelineContext::new());
+        let result = parquet_source.run(ctx).unwrap();
+        assert_frame_equal(result.collect().unwrap(), expected);
+        assert_eq!(parquet_source.name(), "_sample");
+        assert_eq!(parquet_source.connection_type(), "parquet");
+    }
+
+    #[test]
+    fn valid_parquet_source_async_schema() {
+        let tmp = TempFile::default();
+        write_example(&tmp, &example());
+        let model_schema = example_model().schema().unwrap();
+        let parquet_source = ParquetSource::new(&tmp.filepath, "_sample").and_schema(model_schema);
+        let ctx = Arc::new(DefaultPipelineContext::new());
+        let expected = df!(
+            "a" => [-1i64, 1, 3, 5, 6],
+            "b" => ["z", "a", "j", "i", "c"],
+        )
+        .unwrap();
+        async_st!(async || {
+            let result = parquet_source.fetch(ctx).await.unwrap();
+            assert_frame_equal(result.collect().unwrap(), expected);
+        });
+    }
+
+    #[test]
+    fn valid_parquet_source_glob() {
+        let dir = std::env::temp_dir().join(format!("cp_parquet_{}", rng_str(8)));
+        std::fs::create_dir_all(&dir).unwrap();
+        let first = TempFile::new(dir.join("first.parquet").to_str().unwrap()).unwrap();
+        let second = TempFile::new(dir.join("second.parquet").to_str().unwrap()).unwrap();
+        write_example(&first, &example());
+        write_example(&second, &example());
+        let parquet_source = ParquetSource::new(dir.join("*.parquet").to_str().unwrap(), "_sample");
+        let ctx = Arc::new(DefaultPipelineContext::new());
+        let actual = parquet_source.run(ctx).unwrap().collect().unwrap();
+        let _ = std::fs::remove_dir_all(&dir);
+        assert_frame_equal(actual, example().vstack(&example()).unwrap());
+    }
+
+    #[test]
+    fn invalid_parquet_source_missing_file() {
+        let parquet_source = ParquetSource::new("/tmp/__cp_missing_file__.parquet", "_sample");
+        let ctx = Arc::new(DefaultPipelineContext::new());
+        assert!(parquet_source.run(ctx).is_err());
+    }
+
+    #[test]
+    fn valid_parquet_source_config_to_parquet_source() {
+        let tmp = TempFile::default();
+        write_example(&tmp, &example());
+        let mut source_config = ParquetSourceConfig {
+            parquet: LocalFileSourceConfig {
+                filepath: StrKeyword::with_value(tmp.filepath.clone()),
+                output: StrKeyword::with_symbol("output"),
+                model_fields: None,
+                model: Some(StrKeyword::with_value("S".to_owned())),
+                on_schema_drift: None,
+            },
+        };
+        let mut model_reg = ModelRegistry::new();
+        model_reg.insert(example_model());
+        let ctx = Arc::new(DefaultPipelineContext::new().with_model_registry(model_reg));
+        let mapping = serde_yaml_ng::from_str("output: _sample").unwrap();
+        source_config.emplace(&ctx, &mapping).unwrap();
+        assert!(source_config.validate().is_empty());
+        assert_eq!(
+            source_config.parquet.model_fields.clone().unwrap(),
+            example_model().fields
+        );
+        let actual_node = source_config.transform();
+        assert_eq!(actual_node.name(), "_sample");
+        let result = actual_node.run(ctx.clone()).unwrap().collect().unwrap();
+        assert_eq!(result.column("a").unwrap().dtype(), &DataType::Int64);
+    }
+}
@@ -1,11 +1,14 @@
 use polars::prelude::*;
 
 use super::config::{
-    DropTransformConfig, JoinTransformConfig, RootTransformConfig, SelectTransformConfig, SqlTransformConfig,
-    TimeConvertConfig, UniformIdTypeConfig, UnnestTransformConfig, WithColTransformConfig,
+    DistinctTransformConfig, DropTransformConfig, FilterTransformConfig, GroupByTransformConfig, JoinTransformConfig,
+    LimitTransformConfig, OffsetTransformConfig, PivotTransformConfig, RootTransformConfig, SelectTransformConfig,
+    SortTransformConfig, SqlTransformConfig, TimeConvertConfig, UniformIdTypeConfig, UnionTransformConfig,
+    UnnestTransformConfig, UnpivotTransformConfig, WindowTransformConfig, WithColTransformConfig,
 };
 use crate::frame::common::{FrameAsyncListenHandle, FrameUpdate};
 use crate::frame::polars::PolarsAsyncListenHandle;
+use crate::model::constraint::ModelConstraintCheck;
 use crate::parser::keyword::Keyword;
 use crate::task::stage::StageTaskConfig;
 use crate::try_deserialize_stage;
@@ -24,6 +27,12 @@ pub trait Transform {
 
 pub trait TransformConfig {
     fn emplace(&mut self, context: &serde_yaml_ng::Mapping) -> CpResult<()>;
+    /// Resolves the model of transforms that declare one
+    fn emplace_model(&mut self, _ctx: &DefaultPipelineContext, _context: &serde_yaml_ng::Mapping) -> CpResult<()> {
+        Ok(())
+    }
+    /// Receives the input of the root transform, for transforms that refer to it
+    fn emplace_input(&mut self, _input: &str) {}
     fn validate(&self) -> Vec<CpError>;
     fn transform(&self) -> Box<dyn Transform>;
 }
@@ -34,6 +43,9 @@ pub struct RootTransform {
     input: String,
     output: String,
     subtransforms: Vec<Box<dyn Transform>>,
+    check: Option<ModelConstraintCheck>,
+    /// further outputs with the predicate selecting their rows from the output
+    routes: Vec<(String, Expr)>,
 }
 
 impl RootTransform {
@@ -43,11 +55,59 @@ impl RootTransform {
             input: input.to_string(),
             output: output.to_string(),
             subtransforms,
+            check: None,
+            routes: vec![],
         }
     }
 
+    /// Sends the rows of the output matching each predicate to another output as well
+    pub fn with_routes(mut self, routes: Vec<(String, Expr)>) -> RootTransform {
+        self.routes = routes;
+        self
+    }
+
+    /// Checks the output against a model's constraints after the subtransforms have run
+    pub fn with_constraint_check(mut self, check: ModelConstraintCheck) -> RootTransform {
+        let _ = self.check.insert(check);
+        self
+    }
+
     pub fn produces(&self) -> Vec<String> {
-        vec![self.output.clone()]
+        let mut produces = vec![self.output.clone()];
+        produces.extend(self.routes.iter().map(|(route, _)| route.clone()));
+        produces
+    }
+
+    /// Collects the output once if it has routes, returning the collected output along with the
+    /// rows of each route, so that the plan does not run again for every receiver
+    fn route(&self, output: LazyFrame) -> (LazyFrame, Vec<(&str, LazyFrame)>) {
+        if self.routes.is_empty() {
+            return (output, vec![]);
+        }
+        let df = match output.collect() {
+            Ok(df) => df,
+            Err(e) => {
+                log::error!("{}. Routing empty frames", e);
+                DataFrame::empty()
+            }
+        };
+        let routes = self.split_routes(&df);
+        (df.lazy(), routes)
+    }
+
+    /// The rows of a collected output matching each route
+    fn split_routes(&self, df: &DataFrame) -> Vec<(&str, LazyFrame)> {
+        self.routes
+            .iter()
+            .map(|(route, predicate)| {
+                let frame = if df.is_empty() {
+                    DataFrame::empty().lazy()
+                } else {
+                    df.clone().lazy().filter(predicate.clone())
+                };
+                (route.as_str(), frame)
+            })
+            .collect()
     }
 }
 
@@ -68,14 +128,17 @@ impl Stage for RootTransform {
                 DataFrame::empty().lazy()
             }
         };
-        let df = output.clone().collect().expect("transform");
+        let df = output.collect().expect("transform");
         log::info!(
             "[Transform] OUTPUT `{}`: {:?}\n{}",
             &self.label,
             &df,
             format_schema(&df.schema())
         );
-        ctx.insert_result(&self.output, output)
+        for (route, frame) in self.split_routes(&df) {
+            ctx.insert_result(route, frame)?;
+        }
+        ctx.insert_result(&self.output, df.lazy())
     }
     /// The synchronous, concurrent execution listens to input for the initial frame, and broadcasts to output
     /// the produced frame
@@ -85,6 +148,10 @@ impl Stage for RootTransform {
         log::info!("Stage initialized: {}", &self.label);
         let mut input_listener = lctx.get_listener(&self.input, &self.label)?;
         let mut output_broadcast = bctx.get_broadcast(&self.output, &self.label)?;
+        let mut route_broadcasts = vec![];
+        for (route, _) in &self.routes {
+            route_broadcasts.push(bctx.get_broadcast(route, &self.label)?);
+        }
         let update = input_listener.force_listen();
         let input = update.frame.read()?.clone();
         let output = match self.run(input, ctx.clone()) {
@@ -94,6 +161,12 @@ impl Stage for RootTransform {
                 DataFrame::empty().lazy()
             }
         };
+        let (output, routes) = self.route(output);
+        for ((route, frame), broadcast) in routes.into_iter().zip(route_broadcasts.iter_mut()) {
+            if let Err(e) = broadcast.broadcast(frame) {
+                log::error!("{}: {:?}", route, e);
+            }
+        }
         output_broadcast.broadcast(output)
     }
     /// The asynchronous, concurrent execution executes until it receives a Kill message.
@@ -103,6 +176,10 @@ impl Stage for RootTransform {
         let mut listen = ctx.get_async_listener(&self.input, &self.label)?;
         let lp: *mut PolarsAsyncListenHandle = &mut listen;
         let mut output_broadcast = ctx.get_async_broadcast(&self.output, &self.label)?;
+        let mut route_broadcasts = vec![];
+        for (route, _) in &self.routes {
+            route_broadcasts.push(ctx.get_async_broadcast(route, &self.label)?);
+        }
         loop {
             let update: FrameUpdate<LazyFrame> = unsafe { (*lp).listen().await? };
             log::trace!("{} Received update: {:?}", &self.label, update.info);
@@ -116,7 +193,14 @@ impl Stage for RootTransform {
                             DataFrame::empty().lazy()
                         }
                     };
-                    log::trace!("BCAST RootTransform handle {} to: {:?}", &self.label, &self.output);
+                    log::trace!("BCAST RootTransform handle {} to: {:?}", &self.label, &self.produces());
+                    let (output, routes) = self.route(output);
+                    for ((route, frame), broadcast) in routes.into_iter().zip(route_broadcasts.iter_mut()) {
+                        match broadcast.broadcast(frame) {
+                            Ok(_) => log::info!("Sent update for frame {}", route),
+                            Err(e) => log::error!("{}: {:?}", route, e),
+                        };
+                    }
                     match output_broadcast.broadcast(output) {
                         Ok(_) => log::info!("Sent update for frame {}", &self.output),
                         Err(e) => log::error!("{}: {:?}", &self.output, e),
@@ -126,6 +210,10 @@ impl Stage for RootTransform {
                 FrameUpdateType::Kill => {
                     log::info!("[Transform] Sent termination signal for frame {}", &self.output);
                     output_broadcast.kill().unwrap();
+                    for ((route, _), broadcast) in self.routes.iter().zip(route_broadcasts.iter_mut()) {
+                        log::info!("[Transform] Sent termination signal for frame {}", route);
+                        broadcast.kill().unwrap();
+                    }
                     log::info!(
                         "Terminating transform stage `{}` after {} iterations",
                         &self.label,
@@ -146,7 +234,10 @@ impl Transform for RootTransform {
         for subtransform in &self.subtransforms {
             next = subtransform.as_ref().run(next, ctx.clone())?
         }
-        Ok(next)
+        match &self.check {
+            Some(check) => Ok(check.enforce(next.collect()?)?.lazy()),
+            None => Ok(next),
+        }
     }
 }
 
@@ -161,11 +252,21 @@ impl RootTransformConfig {
                     SelectTransformConfig,
                     JoinTransformConfig,
                     DropTransformConfig,
+                    FilterTransformConfig,
+                    SortTransformConfig,
+                    LimitTransformConfig,
+                    OffsetTransformConfig,
+                    DistinctTransformConfig,
+                    GroupByTransformConfig,
                     SqlTransformConfig,
                     UnnestTransformConfig,
                     WithColTransformConfig,
                     TimeConvertConfig,
-                    UniformIdTypeConfig
+                    UniformIdTypeConfig,
+                    WindowTransformConfig,
+                    PivotTransformConfig,
+                    UnionTransformConfig,
+                    UnpivotTransformConfig
                 );
                 config.ok_or_else(|| {
                     CpError::ConfigError(
@@ -181,17 +282,33 @@ impl RootTransformConfig {
 impl StageTaskConfig<RootTransform> for RootTransformConfig {
     fn parse(
         &self,
-        _ctx: &DefaultPipelineContext,
+        ctx: &DefaultPipelineContext,
         context: &serde_yaml_ng::Mapping,
     ) -> Result<RootTransform, Vec<CpError>> {
         let mut subtransforms = vec![];
         let mut errors = vec![];
+        let mut input = self.input.clone();
+        let mut output = self.output.clone();
+        match input.insert_value_from_context(context) {
+            Ok(_) => {}
+            Err(e) => errors.push(e),
+        }
+        match output.insert_value_from_context(context) {
+            Ok(_) => {}
+            Err(e) => errors.push(e),
+        }
         for result in self.parse_subtransforms() {
             match result {
                 Ok(mut config) => {
                     if let Err(e) = config.emplace(context) {
                         errors.push(e);
                     }
+                    if let Err(e) = config.emplace_model(ctx, context) {
+                        errors.push(e);
+                    }
+                    if let Some(input) = input.value() {
+                        config.emplace_input(input);
+                    }
                     let errs = config.validate();
                     if errs.is_empty() {
                         subtransforms.push(config.transform());
@@ -202,22 +319,88 @@ impl StageTaskConfig<RootTransform> for RootTransformConfig {
                 Err(e) => errors.push(e),
             }
         }
-        let mut input = self.input.clone();
-        let mut output = self.output.clone();
-        match input.insert_value_from_context(context) {
-            Ok(_) => {}
-            Err(e) => errors.push(e),
+        let mut routes = vec![];
+        for route in self.routes.iter().flatten() {
+            let mut route = route.clone();
+            if let Err(e) = route.output.insert_value_from_context(context) {
+                errors.push(e);
+            }
+            if let Err(e) = route.when.emplace(context) {
+                errors.push(e);
+            }
+            let mut route_errors = vec![];
+            route.when.validate(&mut route_errors);
+            match (route.output.value(), route_errors.is_empty()) {
+                (Some(name), true) => routes.push((name.clone(), route.when.expr())),
+                _ => errors.extend(route_errors),
+            }
         }
-        match output.insert_value_from_context(context) {
-            Ok(_) => {}
-            Err(e) => errors.push(e),
+        if let Some(mut otherwise) = self.otherwise.clone() {
+            if let Err(e) = otherwise.insert_value_from_context(context) {
+                errors.push(e);
+            }
+            if self.routes.as_ref().is_none_or(|x| x.is_empty()) {
+                errors.push(CpError::ConfigValidationError(
+                    "transform.otherwise",
+                    "Missing routes to take the otherwise branch of, please declare".to_owned(),
+                ));
+            }
+            if let Some(name) = otherwise.value() {
+                // rows with a null predicate are not routed, hence go to otherwise
+                let routed = routes.iter().fold(lit(false), |acc, (_, predicate)| {
+                    acc.or(predicate.clone().fill_null(lit(false)))
+                });
+                routes.push((name.clone(), routed.not()));
+            }
+        }
+        let mut outputs = output
+            .value()
+            .into_iter()
+            .chain(routes.iter().map(|(x, _)| x))
+            .collect::<Vec<_>>();
+        outputs.sort();
+        if let Some(duplicate) = outputs.windows(2).find(|x| x[0] == x[1]) {
+            errors.push(CpError::ConfigValidationError(
+                "transform.routes",
+                format!("Output `{}` is produced more than once", duplicate[0]),
+            ));
         }
+        let mut model_fields = None;
+        if let Some(mut model) = self.model.clone() {
+            match model.insert_value_from_context(context) {
+                Ok(_) => {}
+                Err(e) => errors.push(e),
+            }
+            if let Some(name) = model.value() {
+                match ctx.get_substituted_model_fields(name, context) {
+                    Ok(fields) => {
+                        let _ = model_fields.insert(fields);
+                    }
+                    Err(e) => errors.push(e),
+                }
+            }
+        }
+        let check = match (self.constraint_policy, &model_fields) {
+            (Some(policy), Some(fields)) => {
+                ModelConstraintCheck::new(output.value().map_or("?", |x| x.as_str()), fields, policy)
+            }
+            (Some(_), None) => {
+                errors.push(CpError::ConfigValidationError(
+                    "transform.constraint_policy",
+                    "Missing model to check constraints against, please declare".to_owned(),
+                ));
+                None
+            }
+            (None, _) => None,
+        };
         if errors.is_empty() {
             Ok(RootTransform {
                 label: self.label.clone(),
                 input: input.value().expect("input").clone(),
                 output: output.value().expect("output").clone(),
                 subtransforms,
+                check,
+                routes,
             })
         } else {
             Err(errors)
@@ -233,9 +416,11 @@ mod tests {
 
     use super::{RootTransform, Transform};
     use crate::async_st;
+    use crate::context::model::ModelRegistry;
     use crate::parser::keyword::{Keyword, StrKeyword};
     use crate::task::stage::StageTaskConfig;
     use crate::task::transform::config::RootTransformConfig;
+    use crate::util::{common::create_config_pack, test::assert_frame_equal};
     use crate::{
         frame::common::{FrameAsyncBroadcastHandle, FrameAsyncListenHandle, FrameBroadcastHandle, FrameListenHandle},
         pipeline::context::{DefaultPipelineContext, PipelineContext},
@@ -340,6 +525,10 @@ join:
             input: StrKeyword::with_value("test_input".to_owned()),
             output: StrKeyword::with_value("test_output".to_owned()),
             steps: vec![select_value, join_value],
+            model: None,
+            constraint_policy: None,
+            routes: None,
+            otherwise: None,
         };
         let context = serde_yaml_ng::Mapping::new();
         let ctx = Arc::new(DefaultPipelineContext::new());
@@ -367,6 +556,10 @@ purr_transform:
             input: StrKeyword::with_value("test_input".to_owned()),
             output: StrKeyword::with_value("test_output".to_owned()),
             steps: vec![select_value, invalid_value],
+            model: None,
+            constraint_policy: None,
+            routes: None,
+            otherwise: None,
         };
         let context = serde_yaml_ng::Mapping::new();
         let ctx = Arc::new(DefaultPipelineContext::new());
@@ -396,6 +589,10 @@ select:
             input: StrKeyword::with_value("test_input".to_owned()),
             output: StrKeyword::with_value("test_output".to_owned()),
             steps: vec![select_value_1, select_value_2],
+            model: None,
+            constraint_policy: None,
+            routes: None,
+            otherwise: None,
         };
         let mapping = serde_yaml_ng::from_str::<serde_yaml_ng::Mapping>("{input: one, output: three}").unwrap();
         let ctx = Arc::new(DefaultPipelineContext::new());
@@ -414,4 +611,188 @@ select:
         .unwrap();
         assert_eq!(actual.collect().unwrap(), expected);
     }
+
+    #[test]
+    fn valid_root_transform_constraint_policy() {
+        let config = |policy: &str| {
+            serde_yaml_ng::from_str::<RootTransformConfig>(&format!(
+                "
+label: check
+input: orig
+output: actual
+steps:
+    - select: {{ a: a, b: b }}
+model: $model
+constraint_policy: {}
+",
+                policy
+            ))
+            .unwrap()
+        };
+        let mut config_pack = create_config_pack(["
+model:
+    ab:
+        a:
+            dtype: int32
+            constraints: [unique]
+        b: int32
+"]);
+        let ctx = Arc::new(
+            DefaultPipelineContext::with_results(&["orig", "actual"], 1)
+                .with_model_registry(ModelRegistry::from(&mut config_pack).unwrap()),
+        );
+        let context = serde_yaml_ng::from_str::<serde_yaml_ng::Mapping>("model: ab").unwrap();
+        let main = || df!( "a" => [1, 2, 2], "b" => [4, 5, 6] ).unwrap().lazy();
+
+        let trf = config("error").parse(&ctx, &context).unwrap();
+        assert!(trf.run(main(), ctx.clone()).is_err());
+
+        let trf = config("drop_violating_rows").parse(&ctx, &context).unwrap();
+        ctx.insert_result("orig", main()).unwrap();
+        trf.linear(ctx.clone()).unwrap();
+        assert_frame_equal(
+            ctx.extract_clone_result("actual").unwrap(),
+            df!( "a" => [1], "b" => [4] ).unwrap(),
+        );
+        // a constraint policy needs a model to check against
+        let mut config = config("error");
+        config.model = None;
+        assert!(config.parse(&ctx, &context).is_err());
+    }
+
+    #[test]
+    fn valid_root_transform_union_source_column() {
+        let config = serde_yaml_ng::from_str::<RootTransformConfig>(
+            "
+label: combine
+input: $input
+output: actual
+steps:
+    - union: { frames: [extra], source_column: origin }
+",
+        )
+        .unwrap();
+        let ctx = Arc::new(DefaultPipelineContext::with_results(&["orig", "extra", "actual"], 1));
+        let context = serde_yaml_ng::from_str::<serde_yaml_ng::Mapping>("input: orig").unwrap();
+        ctx.insert_result("orig", df!( "a" => [1] ).unwrap().lazy()).unwrap();
+        ctx.insert_result("extra", df!( "a" => [2] ).unwrap().lazy()).unwrap();
+        config.parse(&ctx, &context).unwrap().linear(ctx.clone()).unwrap();
+        assert_frame_equal(
+            ctx.extract_clone_result("actual").unwrap(),
+            df!( "a" => [1, 2], "origin" => ["orig", "extra"] ).unwrap(),
+        );
+    }
+
+    fn routed() -> RootTransform {
+        let config = "
+label: split
+input: orig
+output: $output
+steps: []
+routes:
+    - output: $valid
+      when: { b: { gte: { int32: 5 } } }
+    - output: high
+      when: { a: { eq: { int32: 3 } } }
+otherwise: suspicious
+";
+        let config: RootTransformConfig = serde_yaml_ng::from_str(config).unwrap();
+        let context = serde_yaml_ng::from_str::<serde_yaml_ng::Mapping>("{output: actual, valid: valid}").unwrap();
+        config.parse(&DefaultPipelineContext::new(), &context).unwrap()
+    }
+
+    fn expected_routes() -> Vec<(&'static str, DataFrame)> {
+        vec![
+            ("actual", expected()),
+            ("valid", df!( "a" => [2, 3], "b" => [5, 6] ).unwrap()),
+            ("high", df!( "a" => [3], "b" => [6] ).unwrap()),
+            ("suspicious", df!( "a" => [1], "b" => [4] ).unwrap()),
+        ]
+    }
+
+    #[test]
+    fn valid_root_transform_routes_linear() {
+        let trf = routed();
+        assert_eq!(trf.produces(), vec!["actual", "valid", "high", "suspicious"]);
+        let ctx = Arc::new(DefaultPipelineContext::with_results(
+            &["orig", "actual", "valid", "high", "suspicious"],
+            1,
+        ));
+        ctx.insert_result("orig", expected().lazy()).unwrap();
+        trf.linear(ctx.clone()).unwrap();
+        for (output, expected) in expected_routes() {
+            assert_eq!(ctx.extract_clone_result(output).unwrap(), expected);
+        }
+    }
+
+    #[test]
+    fn valid_root_transform_routes_sync_exec() {
+        let ctx = Arc::new(DefaultPipelineContext::with_results(
+            &["orig", "actual", "valid", "high", "suspicious"],
+            1,
+        ));
+        let lctx = ctx.clone();
+        let fctx = ctx.clone();
+        let mut broadcast = ctx.get_broadcast("orig", "source").unwrap();
+        broadcast.broadcast(expected().lazy()).unwrap();
+        thread::scope(|s| {
+            let _t = s.spawn(move || routed().sync_exec(lctx).unwrap());
+            let _s = s.spawn(move || {
+                for (output, expected) in expected_routes() {
+                    let mut listener = fctx.get_listener(output, "dest").unwrap();
+                    let update = listener.listen().unwrap();
+                    let actual = update.frame.read().unwrap().clone().collect().unwrap();
+                    assert_eq!(actual, expected);
+                }
+            });
+        });
+    }
+
+    #[test]
+    fn valid_root_transform_routes_async_exec() {
+        async_st!(async || {
+            let ctx = Arc::new(DefaultPipelineContext::with_results(
+                &["orig", "actual", "valid", "high", "suspicious"],
+                2,
+            ));
+            let lctx = ctx.clone();
+            let fctx = ctx.clone();
+            let lhandle = async move || {
+                assert_eq!(routed().async_exec(lctx).await.unwrap(), 1);
+            };
+            let thandle = async move || {
+                let mut listeners = expected_routes()
+                    .into_iter()
+                    .map(|(output, expected)| (fctx.get_async_listener(output, "killer").unwrap(), expected))
+                    .collect::<Vec<_>>();
+                let mut broadcast = fctx.get_async_broadcast("orig", "source").unwrap();
+                broadcast.broadcast(expected().lazy()).unwrap();
+                for (listener, expected) in listeners.iter_mut() {
+                    let update = listener.listen().await.unwrap();
+                    let actual = update.frame.read().unwrap().clone().collect().unwrap();
+                    assert_eq!(&actual, expected);
+                }
+                broadcast.kill().unwrap();
+            };
+            tokio::join!(lhandle(), thandle());
+        });
+    }
+
+    #[test]
+    fn invalid_root_transform_routes() {
+        let ctx = DefaultPipelineContext::new();
+        let context = serde_yaml_ng::Mapping::new();
+        let invalid = [
+            // otherwise without routes
+            "{ label: a, input: in, output: out, steps: [], otherwise: rest }",
+            // duplicate output
+            "{ label: a, input: in, output: out, steps: [], routes: [{ output: out, when: { a: { is_null: true } } }] }",
+            // unresolved symbol in a predicate
+            "{ label: a, input: in, output: out, steps: [], routes: [{ output: b, when: { a: { eq: $x } } }] }",
+        ];
+        for config in invalid {
+            let config: RootTransformConfig = serde_yaml_ng::from_str(config).unwrap();
+            assert!(config.parse(&ctx, &context).is_err());
+        }
+    }
 }
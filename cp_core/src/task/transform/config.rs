@@ -1,12 +1,18 @@
 use std::collections::HashMap;
 
-use serde::Deserialize;
+use serde::{Deserialize, Deserializer, de, de::DeserializeOwned};
+use serde_yaml_ng::Mapping;
 
-use crate::parser::{
-    dtype::DType,
-    filter_fields::FilterFields,
-    jtype::JType,
-    keyword::{PolarsExprKeyword, StrKeyword},
+use crate::{
+    model::common::ModelFields,
+    parser::{
+        dtype::DType,
+        filter::FilterStmt,
+        filter_fields::FilterFields,
+        jtype::JType,
+        keyword::{IntKeyword, PolarsExprKeyword, StrKeyword},
+        model::ConstraintPolicy,
+    },
 };
 
 #[derive(Debug, Clone, PartialEq, Eq, Deserialize)]
@@ -15,6 +21,21 @@ pub struct RootTransformConfig {
     pub input: StrKeyword,
     pub output: StrKeyword,
     pub steps: Vec<serde_yaml_ng::Value>,
+    // model name that the output is checked against with the constraint_policy
+    pub model: Option<StrKeyword>,
+    pub constraint_policy: Option<ConstraintPolicy>,
+    /// further outputs, each receiving the rows of `output` matching its predicate
+    pub routes: Option<Vec<RouteConfig>>,
+    /// output receiving the rows matching none of the routes
+    pub otherwise: Option<StrKeyword>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct RouteConfig {
+    pub output: StrKeyword,
+    /// same grammar as the `filter` step
+    pub when: FilterStmt,
 }
 
 #[derive(Debug, Clone, PartialEq, Eq, Deserialize)]
@@ -42,6 +63,41 @@ pub struct _JoinTransformConfig {
     pub right_prefix: Option<StrKeyword>,
     pub right_on: Vec<StrKeyword>,
     pub how: JType,
+    /// options of `how: asof`
+    pub asof: Option<AsofJoinConfig>,
+    /// checks the uniqueness of the join keys
+    pub validate: Option<JoinCardinality>,
+    /// suffix of the right columns clashing with the left ones, `_right` by default
+    pub suffix: Option<StrKeyword>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct AsofJoinConfig {
+    /// `backward` by default
+    pub strategy: Option<AsofJoinStrategy>,
+    /// a number, or a duration such as `5m` for temporal keys
+    pub tolerance: Option<serde_yaml_ng::Value>,
+    /// columns of both frames matched exactly before the nearest key
+    pub by: Option<Vec<StrKeyword>>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum AsofJoinStrategy {
+    Backward,
+    Forward,
+    Nearest,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize)]
+pub enum JoinCardinality {
+    #[serde(rename = "1:1")]
+    OneToOne,
+    #[serde(rename = "1:m")]
+    OneToMany,
+    #[serde(rename = "m:1")]
+    ManyToOne,
 }
 
 #[derive(Debug, Clone, PartialEq, Eq, Deserialize)]
@@ -49,6 +105,292 @@ pub struct DropTransformConfig {
     pub drop: Vec<PolarsExprKeyword>,
 }
 
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize)]
+pub struct FilterTransformConfig {
+    pub filter: FilterStmt,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum SortOrder {
+    Asc,
+    Desc,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum NullsOrder {
+    First,
+    Last,
+}
+
+/// Sort key, either the column name alone or `{ col, order: asc|desc, nulls: first|last }`
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SortKey {
+    pub col: StrKeyword,
+    pub order: Option<SortOrder>,
+    pub nulls: Option<NullsOrder>,
+}
+
+impl<'de> Deserialize<'de> for SortKey {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: serde::Deserializer<'de>,
+    {
+        #[derive(Deserialize)]
+        #[serde(deny_unknown_fields)]
+        struct Full {
+            col: StrKeyword,
+            order: Option<SortOrder>,
+            nulls: Option<NullsOrder>,
+        }
+        #[derive(Deserialize)]
+        #[serde(untagged)]
+        enum Helper {
+            Short(StrKeyword),
+            Full(Full),
+        }
+        Ok(match Helper::deserialize(deserializer)? {
+            Helper::Short(col) => SortKey {
+                col,
+                order: None,
+                nulls: None,
+            },
+            Helper::Full(Full { col, order, nulls }) => SortKey { col, order, nulls },
+        })
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize)]
+pub struct SortTransformConfig {
+    pub sort: Vec<SortKey>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize)]
+pub struct LimitTransformConfig {
+    pub limit: IntKeyword,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize)]
+pub struct OffsetTransformConfig {
+    pub offset: IntKeyword,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize)]
+pub struct DistinctTransformConfig {
+    pub distinct: _DistinctTransformConfig,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct _DistinctTransformConfig {
+    pub subset: Option<Vec<StrKeyword>>,
+    /// one of `first`, `last` or `any` (default)
+    pub keep: Option<StrKeyword>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum AggFunc {
+    Sum,
+    Mean,
+    Min,
+    Max,
+    Count,
+    NUnique,
+    First,
+    Last,
+    List,
+}
+
+/// Deserializes a map into its entries, keeping the order of the config.
+fn deserialize_ordered<'de, D, K, V>(deserializer: D) -> Result<Vec<(K, V)>, D::Error>
+where
+    D: Deserializer<'de>,
+    K: DeserializeOwned,
+    V: DeserializeOwned,
+{
+    Mapping::deserialize(deserializer)?
+        .into_iter()
+        .map(|(k, v)| {
+            let k = serde_yaml_ng::from_value(k).map_err(de::Error::custom)?;
+            let v = serde_yaml_ng::from_value(v).map_err(de::Error::custom)?;
+            Ok((k, v))
+        })
+        .collect()
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize)]
+pub struct GroupByTransformConfig {
+    pub group_by: _GroupByTransformConfig,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct _GroupByTransformConfig {
+    pub by: Vec<PolarsExprKeyword>,
+    /// output column to a single `{ agg_func: expr }`, in config order
+    #[serde(deserialize_with = "deserialize_ordered")]
+    pub agg: Vec<(StrKeyword, HashMap<AggFunc, PolarsExprKeyword>)>,
+    pub maintain_order: Option<bool>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum WindowFunc {
+    Rank,
+    DenseRank,
+    RowNumber,
+    CumSum,
+    CumCount,
+    Shift,
+    Diff,
+    RollingMean,
+    RollingSum,
+    RollingMin,
+    RollingMax,
+}
+
+/// Rolling window size, either a number of rows or a duration such as `7d` over a temporal column
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize)]
+#[serde(untagged)]
+pub enum WindowSize {
+    Rows(usize),
+    Duration(String),
+}
+
+/// Window function argument, either the column alone or `{ col, n, descending, window, by, min_periods }`
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+pub struct WindowArgs {
+    pub col: Option<PolarsExprKeyword>,
+    /// periods of `shift` and `diff`, 1 by default
+    pub n: Option<i64>,
+    /// ranks the largest values first
+    pub descending: Option<bool>,
+    pub window: Option<WindowSize>,
+    /// temporal column of a duration window, the first `order_by` column by default
+    pub by: Option<StrKeyword>,
+    pub min_periods: Option<usize>,
+}
+
+impl<'de> Deserialize<'de> for WindowArgs {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: serde::Deserializer<'de>,
+    {
+        #[derive(Deserialize)]
+        #[serde(deny_unknown_fields)]
+        struct Full {
+            col: Option<PolarsExprKeyword>,
+            n: Option<i64>,
+            descending: Option<bool>,
+            window: Option<WindowSize>,
+            by: Option<StrKeyword>,
+            min_periods: Option<usize>,
+        }
+        // full form first, as maps are also valid polars expressions
+        #[derive(Deserialize)]
+        #[serde(untagged)]
+        enum Helper {
+            Full(Full),
+            Short(PolarsExprKeyword),
+        }
+        Ok(match Helper::deserialize(deserializer)? {
+            Helper::Short(col) => WindowArgs {
+                col: Some(col),
+                ..Default::default()
+            },
+            Helper::Full(Full {
+                col,
+                n,
+                descending,
+                window,
+                by,
+                min_periods,
+            }) => WindowArgs {
+                col,
+                n,
+                descending,
+                window,
+                by,
+                min_periods,
+            },
+        })
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize)]
+pub struct WindowTransformConfig {
+    pub window: _WindowTransformConfig,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct _WindowTransformConfig {
+    pub partition_by: Option<Vec<StrKeyword>>,
+    pub order_by: Option<Vec<SortKey>>,
+    /// output column to a single `{ window_func: args }`, in config order
+    #[serde(deserialize_with = "deserialize_ordered")]
+    pub columns: Vec<(StrKeyword, HashMap<WindowFunc, WindowArgs>)>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize)]
+pub struct PivotTransformConfig {
+    pub pivot: _PivotTransformConfig,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct _PivotTransformConfig {
+    pub index: Vec<StrKeyword>,
+    /// column whose values become the output columns
+    pub on: StrKeyword,
+    pub values: StrKeyword,
+    /// one of the `group_by` aggregations, `first` by default
+    pub agg: Option<AggFunc>,
+    // model declaring the output columns ahead of time, takes precedence over model_fields
+    pub model: Option<StrKeyword>,
+    pub model_fields: Option<ModelFields>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize)]
+pub struct UnpivotTransformConfig {
+    pub unpivot: _UnpivotTransformConfig,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct _UnpivotTransformConfig {
+    pub index: Vec<StrKeyword>,
+    /// columns to unpivot, all columns outside of `index` by default
+    pub on: Option<Vec<StrKeyword>>,
+    pub variable_name: Option<StrKeyword>,
+    pub value_name: Option<StrKeyword>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize)]
+pub struct UnionTransformConfig {
+    pub union: _UnionTransformConfig,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct _UnionTransformConfig {
+    /// results appended below the main frame, in order
+    pub frames: Vec<StrKeyword>,
+    /// align columns by name rather than by position, `true` by default
+    pub by_name: Option<bool>,
+    /// fill the columns absent from a frame with nulls, requires `by_name`
+    pub fill_missing: Option<bool>,
+    /// cast each column to the supertype of its dtypes across frames
+    pub supercast: Option<bool>,
+    /// column tagging each row with the result it came from, the root transform's input for the main frame
+    pub source_column: Option<StrKeyword>,
+    /// input of the root transform, set when it is parsed
+    #[serde(skip)]
+    pub input: Option<String>,
+}
+
 #[derive(Debug, Clone, PartialEq, Eq, Deserialize)]
 pub struct SqlTransformConfig {
     pub sql: String,
@@ -154,7 +496,10 @@ join:
                     left_prefix: None,
                     right_prefix: None,
                     right_select: None,
-                    how: JType(JoinType::Left)
+                    how: JType(JoinType::Left),
+                    asof: None,
+                    validate: None,
+                    suffix: None
                 }
             }
         );
@@ -186,7 +531,10 @@ join:
                     left_prefix: None,
                     right_prefix: None,
                     right_select: None,
-                    how: JType(JoinType::Right)
+                    how: JType(JoinType::Right),
+                    asof: None,
+                    validate: None,
+                    suffix: None
                 }
             }
         );
@@ -214,7 +562,10 @@ join:
                     left_prefix: Some(StrKeyword::with_value("orig".to_owned())),
                     right_prefix: Some(StrKeyword::with_symbol("orig")),
                     right_select: None,
-                    how: JType(JoinType::Inner)
+                    how: JType(JoinType::Inner),
+                    asof: None,
+                    validate: None,
+                    suffix: None
                 }
             }
         );
@@ -252,7 +603,10 @@ join:
                             PolarsExprKeyword::with_value(col("two"))
                         ),
                     ])),
-                    how: JType(JoinType::Full)
+                    how: JType(JoinType::Full),
+                    asof: None,
+                    validate: None,
+                    suffix: None
                 }
             }
         );
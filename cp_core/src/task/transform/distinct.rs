@@ -0,0 +1,135 @@
+use crate::parser::keyword::Keyword;
+use crate::pipeline::context::DefaultPipelineContext;
+use crate::task::transform::common::{Transform, TransformConfig};
+use crate::task::transform::config::DistinctTransformConfig;
+use crate::util::error::{CpError, CpResult};
+use crate::valid_or_insert_error;
+use polars::prelude::{LazyFrame, PlSmallStr, UniqueKeepStrategy};
+use serde_yaml_ng::Mapping;
+use std::sync::Arc;
+
+pub struct DistinctTransform {
+    subset: Option<Vec<PlSmallStr>>,
+    keep: UniqueKeepStrategy,
+}
+
+impl Transform for DistinctTransform {
+    fn run(&self, main: LazyFrame, _ctx: Arc<DefaultPipelineContext>) -> CpResult<LazyFrame> {
+        Ok(main.unique_stable(self.subset.clone(), self.keep))
+    }
+}
+
+fn parse_keep_strategy(keep: &str) -> Option<UniqueKeepStrategy> {
+    match keep {
+        "first" => Some(UniqueKeepStrategy::First),
+        "last" => Some(UniqueKeepStrategy::Last),
+        "any" => Some(UniqueKeepStrategy::Any),
+        _ => None,
+    }
+}
+
+impl TransformConfig for DistinctTransformConfig {
+    fn emplace(&mut self, context: &Mapping) -> CpResult<()> {
+        if let Some(subset) = self.distinct.subset.as_mut() {
+            for col in subset.iter_mut() {
+                col.insert_value_from_context(context)?;
+            }
+        }
+        if let Some(keep) = self.distinct.keep.as_mut() {
+            keep.insert_value_from_context(context)?;
+        }
+        Ok(())
+    }
+
+    fn validate(&self) -> Vec<CpError> {
+        let mut errors = vec![];
+        if let Some(subset) = &self.distinct.subset {
+            for col in subset {
+                valid_or_insert_error!(errors, col, "transform[distinct].subset");
+            }
+        }
+        if let Some(keep) = &self.distinct.keep {
+            valid_or_insert_error!(errors, keep, "transform[distinct].keep");
+            if let Some(value) = keep.value()
+                && parse_keep_strategy(value).is_none()
+            {
+                errors.push(CpError::ConfigValidationError(
+                    "transform[distinct].keep",
+                    format!("`{}` is not one of first, last, any", value),
+                ));
+            }
+        }
+        errors
+    }
+
+    fn transform(&self) -> Box<dyn Transform> {
+        let subset = self.distinct.subset.as_ref().map(|subset| {
+            subset
+                .iter()
+                .map(|col| PlSmallStr::from_str(col.value().expect("transform[distinct].subset")))
+                .collect()
+        });
+        let keep = self
+            .distinct
+            .keep
+            .as_ref()
+            .map(|keep| parse_keep_strategy(keep.value().expect("transform[distinct].keep")).expect("keep"))
+            .unwrap_or(UniqueKeepStrategy::Any);
+        Box::new(DistinctTransform { subset, keep })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::pipeline::context::DefaultPipelineContext;
+    use crate::task::transform::common::TransformConfig;
+    use crate::task::transform::config::DistinctTransformConfig;
+    use crate::util::common::yaml_from_str;
+    use crate::util::test::{DummyData, assert_frame_equal};
+    use polars::df;
+    use std::sync::Arc;
+
+    fn distinct(config: &str) -> polars::frame::DataFrame {
+        let mut config: DistinctTransformConfig = serde_yaml_ng::from_str(config).unwrap();
+        let context = yaml_from_str("key: csid\nkeep: last").unwrap();
+        config.emplace(context.as_mapping().unwrap()).unwrap();
+        assert!(config.validate().is_empty());
+        let ctx = Arc::new(DefaultPipelineContext::new());
+        config
+            .transform()
+            .run(DummyData::player_scores(), ctx)
+            .unwrap()
+            .collect()
+            .unwrap()
+    }
+
+    #[test]
+    fn valid_distinct_transform_subset_keep() {
+        let expected = df![
+            "csid" => [82938842, 86543102, 8872631],
+            "game" => [1, 1, 1],
+            "scores" => [20, 43, 19],
+        ]
+        .unwrap();
+        assert_eq!(distinct("distinct: { subset: [$key], keep: first }"), expected);
+        let expected = df![
+            "csid" => [82938842, 86543102, 8872631],
+            "game" => [3, 3, 1],
+            "scores" => [-7, 12, 19],
+        ]
+        .unwrap();
+        assert_frame_equal(distinct("distinct: { subset: [$key], keep: $keep }"), expected);
+    }
+
+    #[test]
+    fn valid_distinct_transform_all_columns() {
+        let actual = distinct("distinct: {}");
+        assert_eq!(actual.height(), 7);
+    }
+
+    #[test]
+    fn invalid_distinct_transform_keep() {
+        let config: DistinctTransformConfig = serde_yaml_ng::from_str("distinct: { keep: none }").unwrap();
+        assert_eq!(config.validate().len(), 1);
+    }
+}
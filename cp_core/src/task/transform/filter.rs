@@ -0,0 +1,124 @@
+use crate::pipeline::context::DefaultPipelineContext;
+use crate::task::transform::common::{Transform, TransformConfig};
+use crate::task::transform::config::FilterTransformConfig;
+use crate::util::error::{CpError, CpResult};
+use polars::prelude::{Expr, LazyFrame};
+use serde_yaml_ng::Mapping;
+use std::sync::Arc;
+
+pub struct FilterTransform {
+    filter: Expr,
+}
+
+impl Transform for FilterTransform {
+    fn run(&self, main: LazyFrame, _ctx: Arc<DefaultPipelineContext>) -> CpResult<LazyFrame> {
+        Ok(main.filter(self.filter.clone()))
+    }
+}
+
+impl TransformConfig for FilterTransformConfig {
+    fn emplace(&mut self, context: &Mapping) -> CpResult<()> {
+        self.filter.emplace(context)
+    }
+
+    fn validate(&self) -> Vec<CpError> {
+        let mut errors = vec![];
+        self.filter.validate(&mut errors);
+        errors
+    }
+
+    fn transform(&self) -> Box<dyn Transform> {
+        Box::new(FilterTransform {
+            filter: self.filter.expr(),
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::pipeline::context::DefaultPipelineContext;
+    use crate::task::transform::common::TransformConfig;
+    use crate::task::transform::config::FilterTransformConfig;
+    use crate::util::common::yaml_from_str;
+    use crate::util::test::{DummyData, assert_frame_equal};
+    use polars::df;
+    use polars::prelude::IntoLazy;
+    use std::sync::Arc;
+
+    fn filter(config: &str, context: &str) -> polars::frame::DataFrame {
+        let mut config: FilterTransformConfig = serde_yaml_ng::from_str(config).unwrap();
+        let context = yaml_from_str(context).unwrap();
+        config.emplace(context.as_mapping().unwrap()).unwrap();
+        assert!(config.validate().is_empty());
+        let ctx = Arc::new(DefaultPipelineContext::new());
+        let main = DummyData::df_instruments().lazy();
+        config.transform().run(main, ctx).unwrap().collect().unwrap()
+    }
+
+    #[test]
+    fn valid_filter_transform_compare_symbol() {
+        let actual = filter(
+            "
+filter:
+    mkt: { eq: $market }
+    id: { gte: $min_id }
+",
+            "
+market: { str: amer }
+min_id: { int32: 1 }
+",
+        );
+        let expected = df![
+            "ric" => ["AMZN", "GOOG", "NVDA"],
+            "mkt" => ["amer", "amer", "amer"],
+            "id" => [1, 2, 3],
+        ]
+        .unwrap();
+        assert_frame_equal(actual, expected);
+    }
+
+    #[test]
+    fn valid_filter_transform_nested() {
+        let actual = filter(
+            "
+filter:
+    or:
+        - mkt: { in: [{ str: apac }] }
+        - and:
+            - id: { between: [{ int32: 0 }, { int32: 6 }] }
+            - not:
+                ric: { regex: '^(A|N)' }
+            - ric: { is_null: false }
+",
+            "{}",
+        );
+        let expected = df![
+            "ric" => ["GOOG", "BABA", "SPOT"],
+            "mkt" => ["amer", "apac", "emea"],
+            "id" => [2, 5, 6],
+        ]
+        .unwrap();
+        assert_frame_equal(actual, expected);
+    }
+
+    #[test]
+    fn valid_filter_transform_in() {
+        let actual = filter(
+            "filter: { mkt: { in: [{ str: apac }, $market] } }",
+            "market: { str: emea }",
+        );
+        let expected = df![
+            "ric" => ["NOVA", "BABA", "SPOT"],
+            "mkt" => ["emea", "apac", "emea"],
+            "id" => [4, 5, 6],
+        ]
+        .unwrap();
+        assert_frame_equal(actual, expected);
+    }
+
+    #[test]
+    fn invalid_filter_transform_missing_symbol() {
+        let config: FilterTransformConfig = serde_yaml_ng::from_str("filter: { $col: { gt: $value } }").unwrap();
+        assert_eq!(config.validate().len(), 2);
+    }
+}
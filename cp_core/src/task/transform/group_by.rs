@@ -0,0 +1,225 @@
+use std::{collections::HashMap, sync::Arc};
+
+use polars::prelude::{Expr, LazyFrame};
+
+use crate::{
+    parser::keyword::Keyword,
+    pipeline::context::DefaultPipelineContext,
+    util::error::{CpError, CpResult},
+    valid_or_insert_error,
+};
+
+use super::{
+    common::{Transform, TransformConfig},
+    config::{AggFunc, GroupByTransformConfig},
+};
+
+pub struct GroupByTransform {
+    by: Vec<Expr>,
+    agg: Vec<Expr>,
+    maintain_order: bool,
+}
+
+impl Transform for GroupByTransform {
+    fn run(&self, main: LazyFrame, _ctx: Arc<DefaultPipelineContext>) -> CpResult<LazyFrame> {
+        let grouped = if self.maintain_order {
+            main.group_by_stable(&self.by)
+        } else {
+            main.group_by(&self.by)
+        };
+        Ok(grouped.agg(&self.agg))
+    }
+}
+
+pub fn agg_expr(func: AggFunc, expr: Expr) -> Expr {
+    match func {
+        AggFunc::Sum => expr.sum(),
+        AggFunc::Mean => expr.mean(),
+        AggFunc::Min => expr.min(),
+        AggFunc::Max => expr.max(),
+        AggFunc::Count => expr.count(),
+        AggFunc::NUnique => expr.n_unique(),
+        AggFunc::First => expr.first(),
+        AggFunc::Last => expr.last(),
+        // an unaggregated column is collected into a list per group
+        AggFunc::List => expr,
+    }
+}
+
+impl TransformConfig for GroupByTransformConfig {
+    fn emplace(&mut self, context: &serde_yaml_ng::Mapping) -> CpResult<()> {
+        for expr_kw in self.group_by.by.iter_mut() {
+            expr_kw.insert_value_from_context(context)?;
+        }
+        let mut agg = vec![];
+        for (alias_kw, action) in &self.group_by.agg {
+            let mut alias = alias_kw.clone();
+            alias.insert_value_from_context(context)?;
+            let mut new_action = HashMap::new();
+            for (func, expr_kw) in action {
+                let mut expr = expr_kw.clone();
+                expr.insert_value_from_context(context)?;
+                new_action.insert(*func, expr);
+            }
+            agg.push((alias, new_action));
+        }
+        self.group_by.agg = agg;
+        Ok(())
+    }
+
+    fn validate(&self) -> Vec<CpError> {
+        let mut errors = vec![];
+        if self.group_by.by.is_empty() {
+            errors.push(CpError::ConfigValidationError(
+                "transform[group_by].by",
+                "no group keys provided".to_owned(),
+            ));
+        }
+        for expr_kw in &self.group_by.by {
+            valid_or_insert_error!(errors, expr_kw, "transform[group_by].by");
+        }
+        for (alias_kw, action) in &self.group_by.agg {
+            valid_or_insert_error!(errors, alias_kw, "transform[group_by].agg");
+            if action.len() != 1 {
+                errors.push(CpError::ConfigValidationError(
+                    "transform[group_by].agg",
+                    format!(
+                        "`{}` must have exactly one aggregation, found: {:?}",
+                        alias_kw.value().map_or("?", |x| x.as_str()),
+                        action.keys().collect::<Vec<_>>()
+                    ),
+                ));
+            }
+            for expr_kw in action.values() {
+                valid_or_insert_error!(errors, expr_kw, "transform[group_by].agg");
+            }
+        }
+        errors
+    }
+
+    fn transform(&self) -> Box<dyn Transform> {
+        let by = self
+            .group_by
+            .by
+            .iter()
+            .map(|x| x.value().expect("transform[group_by].by").clone())
+            .collect();
+        let mut agg = vec![];
+        for (alias_kw, action) in &self.group_by.agg {
+            let alias = alias_kw.value().expect("alias").clone();
+            let (func, expr_kw) = action.iter().next().expect("agg");
+            let expr = expr_kw.value().expect("expr").clone();
+            agg.push(agg_expr(*func, expr).alias(alias));
+        }
+        Box::new(GroupByTransform {
+            by,
+            agg,
+            maintain_order: self.group_by.maintain_order.unwrap_or(false),
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Arc;
+
+    use polars::prelude::*;
+
+    use crate::{
+        pipeline::context::DefaultPipelineContext,
+        task::transform::{common::TransformConfig, config::GroupByTransformConfig},
+        util::{
+            common::yaml_from_str,
+            test::{DummyData, assert_frame_equal},
+        },
+    };
+
+    #[test]
+    fn valid_group_by_transform_basic() {
+        let config = "
+group_by:
+    by: [$key]
+    agg:
+        total: { sum: scores }
+        avg: { mean: scores }
+        best: { max: scores }
+        worst: { min: $value }
+        games: { count: game }
+        distinct_games: { n_unique: game }
+        first_game: { first: game }
+        last_game: { last: game }
+    maintain_order: true
+";
+        let mut config: GroupByTransformConfig = serde_yaml_ng::from_str(config).unwrap();
+        let context = yaml_from_str("key: csid\nvalue: scores").unwrap();
+        config.emplace(context.as_mapping().unwrap()).unwrap();
+        assert!(config.validate().is_empty());
+        let ctx = Arc::new(DefaultPipelineContext::new());
+        let actual = config
+            .transform()
+            .run(DummyData::player_scores(), ctx)
+            .unwrap()
+            .collect()
+            .unwrap();
+        let expected = df![
+            "csid" => [82938842, 86543102, 8872631],
+            "total" => [16, 105, 19],
+            "avg" => [16.0 / 3.0, 35.0, 19.0],
+            "best" => [20, 50, 19],
+            "worst" => [-7, 12, 19],
+            "games" => [3u32, 3, 1],
+            "distinct_games" => [3u32, 3, 1],
+            "first_game" => [1, 1, 1],
+            "last_game" => [3, 3, 1],
+        ]
+        .unwrap();
+        // output columns follow the group keys in config order
+        assert_eq!(actual.get_column_names(), expected.get_column_names());
+        assert_frame_equal(actual, expected);
+    }
+
+    #[test]
+    fn valid_group_by_transform_list() {
+        let config = "
+group_by:
+    by: [csid]
+    agg:
+        games: { list: game }
+    maintain_order: true
+";
+        let config: GroupByTransformConfig = serde_yaml_ng::from_str(config).unwrap();
+        let ctx = Arc::new(DefaultPipelineContext::new());
+        let actual = config
+            .transform()
+            .run(DummyData::player_scores(), ctx)
+            .unwrap()
+            .collect()
+            .unwrap();
+        let games = actual.column("games").unwrap().list().unwrap();
+        assert_eq!(
+            actual.column("csid").unwrap().i32().unwrap().to_vec(),
+            vec![Some(82938842), Some(86543102), Some(8872631)]
+        );
+        assert_eq!(
+            games.get_as_series(0).unwrap().i32().unwrap().to_vec(),
+            vec![Some(1), Some(2), Some(3)]
+        );
+        assert_eq!(games.get_as_series(2).unwrap().i32().unwrap().to_vec(), vec![Some(1)]);
+    }
+
+    #[test]
+    fn invalid_group_by_transform() {
+        let config = "
+group_by:
+    by: [$key]
+    agg:
+        total: { sum: scores, mean: scores }
+";
+        let config: GroupByTransformConfig = serde_yaml_ng::from_str(config).unwrap();
+        assert_eq!(config.validate().len(), 2);
+        assert!(
+            serde_yaml_ng::from_str::<GroupByTransformConfig>("group_by: { by: [a], agg: { x: { median: b } } }")
+                .is_err()
+        );
+    }
+}
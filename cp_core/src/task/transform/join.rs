@@ -2,7 +2,10 @@ use std::sync::Arc;
 
 use polars::{
     frame::DataFrame,
-    prelude::{Expr, IntoLazy, JoinArgs, LazyFrame, Null, all, coalesce, col, lit},
+    prelude::{
+        AnyValue, AsOfOptions, AsofStrategy, Expr, IntoLazy, JoinArgs, JoinType, JoinValidation, LazyFrame, Null,
+        SortMultipleOptions, all, coalesce, col, lit,
+    },
 };
 
 use crate::{
@@ -14,9 +17,12 @@ use crate::{
 
 use super::{
     common::{Transform, TransformConfig},
-    config::JoinTransformConfig,
+    config::{AsofJoinConfig, AsofJoinStrategy, JoinCardinality, JoinTransformConfig},
 };
 
+/// Original row position of the main frame, restored after sorting it for an asof join
+const ROW_INDEX: &str = "__join_row";
+
 pub struct JoinTransform {
     right_label: String,
     left_prefix: Option<Vec<Expr>>,
@@ -54,8 +60,18 @@ impl Transform for JoinTransform {
             .right_prefix
             .as_ref()
             .map_or_else(|| right_join.clone(), |x| right_join.clone().with_columns(x));
-        let joined = left.join(right, &self.left_on, &self.right_on, self.join_args.clone());
-        Ok(joined)
+        if !matches!(self.join_args.how, JoinType::AsOf(_)) {
+            return Ok(left.join(right, &self.left_on, &self.right_on, self.join_args.clone()));
+        }
+        // asof joins need both frames sorted by their key
+        let left = left
+            .with_row_index(ROW_INDEX, None)
+            .sort_by_exprs(&self.left_on, SortMultipleOptions::default());
+        let right = right.sort_by_exprs(&self.right_on, SortMultipleOptions::default());
+        Ok(left
+            .join(right, &self.left_on, &self.right_on, self.join_args.clone())
+            .sort([ROW_INDEX], SortMultipleOptions::default())
+            .drop([ROW_INDEX]))
     }
 }
 
@@ -78,6 +94,12 @@ impl TransformConfig for JoinTransformConfig {
             on.insert_value_from_context(context)?;
         }
         self.join.right.insert_value_from_context(context)?;
+        if let Some(suffix) = self.join.suffix.as_mut() {
+            suffix.insert_value_from_context(context)?;
+        }
+        for by in self.join.asof.iter_mut().flat_map(|x| x.by.iter_mut().flatten()) {
+            by.insert_value_from_context(context)?;
+        }
         Ok(())
     }
     fn validate(&self) -> Vec<CpError> {
@@ -99,6 +121,44 @@ impl TransformConfig for JoinTransformConfig {
         }
 
         valid_or_insert_error!(errors, self.join.right, "transform[join].right");
+
+        if let Some(suffix) = self.join.suffix.as_ref() {
+            valid_or_insert_error!(errors, suffix, "transform[join].suffix");
+        }
+
+        let is_asof = matches!(self.join.how.0, JoinType::AsOf(_));
+        if is_asof && (self.join.left_on.len() != 1 || self.join.right_on.len() != 1) {
+            errors.push(CpError::ConfigValidationError(
+                "transform[join].how",
+                "asof joins take exactly one left_on and right_on key".to_owned(),
+            ));
+        }
+        if let Some(asof) = self.join.asof.as_ref() {
+            if !is_asof {
+                errors.push(CpError::ConfigValidationError(
+                    "transform[join].asof",
+                    "asof options require `how: asof`".to_owned(),
+                ));
+            }
+            for by in asof.by.iter().flatten() {
+                valid_or_insert_error!(errors, by, "transform[join].asof.by");
+            }
+            if let Some(tolerance) = asof.tolerance.as_ref()
+                && !(tolerance.is_i64() || tolerance.is_f64() || tolerance.is_string())
+            {
+                errors.push(CpError::ConfigValidationError(
+                    "transform[join].asof.tolerance",
+                    format!("expected a number or a duration, got {:?}", tolerance),
+                ));
+            }
+        }
+        if self.join.validate.is_some() && !matches!(self.join.how.0, JoinType::Inner | JoinType::Left | JoinType::Full)
+        {
+            errors.push(CpError::ConfigValidationError(
+                "transform[join].validate",
+                "only inner, left and full joins can be validated".to_owned(),
+            ));
+        }
         errors
     }
 
@@ -140,7 +200,23 @@ impl TransformConfig for JoinTransformConfig {
             .map(|x| col(x.value().expect("right_on")))
             .collect::<Vec<_>>();
 
-        let join_args = JoinArgs::new(self.join.how.clone().into());
+        let how = match (self.join.how.clone().into(), self.join.asof.as_ref()) {
+            (JoinType::AsOf(_), Some(asof)) => JoinType::AsOf(asof_options(asof)),
+            (how, _) => how,
+        };
+        let mut join_args = JoinArgs::new(how);
+        if let Some(cardinality) = self.join.validate {
+            join_args.validation = match cardinality {
+                JoinCardinality::OneToOne => JoinValidation::OneToOne,
+                JoinCardinality::OneToMany => JoinValidation::OneToMany,
+                JoinCardinality::ManyToOne => JoinValidation::ManyToOne,
+            };
+        }
+        join_args.suffix = self
+            .join
+            .suffix
+            .as_ref()
+            .map(|x| x.value().expect("suffix").as_str().into());
 
         let join = JoinTransform {
             right_on,
@@ -155,6 +231,30 @@ impl TransformConfig for JoinTransformConfig {
     }
 }
 
+fn asof_options(asof: &AsofJoinConfig) -> AsOfOptions {
+    let by = asof.by.as_ref().map(|keys| {
+        keys.iter()
+            .map(|x| x.value().expect("asof.by").as_str().into())
+            .collect::<Vec<_>>()
+    });
+    let tolerance = asof.tolerance.as_ref();
+    AsOfOptions {
+        strategy: match asof.strategy.unwrap_or(AsofJoinStrategy::Backward) {
+            AsofJoinStrategy::Backward => AsofStrategy::Backward,
+            AsofJoinStrategy::Forward => AsofStrategy::Forward,
+            AsofJoinStrategy::Nearest => AsofStrategy::Nearest,
+        },
+        tolerance: tolerance.and_then(|x| match (x.as_i64(), x.as_f64()) {
+            (Some(i), _) => Some(AnyValue::Int64(i)),
+            (None, Some(f)) => Some(AnyValue::Float64(f)),
+            _ => None,
+        }),
+        tolerance_str: tolerance.and_then(|x| x.as_str()).map(|x| x.into()),
+        left_by: by.clone(),
+        right_by: by,
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use crate::parser::jtype::JType;
@@ -162,9 +262,10 @@ mod tests {
     use crate::pipeline::context::{DefaultPipelineContext, PipelineContext};
     use crate::task::transform::common::TransformConfig;
     use crate::task::transform::config::{_JoinTransformConfig, JoinTransformConfig};
-    use crate::util::test::assert_frame_equal;
+    use crate::util::common::yaml_from_str;
+    use crate::util::test::{DummyData, assert_frame_equal};
     use polars::df;
-    use polars::prelude::{IntoLazy, JoinType, col};
+    use polars::prelude::{DataFrame, IntoLazy, JoinType, PolarsResult, col, polars_bail};
     use std::collections::HashMap;
     use std::sync::Arc;
 
@@ -233,6 +334,9 @@ mod tests {
                         ),
                     ])),
                     how: JType(join_type),
+                    asof: None,
+                    validate: None,
+                    suffix: None,
                 },
             };
 
@@ -279,9 +383,149 @@ mod tests {
                     ),
                 ])),
                 how: JType(JoinType::Left),
+                asof: None,
+                validate: None,
+                suffix: None,
             },
         };
 
         assert_eq!(config.validate().len(), 2);
     }
+
+    fn run_join(config: &str, right: DataFrame, main: DataFrame) -> PolarsResult<DataFrame> {
+        let mut config: JoinTransformConfig = serde_yaml_ng::from_str(config).unwrap();
+        let context = yaml_from_str("right: RIGHT\nkey: team").unwrap();
+        config.emplace(context.as_mapping().unwrap()).unwrap();
+        assert!(config.validate().is_empty());
+        let ctx = Arc::new(DefaultPipelineContext::with_results(&["RIGHT"], 1));
+        ctx.insert_result("RIGHT", right.lazy()).unwrap();
+        match config.transform().run(main.lazy(), ctx) {
+            Ok(x) => x.collect(),
+            Err(e) => polars_bail!(ComputeError: "{}", e),
+        }
+    }
+
+    #[test]
+    fn valid_join_transform_asof() {
+        let roster = df!(
+            "team"      => ["a", "a", "b"],
+            "at"        => [0i64, 10, 10],
+            "roster"    => [1, 2, 3]
+        )
+        .unwrap();
+        let events = df!(
+            "team"      => ["a", "b", "a", "b"],
+            "t"         => [12i64, 20, 4, 3],
+        )
+        .unwrap();
+        let config = "
+join:
+    right: $right
+    left_on: [t]
+    right_on: [at]
+    how: asof
+    asof:
+        by: [$key]
+        tolerance: 5
+";
+        let actual = run_join(config, roster.clone(), events.clone()).unwrap();
+        // rows keep the order of the main frame
+        let expected = df!(
+            "team"      => ["a", "b", "a", "b"],
+            "t"         => [12i64, 20, 4, 3],
+            "at"        => [Some(10i64), None, Some(0), None],
+            "roster"    => [Some(2), None, Some(1), None],
+        )
+        .unwrap();
+        assert_frame_equal(actual, expected);
+
+        let config = "
+join:
+    right: RIGHT
+    left_on: [t]
+    right_on: [at]
+    how: asof
+    asof: { by: [team], strategy: nearest }
+";
+        let actual = run_join(config, roster, events).unwrap();
+        assert_eq!(
+            actual
+                .column("roster")
+                .unwrap()
+                .i32()
+                .unwrap()
+                .into_no_null_iter()
+                .collect::<Vec<_>>(),
+            vec![2, 3, 1, 3]
+        );
+    }
+
+    #[test]
+    fn valid_join_transform_semi_anti() {
+        let right = df!( "csid" => [82938842, 8872631] ).unwrap();
+        let main = DummyData::player_scores().collect().unwrap();
+        let semi = run_join(
+            "join: { right: RIGHT, left_on: [csid], right_on: [csid], how: semi }",
+            right.clone(),
+            main.clone(),
+        )
+        .unwrap();
+        assert_eq!(semi.height(), 4);
+        assert_eq!(semi.get_column_names(), main.get_column_names());
+        let anti = run_join(
+            "join: { right: RIGHT, left_on: [csid], right_on: [csid], how: anti }",
+            right,
+            main,
+        )
+        .unwrap();
+        assert_eq!(
+            anti.column("csid")
+                .unwrap()
+                .i32()
+                .unwrap()
+                .into_no_null_iter()
+                .collect::<Vec<_>>(),
+            vec![86543102, 86543102, 86543102]
+        );
+    }
+
+    #[test]
+    fn valid_join_transform_validate_suffix() {
+        let right = df!( "csid" => [82938842, 82938842], "scores" => [1, 2] ).unwrap();
+        let main = DummyData::player_scores().collect().unwrap();
+        let config = "join: { right: RIGHT, left_on: [csid], right_on: [csid], how: left, suffix: _other }";
+        let actual = run_join(config, right.clone(), main.clone()).unwrap();
+        assert!(actual.column("scores_other").is_ok());
+        let config = "join: { right: RIGHT, left_on: [csid], right_on: [csid], how: left, validate: 'm:1' }";
+        assert!(run_join(config, right.clone(), main.clone()).is_err());
+        let config = "join: { right: RIGHT, left_on: [csid], right_on: [csid], how: left, validate: '1:m' }";
+        assert!(run_join(config, right, main.clone()).is_err());
+        // a unique right key holds for m:1
+        let right = df!( "csid" => [82938842], "bonus" => [3] ).unwrap();
+        let config = "join: { right: RIGHT, left_on: [csid], right_on: [csid], how: left, validate: 'm:1' }";
+        let actual = run_join(config, right, main.clone()).unwrap();
+        assert_eq!(actual.height(), main.height());
+        // the 3 games of 82938842 get the bonus
+        assert_eq!(actual.column("bonus").unwrap().null_count(), 4);
+    }
+
+    #[test]
+    fn invalid_join_transform_options() {
+        let invalid = [
+            "join: { right: RIGHT, left_on: [a, b], right_on: [a, b], how: asof }",
+            "join: { right: RIGHT, left_on: [a], right_on: [a], how: left, asof: { strategy: nearest } }",
+            "join: { right: RIGHT, left_on: [a], right_on: [a], how: asof, asof: { tolerance: [1] } }",
+            "join: { right: RIGHT, left_on: [a], right_on: [a], how: semi, validate: '1:1' }",
+        ];
+        for config in invalid {
+            let config: JoinTransformConfig = serde_yaml_ng::from_str(config).unwrap();
+            assert_eq!(config.validate().len(), 1);
+        }
+        assert!(
+            serde_yaml_ng::from_str::<JoinTransformConfig>(
+                "join: { right: RIGHT, left_on: [a], right_on: [a], how: left, validate: 'm:m' }"
+            )
+            .is_err()
+        );
+    }
 }
@@ -0,0 +1,135 @@
+use crate::parser::keyword::Keyword;
+use crate::pipeline::context::DefaultPipelineContext;
+use crate::task::transform::common::{Transform, TransformConfig};
+use crate::task::transform::config::{LimitTransformConfig, OffsetTransformConfig};
+use crate::util::error::{CpError, CpResult};
+use crate::valid_or_insert_error;
+use polars::prelude::{IdxSize, LazyFrame};
+use serde_yaml_ng::Mapping;
+use std::sync::Arc;
+
+/// Keeps the first `n` rows
+pub struct LimitTransform {
+    n: IdxSize,
+}
+
+/// Skips the first `n` rows, or keeps the last `-n` rows if negative
+pub struct OffsetTransform {
+    n: i64,
+}
+
+impl Transform for LimitTransform {
+    fn run(&self, main: LazyFrame, _ctx: Arc<DefaultPipelineContext>) -> CpResult<LazyFrame> {
+        Ok(main.limit(self.n))
+    }
+}
+
+impl Transform for OffsetTransform {
+    fn run(&self, main: LazyFrame, _ctx: Arc<DefaultPipelineContext>) -> CpResult<LazyFrame> {
+        Ok(main.slice(self.n, IdxSize::MAX))
+    }
+}
+
+impl TransformConfig for LimitTransformConfig {
+    fn emplace(&mut self, context: &Mapping) -> CpResult<()> {
+        self.limit.insert_value_from_context(context)
+    }
+
+    fn validate(&self) -> Vec<CpError> {
+        let mut errors = vec![];
+        valid_or_insert_error!(errors, self.limit, "transform[limit]");
+        if let Some(value) = self.limit.value()
+            && IdxSize::try_from(*value).is_err()
+        {
+            errors.push(CpError::ConfigValidationError(
+                "transform[limit]",
+                format!("`{}` is not a valid row count", value),
+            ));
+        }
+        errors
+    }
+
+    fn transform(&self) -> Box<dyn Transform> {
+        let n = *self.limit.value().expect("transform[limit]");
+        Box::new(LimitTransform {
+            n: IdxSize::try_from(n).expect("transform[limit]"),
+        })
+    }
+}
+
+impl TransformConfig for OffsetTransformConfig {
+    fn emplace(&mut self, context: &Mapping) -> CpResult<()> {
+        self.offset.insert_value_from_context(context)
+    }
+
+    fn validate(&self) -> Vec<CpError> {
+        let mut errors = vec![];
+        valid_or_insert_error!(errors, self.offset, "transform[offset]");
+        errors
+    }
+
+    fn transform(&self) -> Box<dyn Transform> {
+        Box::new(OffsetTransform {
+            n: *self.offset.value().expect("transform[offset]"),
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::pipeline::context::DefaultPipelineContext;
+    use crate::task::transform::common::TransformConfig;
+    use crate::task::transform::config::{LimitTransformConfig, OffsetTransformConfig};
+    use crate::util::common::yaml_from_str;
+    use crate::util::test::DummyData;
+    use polars::df;
+    use polars::prelude::IntoLazy;
+    use std::sync::Arc;
+
+    #[test]
+    fn valid_limit_offset_transform() {
+        let ctx = Arc::new(DefaultPipelineContext::new());
+        let context = yaml_from_str("n: 2").unwrap();
+        let mut offset: OffsetTransformConfig = serde_yaml_ng::from_str("offset: 3").unwrap();
+        let mut limit: LimitTransformConfig = serde_yaml_ng::from_str("limit: $n").unwrap();
+        offset.emplace(context.as_mapping().unwrap()).unwrap();
+        limit.emplace(context.as_mapping().unwrap()).unwrap();
+        assert!(offset.validate().is_empty());
+        assert!(limit.validate().is_empty());
+        let main = DummyData::df_instruments().lazy();
+        let main = offset.transform().run(main, ctx.clone()).unwrap();
+        let actual = limit.transform().run(main, ctx).unwrap().collect().unwrap();
+        let expected = df![
+            "ric" => ["NVDA", "NOVA"],
+            "mkt" => ["amer", "emea"],
+            "id" => [3, 4],
+        ]
+        .unwrap();
+        assert_eq!(actual, expected);
+    }
+
+    #[test]
+    fn valid_offset_transform_negative() {
+        let ctx = Arc::new(DefaultPipelineContext::new());
+        let offset: OffsetTransformConfig = serde_yaml_ng::from_str("offset: -1").unwrap();
+        let main = DummyData::df_instruments().lazy();
+        let actual = offset.transform().run(main, ctx).unwrap().collect().unwrap();
+        let expected = df![
+            "ric" => ["SPOT"],
+            "mkt" => ["emea"],
+            "id" => [6],
+        ]
+        .unwrap();
+        assert_eq!(actual, expected);
+    }
+
+    #[test]
+    fn invalid_limit_transform() {
+        let limit: LimitTransformConfig = serde_yaml_ng::from_str("limit: -3").unwrap();
+        assert_eq!(limit.validate().len(), 1);
+        let limit: LimitTransformConfig = serde_yaml_ng::from_str("limit: $n").unwrap();
+        assert_eq!(limit.validate().len(), 1);
+        assert!(serde_yaml_ng::from_str::<LimitTransformConfig>("limit: 1.5").is_err());
+        assert!(serde_yaml_ng::from_str::<OffsetTransformConfig>("offset: three").is_err());
+    }
+}
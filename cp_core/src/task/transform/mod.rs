@@ -1,10 +1,18 @@
 pub mod common;
 pub mod config;
+pub mod distinct;
 pub mod drop;
+pub mod filter;
+pub mod group_by;
 pub mod join;
+pub mod limit;
+pub mod pivot;
 pub mod select;
+pub mod sort;
 pub mod sql;
 pub mod time;
 pub mod uniform_id;
+pub mod union;
 pub mod unnest;
+pub mod window;
 pub mod with;
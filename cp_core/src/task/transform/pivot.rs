@@ -0,0 +1,327 @@
+use std::sync::Arc;
+
+use polars::prelude::{DataType, Expr, LazyFrame, Null, Selector, UnpivotArgsDSL, col, lit, when};
+
+use crate::{
+    model::common::ModelConfig,
+    model_emplace,
+    parser::keyword::{Keyword, StrKeyword},
+    pipeline::context::{DefaultPipelineContext, PipelineContext},
+    util::error::{CpError, CpResult},
+    valid_or_insert_error,
+};
+
+use super::{
+    common::{Transform, TransformConfig},
+    config::{AggFunc, PivotTransformConfig, UnpivotTransformConfig},
+    group_by::agg_expr,
+};
+
+/// Turns the distinct values of `on` into columns holding the aggregated `values` of each `index` group.
+/// Without declared columns, the distinct values are read from the input before building the plan.
+pub struct PivotTransform {
+    index: Vec<String>,
+    on: String,
+    values: String,
+    agg: AggFunc,
+    // output columns other than the index, with their model dtype
+    columns: Option<Vec<(String, DataType)>>,
+}
+
+impl PivotTransform {
+    fn distinct_on(&self, main: &LazyFrame) -> CpResult<Vec<String>> {
+        let frame = main
+            .clone()
+            .select([col(self.on.as_str())
+                .cast(DataType::String)
+                .unique_stable()
+                .drop_nulls()])
+            .collect()?;
+        Ok(frame
+            .column(&self.on)?
+            .str()?
+            .into_no_null_iter()
+            .map(|x| x.to_owned())
+            .collect())
+    }
+
+    fn column(&self, name: &str) -> Expr {
+        let mask = col(self.on.as_str()).cast(DataType::String).eq(lit(name));
+        let value = agg_expr(self.agg, col(self.values.as_str()).filter(mask.clone()));
+        // groups without the value are null rather than e.g. a sum of 0
+        when(mask.any(true)).then(value).otherwise(lit(Null {}))
+    }
+}
+
+impl Transform for PivotTransform {
+    fn run(&self, main: LazyFrame, _ctx: Arc<DefaultPipelineContext>) -> CpResult<LazyFrame> {
+        let columns = match &self.columns {
+            Some(columns) => columns
+                .iter()
+                .map(|(name, dtype)| self.column(name).cast(dtype.clone()).alias(name.as_str()))
+                .collect::<Vec<_>>(),
+            None => self
+                .distinct_on(&main)?
+                .iter()
+                .map(|name| self.column(name).alias(name.as_str()))
+                .collect(),
+        };
+        let index = self.index.iter().map(|x| col(x.as_str())).collect::<Vec<_>>();
+        Ok(main.group_by_stable(index).agg(columns))
+    }
+}
+
+pub struct UnpivotTransform {
+    args: UnpivotArgsDSL,
+}
+
+impl Transform for UnpivotTransform {
+    fn run(&self, main: LazyFrame, _ctx: Arc<DefaultPipelineContext>) -> CpResult<LazyFrame> {
+        Ok(main.unpivot(self.args.clone()))
+    }
+}
+
+impl TransformConfig for PivotTransformConfig {
+    fn emplace(&mut self, context: &serde_yaml_ng::Mapping) -> CpResult<()> {
+        for key in self.pivot.index.iter_mut() {
+            key.insert_value_from_context(context)?;
+        }
+        self.pivot.on.insert_value_from_context(context)?;
+        self.pivot.values.insert_value_from_context(context)
+    }
+
+    fn emplace_model(&mut self, ctx: &DefaultPipelineContext, context: &serde_yaml_ng::Mapping) -> CpResult<()> {
+        model_emplace!(self.pivot, ctx, context);
+        Ok(())
+    }
+
+    fn validate(&self) -> Vec<CpError> {
+        let mut errors = vec![];
+        if self.pivot.index.is_empty() {
+            errors.push(CpError::ConfigValidationError(
+                "transform[pivot].index",
+                "no index columns provided".to_owned(),
+            ));
+        }
+        for key in &self.pivot.index {
+            valid_or_insert_error!(errors, key, "transform[pivot].index");
+        }
+        valid_or_insert_error!(errors, self.pivot.on, "transform[pivot].on");
+        valid_or_insert_error!(errors, self.pivot.values, "transform[pivot].values");
+        if let Some(model_fields) = &self.pivot.model_fields {
+            for (key_kw, field_kw) in model_fields.iter() {
+                valid_or_insert_error!(errors, key_kw, "transform[pivot].model.key");
+                valid_or_insert_error!(errors, field_kw, "transform[pivot].model.field");
+            }
+        }
+        errors
+    }
+
+    fn transform(&self) -> Box<dyn Transform> {
+        let index = self
+            .pivot
+            .index
+            .iter()
+            .map(|x| x.value().expect("transform[pivot].index").to_owned())
+            .collect::<Vec<_>>();
+        let columns = self.pivot.model_fields.as_ref().map(|fields| {
+            ModelConfig {
+                label: "".to_owned(),
+                fields: fields.clone(),
+            }
+            .schema()
+            .expect("transform[pivot].model")
+            .iter()
+            .filter(|(name, _)| !index.iter().any(|x| x == name.as_str()))
+            .map(|(name, dtype)| (name.to_string(), dtype.clone()))
+            .collect()
+        });
+        Box::new(PivotTransform {
+            index,
+            on: self.pivot.on.value().expect("transform[pivot].on").to_owned(),
+            values: self.pivot.values.value().expect("transform[pivot].values").to_owned(),
+            agg: self.pivot.agg.unwrap_or(AggFunc::First),
+            columns,
+        })
+    }
+}
+
+fn selectors(keys: &[StrKeyword]) -> Vec<Selector> {
+    keys.iter()
+        .map(|x| x.value().expect("transform[unpivot].columns").as_str().into())
+        .collect()
+}
+
+impl TransformConfig for UnpivotTransformConfig {
+    fn emplace(&mut self, context: &serde_yaml_ng::Mapping) -> CpResult<()> {
+        for key in self
+            .unpivot
+            .index
+            .iter_mut()
+            .chain(self.unpivot.on.iter_mut().flatten())
+        {
+            key.insert_value_from_context(context)?;
+        }
+        for key in [&mut self.unpivot.variable_name, &mut self.unpivot.value_name]
+            .into_iter()
+            .flatten()
+        {
+            key.insert_value_from_context(context)?;
+        }
+        Ok(())
+    }
+
+    fn validate(&self) -> Vec<CpError> {
+        let mut errors = vec![];
+        for key in self.unpivot.index.iter().chain(self.unpivot.on.iter().flatten()) {
+            valid_or_insert_error!(errors, key, "transform[unpivot].columns");
+        }
+        for key in [&self.unpivot.variable_name, &self.unpivot.value_name]
+            .into_iter()
+            .flatten()
+        {
+            valid_or_insert_error!(errors, key, "transform[unpivot].name");
+        }
+        errors
+    }
+
+    fn transform(&self) -> Box<dyn Transform> {
+        Box::new(UnpivotTransform {
+            args: UnpivotArgsDSL {
+                on: selectors(self.unpivot.on.as_deref().unwrap_or_default()),
+                index: selectors(&self.unpivot.index),
+                variable_name: self
+                    .unpivot
+                    .variable_name
+                    .as_ref()
+                    .map(|x| x.value().expect("transform[unpivot].variable_name").into()),
+                value_name: self
+                    .unpivot
+                    .value_name
+                    .as_ref()
+                    .map(|x| x.value().expect("transform[unpivot].value_name").into()),
+            },
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Arc;
+
+    use polars::prelude::*;
+
+    use crate::{
+        context::model::ModelRegistry,
+        model::common::ModelConfig,
+        pipeline::context::DefaultPipelineContext,
+        task::transform::{
+            common::TransformConfig,
+            config::{PivotTransformConfig, UnpivotTransformConfig},
+        },
+        util::{
+            common::yaml_from_str,
+            test::{DummyData, assert_frame_equal},
+        },
+    };
+
+    #[test]
+    fn valid_pivot_transform() {
+        let config = "
+pivot:
+    index: [$key]
+    on: game
+    values: scores
+    agg: sum
+";
+        let mut config: PivotTransformConfig = serde_yaml_ng::from_str(config).unwrap();
+        let context = yaml_from_str("key: csid").unwrap();
+        config.emplace(context.as_mapping().unwrap()).unwrap();
+        assert!(config.validate().is_empty());
+        let ctx = Arc::new(DefaultPipelineContext::new());
+        let actual = config
+            .transform()
+            .run(DummyData::player_scores(), ctx)
+            .unwrap()
+            .collect()
+            .unwrap();
+        let expected = df![
+            "csid" => [82938842, 86543102, 8872631],
+            "1" => [20, 43, 19],
+            "2" => [Some(3), Some(50), None],
+            "3" => [Some(-7), Some(12), None],
+        ]
+        .unwrap();
+        assert_frame_equal(actual, expected);
+    }
+
+    #[test]
+    fn valid_pivot_transform_model() {
+        let mut model_registry = ModelRegistry::new();
+        model_registry.insert(ModelConfig {
+            label: "by_game".to_owned(),
+            fields: serde_yaml_ng::from_str("{ csid: int32, '1': double, '3': double, '4': double }").unwrap(),
+        });
+        let ctx = Arc::new(DefaultPipelineContext::new().with_model_registry(model_registry));
+        let context = yaml_from_str("model: by_game").unwrap();
+        let mut config: PivotTransformConfig =
+            serde_yaml_ng::from_str("pivot: { index: [csid], on: game, values: scores, model: $model }").unwrap();
+        config.emplace(context.as_mapping().unwrap()).unwrap();
+        config.emplace_model(&ctx, context.as_mapping().unwrap()).unwrap();
+        assert!(config.validate().is_empty());
+        let mut actual = config.transform().run(DummyData::player_scores(), ctx).unwrap();
+        // the schema is known without running the plan
+        assert_eq!(
+            actual.collect_schema().unwrap().iter_names().collect::<Vec<_>>(),
+            vec!["csid", "1", "3", "4"]
+        );
+        let expected = df![
+            "csid" => [82938842, 86543102, 8872631],
+            "1" => [20.0, 43.0, 19.0],
+            "3" => [Some(-7.0), Some(12.0), None],
+            "4" => [None::<f64>, None, None],
+        ]
+        .unwrap();
+        assert_frame_equal(actual.collect().unwrap(), expected);
+    }
+
+    #[test]
+    fn valid_unpivot_transform() {
+        let config = "
+unpivot:
+    index: [$key]
+    on: [game, scores]
+    variable_name: field
+    value_name: $value
+";
+        let mut config: UnpivotTransformConfig = serde_yaml_ng::from_str(config).unwrap();
+        let context = yaml_from_str("key: csid\nvalue: value").unwrap();
+        config.emplace(context.as_mapping().unwrap()).unwrap();
+        assert!(config.validate().is_empty());
+        let ctx = Arc::new(DefaultPipelineContext::new());
+        let actual = config
+            .transform()
+            .run(DummyData::player_scores().limit(2), ctx)
+            .unwrap()
+            .collect()
+            .unwrap();
+        let expected = df![
+            "csid" => [82938842, 82938842, 82938842, 82938842],
+            "field" => ["game", "game", "scores", "scores"],
+            "value" => [1, 2, 20, 3],
+        ]
+        .unwrap();
+        assert_frame_equal(actual, expected);
+    }
+
+    #[test]
+    fn invalid_pivot_transform() {
+        let config: PivotTransformConfig =
+            serde_yaml_ng::from_str("pivot: { index: [], on: $on, values: scores }").unwrap();
+        assert_eq!(config.validate().len(), 2);
+        assert!(
+            serde_yaml_ng::from_str::<PivotTransformConfig>("pivot: { index: [a], on: b, values: c, agg: median }")
+                .is_err()
+        );
+    }
+}
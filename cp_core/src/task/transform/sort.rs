@@ -0,0 +1,123 @@
+use crate::parser::keyword::Keyword;
+use crate::pipeline::context::DefaultPipelineContext;
+use crate::task::transform::common::{Transform, TransformConfig};
+use crate::task::transform::config::{NullsOrder, SortOrder, SortTransformConfig};
+use crate::util::error::{CpError, CpResult};
+use crate::valid_or_insert_error;
+use polars::prelude::{LazyFrame, SortMultipleOptions};
+use serde_yaml_ng::Mapping;
+use std::sync::Arc;
+
+pub struct SortTransform {
+    by: Vec<String>,
+    descending: Vec<bool>,
+    nulls_last: Vec<bool>,
+}
+
+impl Transform for SortTransform {
+    fn run(&self, main: LazyFrame, _ctx: Arc<DefaultPipelineContext>) -> CpResult<LazyFrame> {
+        let options = SortMultipleOptions::default()
+            .with_order_descending_multi(self.descending.clone())
+            .with_nulls_last_multi(self.nulls_last.clone())
+            .with_maintain_order(true);
+        Ok(main.sort(self.by.clone(), options))
+    }
+}
+
+impl TransformConfig for SortTransformConfig {
+    fn emplace(&mut self, context: &Mapping) -> CpResult<()> {
+        for key in self.sort.iter_mut() {
+            key.col.insert_value_from_context(context)?;
+        }
+        Ok(())
+    }
+
+    fn validate(&self) -> Vec<CpError> {
+        let mut errors = vec![];
+        if self.sort.is_empty() {
+            errors.push(CpError::ConfigValidationError(
+                "transform[sort]",
+                "no sort keys provided".to_owned(),
+            ));
+        }
+        for key in &self.sort {
+            valid_or_insert_error!(errors, key.col, "transform[sort].col");
+        }
+        errors
+    }
+
+    fn transform(&self) -> Box<dyn Transform> {
+        Box::new(SortTransform {
+            by: self
+                .sort
+                .iter()
+                .map(|key| key.col.value().expect("transform[sort].col").to_owned())
+                .collect(),
+            descending: self.sort.iter().map(|key| key.order == Some(SortOrder::Desc)).collect(),
+            nulls_last: self
+                .sort
+                .iter()
+                .map(|key| key.nulls == Some(NullsOrder::Last))
+                .collect(),
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::parser::keyword::{Keyword, StrKeyword};
+    use crate::pipeline::context::DefaultPipelineContext;
+    use crate::task::transform::common::TransformConfig;
+    use crate::task::transform::config::{NullsOrder, SortKey, SortOrder, SortTransformConfig};
+    use crate::util::common::yaml_from_str;
+    use polars::df;
+    use polars::prelude::IntoLazy;
+    use std::sync::Arc;
+
+    #[test]
+    fn valid_sort_transform_config() {
+        let config: SortTransformConfig =
+            serde_yaml_ng::from_str("sort: [mkt, { col: $price, order: desc, nulls: last }]").unwrap();
+        assert_eq!(
+            config,
+            SortTransformConfig {
+                sort: vec![
+                    SortKey {
+                        col: StrKeyword::with_value("mkt".to_owned()),
+                        order: None,
+                        nulls: None,
+                    },
+                    SortKey {
+                        col: StrKeyword::with_symbol("price"),
+                        order: Some(SortOrder::Desc),
+                        nulls: Some(NullsOrder::Last),
+                    },
+                ]
+            }
+        );
+        assert_eq!(config.validate().len(), 1);
+    }
+
+    #[test]
+    fn valid_sort_transform_multi_key() {
+        let mut config: SortTransformConfig =
+            serde_yaml_ng::from_str("sort: [mkt, { col: $price, order: desc, nulls: last }]").unwrap();
+        let context = yaml_from_str("price: px").unwrap();
+        config.emplace(context.as_mapping().unwrap()).unwrap();
+        assert!(config.validate().is_empty());
+        let main = df![
+            "mkt" => ["emea", "amer", "emea", "amer", "amer"],
+            "px" => [Some(1.0), Some(2.0), Some(3.0), None, Some(5.0)],
+        ]
+        .unwrap()
+        .lazy();
+        let ctx = Arc::new(DefaultPipelineContext::new());
+        let actual = config.transform().run(main, ctx).unwrap().collect().unwrap();
+        let expected = df![
+            "mkt" => ["amer", "amer", "amer", "emea", "emea"],
+            "px" => [Some(5.0), Some(2.0), None, Some(3.0), Some(1.0)],
+        ]
+        .unwrap();
+        assert_eq!(actual, expected);
+    }
+}
@@ -1,3 +1,4 @@
+use crate::parser::template::render_sql_template;
 use crate::pipeline::context::{DefaultPipelineContext, PipelineContext};
 use crate::task::transform::common::{Transform, TransformConfig};
 use crate::task::transform::config::SqlTransformConfig;
@@ -31,7 +32,8 @@ impl Transform for SqlTransform {
 }
 
 impl TransformConfig for SqlTransformConfig {
-    fn emplace(&mut self, _context: &Mapping) -> CpResult<()> {
+    fn emplace(&mut self, context: &Mapping) -> CpResult<()> {
+        self.sql = render_sql_template(&self.sql, context)?;
         Ok(())
     }
     fn validate(&self) -> Vec<CpError> {
@@ -58,7 +60,8 @@ mod tests {
     use crate::pipeline::context::{DefaultPipelineContext, PipelineContext};
     use crate::task::transform::common::TransformConfig;
     use crate::task::transform::config::SqlTransformConfig;
-    use crate::util::test::assert_frame_equal;
+    use crate::util::common::yaml_from_str;
+    use crate::util::test::{DummyData, assert_frame_equal};
     use polars::df;
     use polars::prelude::IntoLazy;
     use std::sync::Arc;
@@ -100,6 +103,31 @@ mod tests {
         assert_frame_equal(actual.collect().unwrap(), expected.unwrap());
     }
 
+    #[test]
+    fn valid_sql_transform_template() {
+        let mut config = SqlTransformConfig {
+            sql: "select * from self where mkt = {{ $mkt }} and id < {{ $max_id }}".to_owned(),
+            sql_context: None,
+        };
+        let context = yaml_from_str("mkt: emea\nmax_id: 5").unwrap();
+        config.emplace(context.as_mapping().unwrap()).unwrap();
+        assert!(config.validate().is_empty());
+        let ctx = Arc::new(DefaultPipelineContext::new());
+        let actual = config
+            .transform()
+            .run(DummyData::df_instruments().lazy(), ctx)
+            .unwrap()
+            .collect()
+            .unwrap();
+        let expected = df!(
+            "ric" => ["NOVA"],
+            "mkt" => ["emea"],
+            "id" => [4],
+        )
+        .unwrap();
+        assert_frame_equal(actual, expected);
+    }
+
     #[test]
     fn invalid_sql_transform_basic() {
         let config = SqlTransformConfig {
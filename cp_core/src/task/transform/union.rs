@@ -0,0 +1,282 @@
+use std::sync::Arc;
+
+use polars::prelude::{DataType, Expr, IntoLazy, LazyFrame, Null, Schema, UnionArgs, col, concat, lit};
+
+use crate::{
+    parser::keyword::Keyword,
+    pipeline::context::{DefaultPipelineContext, PipelineContext},
+    util::error::{CpError, CpResult},
+    valid_or_insert_error,
+};
+
+use super::{
+    common::{Transform, TransformConfig},
+    config::UnionTransformConfig,
+};
+
+/// Appends other results below the main frame, aligning their columns on the main frame.
+pub struct UnionTransform {
+    input: String,
+    frames: Vec<String>,
+    by_name: bool,
+    fill_missing: bool,
+    supercast: bool,
+    source_column: Option<String>,
+}
+
+impl UnionTransform {
+    fn columns(&self, label: &str, schema: &Schema, names: &[(String, DataType)]) -> CpResult<Vec<Expr>> {
+        if !self.by_name {
+            if schema.len() != names.len() {
+                return Err(CpError::TaskError(
+                    "Union column mismatch",
+                    format!("`{}` has {} columns, expected {}", label, schema.len(), names.len()),
+                ));
+            }
+            return Ok(schema
+                .iter_names()
+                .zip(names)
+                .map(|(old, (new, _))| col(old.as_str()).alias(new.as_str()))
+                .collect());
+        }
+        let missing = names
+            .iter()
+            .filter(|(name, _)| !schema.contains(name))
+            .map(|(name, _)| name.as_str())
+            .collect::<Vec<_>>();
+        let extra = schema
+            .iter_names()
+            .filter(|name| !names.iter().any(|(x, _)| x == name.as_str()))
+            .map(|name| name.as_str())
+            .collect::<Vec<_>>();
+        if !extra.is_empty() || (!missing.is_empty() && !self.fill_missing) {
+            return Err(CpError::TaskError(
+                "Union column mismatch",
+                format!(
+                    "`{}` columns differ from the union: missing [{}], extra [{}]",
+                    label,
+                    missing.join(", "),
+                    extra.join(", ")
+                ),
+            ));
+        }
+        Ok(names
+            .iter()
+            .map(|(name, dtype)| match schema.contains(name) {
+                true => col(name.as_str()),
+                false => lit(Null {}).cast(dtype.clone()).alias(name.as_str()),
+            })
+            .collect())
+    }
+}
+
+impl Transform for UnionTransform {
+    fn run(&self, main: LazyFrame, ctx: Arc<DefaultPipelineContext>) -> CpResult<LazyFrame> {
+        let mut inputs = vec![(self.input.clone(), main)];
+        for label in &self.frames {
+            let frame = ctx.extract_clone_result(label).map_err(|e| {
+                CpError::TaskError(
+                    "Union frame not found",
+                    format!("failed to extract union frame `{}`: {}", label, e),
+                )
+            })?;
+            // stages that fail produce frames without columns
+            if frame.width() == 0 {
+                return Err(CpError::TaskError(
+                    "Union frame not found",
+                    format!("union frame `{}` has no columns, did its stage fail?", label),
+                ));
+            }
+            inputs.push((label.clone(), frame.lazy()));
+        }
+        let mut schemas = vec![];
+        for (_, frame) in inputs.iter_mut() {
+            schemas.push(frame.collect_schema()?);
+        }
+        // output columns in order of first appearance, with the dtype they first appear with
+        let mut names: Vec<(String, DataType)> = schemas[0]
+            .iter()
+            .map(|(name, dtype)| (name.to_string(), dtype.clone()))
+            .collect();
+        if self.by_name && self.fill_missing {
+            for schema in &schemas[1..] {
+                for (name, dtype) in schema.iter() {
+                    if !names.iter().any(|(x, _)| x == name.as_str()) {
+                        names.push((name.to_string(), dtype.clone()));
+                    }
+                }
+            }
+        }
+        let mut frames = vec![];
+        for ((label, frame), schema) in inputs.into_iter().zip(schemas) {
+            let mut columns = self.columns(&label, &schema, &names)?;
+            if let Some(source_column) = &self.source_column {
+                columns.push(lit(label).alias(source_column.as_str()));
+            }
+            frames.push(frame.select(columns));
+        }
+        let args = UnionArgs {
+            to_supertypes: self.supercast,
+            ..Default::default()
+        };
+        Ok(concat(frames, args)?)
+    }
+}
+
+impl TransformConfig for UnionTransformConfig {
+    fn emplace(&mut self, context: &serde_yaml_ng::Mapping) -> CpResult<()> {
+        for frame in self.union.frames.iter_mut() {
+            frame.insert_value_from_context(context)?;
+        }
+        if let Some(source_column) = self.union.source_column.as_mut() {
+            source_column.insert_value_from_context(context)?;
+        }
+        Ok(())
+    }
+
+    fn emplace_input(&mut self, input: &str) {
+        let _ = self.union.input.insert(input.to_owned());
+    }
+
+    fn validate(&self) -> Vec<CpError> {
+        let mut errors = vec![];
+        if self.union.frames.is_empty() {
+            errors.push(CpError::ConfigValidationError(
+                "transform[union].frames",
+                "no frames provided".to_owned(),
+            ));
+        }
+        for frame in &self.union.frames {
+            valid_or_insert_error!(errors, frame, "transform[union].frames");
+        }
+        if let Some(source_column) = &self.union.source_column {
+            valid_or_insert_error!(errors, source_column, "transform[union].source_column");
+            if self.union.input.is_none() {
+                errors.push(CpError::ConfigValidationError(
+                    "transform[union].source_column",
+                    "the input of the root transform is needed to tag its rows".to_owned(),
+                ));
+            }
+        }
+        if self.union.fill_missing.unwrap_or(false) && !self.union.by_name.unwrap_or(true) {
+            errors.push(CpError::ConfigValidationError(
+                "transform[union].fill_missing",
+                "filling missing columns requires `by_name`".to_owned(),
+            ));
+        }
+        errors
+    }
+
+    fn transform(&self) -> Box<dyn Transform> {
+        Box::new(UnionTransform {
+            input: self.union.input.clone().unwrap_or_default(),
+            frames: self
+                .union
+                .frames
+                .iter()
+                .map(|x| x.value().expect("transform[union].frames").to_owned())
+                .collect(),
+            by_name: self.union.by_name.unwrap_or(true),
+            fill_missing: self.union.fill_missing.unwrap_or(false),
+            supercast: self.union.supercast.unwrap_or(false),
+            source_column: self
+                .union
+                .source_column
+                .as_ref()
+                .map(|x| x.value().expect("transform[union].source_column").to_owned()),
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Arc;
+
+    use polars::prelude::*;
+
+    use crate::{
+        pipeline::context::{DefaultPipelineContext, PipelineContext},
+        task::transform::{common::TransformConfig, config::UnionTransformConfig},
+        util::{common::yaml_from_str, test::assert_frame_equal},
+    };
+
+    fn ctx() -> Arc<DefaultPipelineContext> {
+        let ctx = Arc::new(DefaultPipelineContext::with_results(&["OTHER", "EMPTY", "FAILED"], 1));
+        let other = df!( "b" => ["y"], "a" => [2i64], "c" => [true] ).unwrap().lazy();
+        ctx.insert_result("OTHER", other).unwrap();
+        let empty = df!( "a" => Vec::<i64>::new(), "b" => Vec::<&str>::new() )
+            .unwrap()
+            .lazy();
+        ctx.insert_result("EMPTY", empty).unwrap();
+        ctx.insert_result("FAILED", DataFrame::empty().lazy()).unwrap();
+        ctx
+    }
+
+    fn main() -> LazyFrame {
+        df!( "a" => [1i64], "b" => ["x"] ).unwrap().lazy()
+    }
+
+    fn run(config: &str) -> PolarsResult<DataFrame> {
+        let mut config: UnionTransformConfig = serde_yaml_ng::from_str(config).unwrap();
+        let context = yaml_from_str("frame: OTHER\nsource: origin").unwrap();
+        config.emplace(context.as_mapping().unwrap()).unwrap();
+        config.emplace_input("MAIN");
+        assert!(config.validate().is_empty());
+        match config.transform().run(main(), ctx()) {
+            Ok(x) => x.collect(),
+            Err(e) => polars_bail!(ComputeError: "{}", e),
+        }
+    }
+
+    #[test]
+    fn valid_union_transform_fill_missing() {
+        let actual = run("union: { frames: [$frame, EMPTY], fill_missing: true, source_column: $source }").unwrap();
+        let expected = df![
+            "a" => [1i64, 2],
+            "b" => ["x", "y"],
+            "c" => [None, Some(true)],
+            "origin" => ["MAIN", "OTHER"],
+        ]
+        .unwrap();
+        assert_frame_equal(actual, expected);
+        // without filling, the extra column of OTHER is an error
+        assert!(run("union: { frames: [$frame] }").is_err());
+    }
+
+    #[test]
+    fn invalid_union_transform_frames() {
+        for config in ["union: { frames: [MISSING] }", "union: { frames: [$frame, FAILED] }"] {
+            let err = run(config).unwrap_err().to_string();
+            assert!(err.contains("MISSING") || err.contains("FAILED"), "{}", err);
+        }
+    }
+
+    #[test]
+    fn valid_union_transform_positional_supercast() {
+        let ctx = Arc::new(DefaultPipelineContext::with_results(&["OTHER"], 1));
+        let other = df!( "x" => [2.5], "y" => ["y"] ).unwrap().lazy();
+        ctx.insert_result("OTHER", other).unwrap();
+        let config: UnionTransformConfig =
+            serde_yaml_ng::from_str("union: { frames: [OTHER], by_name: false, supercast: true }").unwrap();
+        assert!(config.validate().is_empty());
+        let actual = config.transform().run(main(), ctx.clone()).unwrap().collect().unwrap();
+        let expected = df![
+            "a" => [1.0, 2.5],
+            "b" => ["x", "y"],
+        ]
+        .unwrap();
+        assert_frame_equal(actual, expected);
+        let config: UnionTransformConfig =
+            serde_yaml_ng::from_str("union: { frames: [OTHER], by_name: false }").unwrap();
+        assert!(config.transform().run(main(), ctx).unwrap().collect().is_err());
+    }
+
+    #[test]
+    fn invalid_union_transform() {
+        let config: UnionTransformConfig =
+            serde_yaml_ng::from_str("union: { frames: [], by_name: false, fill_missing: true, source_column: $s }")
+                .unwrap();
+        assert_eq!(config.validate().len(), 4);
+        assert!(serde_yaml_ng::from_str::<UnionTransformConfig>("union: { frames: [a], how: diagonal }").is_err());
+    }
+}
@@ -0,0 +1,388 @@
+use std::{collections::HashMap, sync::Arc};
+
+use polars::{
+    prelude::{
+        ClosedWindow, Duration, Expr, IDX_DTYPE, LazyFrame, RankMethod, RankOptions, RollingOptionsDynamicWindow,
+        RollingOptionsFixedWindow, SortMultipleOptions, col, int_range, len, lit,
+    },
+    series::ops::NullBehavior,
+};
+
+use crate::{
+    parser::keyword::Keyword,
+    pipeline::context::DefaultPipelineContext,
+    util::error::{CpError, CpResult},
+    valid_or_insert_error,
+};
+
+use super::{
+    common::{Transform, TransformConfig},
+    config::{NullsOrder, SortOrder, WindowArgs, WindowFunc, WindowSize, WindowTransformConfig},
+};
+
+/// Original row position, restored after computing the columns in `order_by` order
+const ROW_INDEX: &str = "__window_row";
+
+pub struct WindowTransform {
+    order_by: Vec<String>,
+    descending: Vec<bool>,
+    nulls_last: Vec<bool>,
+    columns: Vec<Expr>,
+}
+
+impl Transform for WindowTransform {
+    fn run(&self, main: LazyFrame, _ctx: Arc<DefaultPipelineContext>) -> CpResult<LazyFrame> {
+        if self.order_by.is_empty() {
+            return Ok(main.with_columns(&self.columns));
+        }
+        let options = SortMultipleOptions::default()
+            .with_order_descending_multi(self.descending.clone())
+            .with_nulls_last_multi(self.nulls_last.clone())
+            .with_maintain_order(true);
+        Ok(main
+            .with_row_index(ROW_INDEX, None)
+            .sort(self.order_by.clone(), options)
+            .with_columns(&self.columns)
+            .sort([ROW_INDEX], SortMultipleOptions::default())
+            .drop([ROW_INDEX]))
+    }
+}
+
+fn is_rolling(func: WindowFunc) -> bool {
+    matches!(
+        func,
+        WindowFunc::RollingMean | WindowFunc::RollingSum | WindowFunc::RollingMin | WindowFunc::RollingMax
+    )
+}
+
+fn rolling_expr(func: WindowFunc, expr: Expr, args: &WindowArgs, by: Option<&String>) -> Expr {
+    match args.window.as_ref().expect("transform[window].window") {
+        WindowSize::Rows(window_size) => {
+            let options = RollingOptionsFixedWindow {
+                window_size: *window_size,
+                min_periods: args.min_periods.unwrap_or(*window_size),
+                ..Default::default()
+            };
+            match func {
+                WindowFunc::RollingSum => expr.rolling_sum(options),
+                WindowFunc::RollingMin => expr.rolling_min(options),
+                WindowFunc::RollingMax => expr.rolling_max(options),
+                _ => expr.rolling_mean(options),
+            }
+        }
+        WindowSize::Duration(duration) => {
+            let options = RollingOptionsDynamicWindow {
+                window_size: Duration::parse(duration),
+                min_periods: args.min_periods.unwrap_or(1),
+                closed_window: ClosedWindow::Right,
+                fn_params: None,
+            };
+            let by = col(by.expect("transform[window].by").as_str());
+            match func {
+                WindowFunc::RollingSum => expr.rolling_sum_by(by, options),
+                WindowFunc::RollingMin => expr.rolling_min_by(by, options),
+                WindowFunc::RollingMax => expr.rolling_max_by(by, options),
+                _ => expr.rolling_mean_by(by, options),
+            }
+        }
+    }
+}
+
+fn window_expr(func: WindowFunc, args: &WindowArgs, by: Option<&String>) -> Expr {
+    let expr = args
+        .col
+        .as_ref()
+        .map(|x| x.value().expect("transform[window].col").clone());
+    if func == WindowFunc::RowNumber {
+        return int_range(lit(0), len(), 1, IDX_DTYPE) + lit(1);
+    }
+    let expr = expr.expect("transform[window].col");
+    match func {
+        WindowFunc::Rank | WindowFunc::DenseRank => expr.rank(
+            RankOptions {
+                method: match func {
+                    WindowFunc::Rank => RankMethod::Min,
+                    _ => RankMethod::Dense,
+                },
+                descending: args.descending.unwrap_or(false),
+            },
+            None,
+        ),
+        WindowFunc::CumSum => expr.cum_sum(false),
+        WindowFunc::CumCount => expr.cum_count(false),
+        WindowFunc::Shift => expr.shift(lit(args.n.unwrap_or(1))),
+        WindowFunc::Diff => expr.diff(args.n.unwrap_or(1), NullBehavior::Ignore),
+        _ => rolling_expr(func, expr, args, by),
+    }
+}
+
+impl WindowTransformConfig {
+    /// The temporal column of a duration window
+    fn rolling_by(&self, args: &WindowArgs) -> Option<String> {
+        args.by
+            .as_ref()
+            .or(self.window.order_by.as_ref().and_then(|x| x.first()).map(|x| &x.col))
+            .and_then(|x| x.value().cloned())
+    }
+}
+
+impl TransformConfig for WindowTransformConfig {
+    fn emplace(&mut self, context: &serde_yaml_ng::Mapping) -> CpResult<()> {
+        for key in self.window.partition_by.iter_mut().flatten() {
+            key.insert_value_from_context(context)?;
+        }
+        for key in self.window.order_by.iter_mut().flatten() {
+            key.col.insert_value_from_context(context)?;
+        }
+        let mut columns = vec![];
+        for (alias_kw, action) in &self.window.columns {
+            let mut alias = alias_kw.clone();
+            alias.insert_value_from_context(context)?;
+            let mut new_action = HashMap::new();
+            for (func, args) in action {
+                let mut args = args.clone();
+                if let Some(expr) = args.col.as_mut() {
+                    expr.insert_value_from_context(context)?;
+                }
+                if let Some(by) = args.by.as_mut() {
+                    by.insert_value_from_context(context)?;
+                }
+                new_action.insert(*func, args);
+            }
+            columns.push((alias, new_action));
+        }
+        self.window.columns = columns;
+        Ok(())
+    }
+
+    fn validate(&self) -> Vec<CpError> {
+        let mut errors = vec![];
+        if self.window.columns.is_empty() {
+            errors.push(CpError::ConfigValidationError(
+                "transform[window].columns",
+                "no window columns provided".to_owned(),
+            ));
+        }
+        for key in self.window.partition_by.iter().flatten() {
+            valid_or_insert_error!(errors, key, "transform[window].partition_by");
+        }
+        for key in self.window.order_by.iter().flatten() {
+            valid_or_insert_error!(errors, key.col, "transform[window].order_by");
+        }
+        for (alias_kw, action) in &self.window.columns {
+            valid_or_insert_error!(errors, alias_kw, "transform[window].columns");
+            let alias = alias_kw.value().map_or("?", |x| x.as_str());
+            if action.len() != 1 {
+                errors.push(CpError::ConfigValidationError(
+                    "transform[window].columns",
+                    format!(
+                        "`{}` must have exactly one window function, found: {:?}",
+                        alias,
+                        action.keys().collect::<Vec<_>>()
+                    ),
+                ));
+            }
+            for (func, args) in action {
+                match &args.col {
+                    Some(expr) => valid_or_insert_error!(errors, expr, "transform[window].columns.col"),
+                    None if *func != WindowFunc::RowNumber => errors.push(CpError::ConfigValidationError(
+                        "transform[window].columns.col",
+                        format!("`{}` has no column to compute {:?} over", alias, func),
+                    )),
+                    None => {}
+                }
+                if !is_rolling(*func) {
+                    continue;
+                }
+                match &args.window {
+                    None | Some(WindowSize::Rows(0)) => errors.push(CpError::ConfigValidationError(
+                        "transform[window].columns.window",
+                        format!("`{}` needs a window of at least one row or a duration", alias),
+                    )),
+                    Some(WindowSize::Rows(_)) => {}
+                    Some(WindowSize::Duration(duration)) => {
+                        if let Err(e) = Duration::try_parse(duration) {
+                            errors.push(CpError::ConfigValidationError(
+                                "transform[window].columns.window",
+                                format!("`{}` has an invalid duration `{}`: {}", alias, duration, e),
+                            ));
+                        }
+                        if let Some(by) = &args.by {
+                            valid_or_insert_error!(errors, by, "transform[window].columns.by");
+                        } else if self.rolling_by(args).is_none() {
+                            errors.push(CpError::ConfigValidationError(
+                                "transform[window].columns.by",
+                                format!("`{}` needs a temporal `by` column or `order_by`", alias),
+                            ));
+                        }
+                    }
+                }
+            }
+        }
+        errors
+    }
+
+    fn transform(&self) -> Box<dyn Transform> {
+        let partition_by = self
+            .window
+            .partition_by
+            .iter()
+            .flatten()
+            .map(|x| col(x.value().expect("transform[window].partition_by").as_str()))
+            .collect::<Vec<_>>();
+        let order_by = self.window.order_by.clone().unwrap_or_default();
+        let mut columns = vec![];
+        for (alias_kw, action) in &self.window.columns {
+            let alias = alias_kw.value().expect("alias").clone();
+            let (func, args) = action.iter().next().expect("window function");
+            let mut expr = window_expr(*func, args, self.rolling_by(args).as_ref());
+            if !partition_by.is_empty() {
+                expr = expr.over(&partition_by);
+            }
+            columns.push(expr.alias(alias));
+        }
+        Box::new(WindowTransform {
+            order_by: order_by
+                .iter()
+                .map(|key| key.col.value().expect("transform[window].order_by").to_owned())
+                .collect(),
+            descending: order_by.iter().map(|key| key.order == Some(SortOrder::Desc)).collect(),
+            nulls_last: order_by.iter().map(|key| key.nulls == Some(NullsOrder::Last)).collect(),
+            columns,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Arc;
+
+    use polars::prelude::*;
+
+    use crate::{
+        pipeline::context::DefaultPipelineContext,
+        task::transform::{common::TransformConfig, config::WindowTransformConfig},
+        util::{
+            common::yaml_from_str,
+            test::{DummyData, assert_frame_equal},
+        },
+    };
+
+    fn run(config: &str) -> DataFrame {
+        let config: WindowTransformConfig = serde_yaml_ng::from_str(config).unwrap();
+        assert!(config.validate().is_empty());
+        let ctx = Arc::new(DefaultPipelineContext::new());
+        config
+            .transform()
+            .run(DummyData::player_scores(), ctx)
+            .unwrap()
+            .collect()
+            .unwrap()
+    }
+
+    #[test]
+    fn valid_window_transform_running() {
+        let config = "
+window:
+    partition_by: [$key]
+    order_by: [game]
+    columns:
+        total: { cum_sum: $value }
+        games: { cum_count: scores }
+        game_number: { row_number: {} }
+        previous: { shift: scores }
+        change: { diff: scores }
+";
+        let mut config: WindowTransformConfig = serde_yaml_ng::from_str(config).unwrap();
+        let context = yaml_from_str("key: csid\nvalue: scores").unwrap();
+        config.emplace(context.as_mapping().unwrap()).unwrap();
+        assert!(config.validate().is_empty());
+        let ctx = Arc::new(DefaultPipelineContext::new());
+        let actual = config
+            .transform()
+            .run(DummyData::player_scores(), ctx)
+            .unwrap()
+            .drop(["csid", "game", "scores"])
+            .collect()
+            .unwrap();
+        // rows keep their input order: csid [a, a, b, a, b, b, c], game [1, 2, 1, 3, 2, 3, 1]
+        let expected = df![
+            "total" => [20, 23, 43, 16, 93, 105, 19],
+            "games" => [1u32, 2, 1, 3, 2, 3, 1],
+            "game_number" => [1 as IdxSize, 2, 1, 3, 2, 3, 1],
+            "previous" => [None, Some(20), None, Some(3), Some(43), Some(50), None],
+            "change" => [None, Some(-17), None, Some(-10), Some(7), Some(-38), None],
+        ]
+        .unwrap();
+        assert_frame_equal(actual, expected);
+    }
+
+    #[test]
+    fn valid_window_transform_column_order() {
+        let actual = run("
+window:
+    partition_by: [csid]
+    columns:
+        zeta: { row_number: {} }
+        alpha: { cum_sum: scores }
+        mid: { dense_rank: game }
+");
+        assert_eq!(
+            actual.get_column_names_str(),
+            ["csid", "game", "scores", "zeta", "alpha", "mid"]
+        );
+    }
+
+    #[test]
+    fn valid_window_transform_rank() {
+        let actual = run("
+window:
+    partition_by: [game]
+    columns:
+        rank: { rank: { col: scores, descending: true } }
+        dense: { dense_rank: csid }
+");
+        let expected = df![
+            "rank" => [2 as IdxSize, 2, 1, 2, 1, 1, 3],
+            "dense" => [2 as IdxSize, 1, 3, 1, 2, 2, 1],
+        ]
+        .unwrap();
+        assert_frame_equal(actual.select(["rank", "dense"]).unwrap(), expected);
+    }
+
+    #[test]
+    fn valid_window_transform_rolling() {
+        let actual = run("
+window:
+    partition_by: [csid]
+    order_by: [game]
+    columns:
+        avg: { rolling_mean: { col: scores, window: 2, min_periods: 1 } }
+        best: { rolling_max: { col: scores, window: 2 } }
+        total: { rolling_sum: { col: scores, window: 2i, by: game } }
+");
+        let expected = df![
+            "avg" => [20.0, 11.5, 43.0, -2.0, 46.5, 31.0, 19.0],
+            "best" => [None, Some(20), None, Some(3), Some(50), Some(50), None],
+            "total" => [20, 23, 43, -4, 93, 62, 19],
+        ]
+        .unwrap();
+        assert_frame_equal(actual.select(["avg", "best", "total"]).unwrap(), expected);
+    }
+
+    #[test]
+    fn invalid_window_transform() {
+        let config = "
+window:
+    columns:
+        a: { cum_sum: scores, rank: scores }
+        b: { rank: {} }
+        c: { rolling_mean: scores }
+        d: { rolling_sum: { col: scores, window: 2d } }
+        e: { rolling_min: { col: scores, window: 2x, by: game } }
+";
+        let config: WindowTransformConfig = serde_yaml_ng::from_str(config).unwrap();
+        assert_eq!(config.validate().len(), 5);
+        assert!(serde_yaml_ng::from_str::<WindowTransformConfig>("window: { columns: { a: { median: b } } }").is_err());
+    }
+}
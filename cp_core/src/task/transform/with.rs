@@ -89,6 +89,7 @@ mod tests {
         parser::keyword::{Keyword, PolarsExprKeyword, StrKeyword},
         pipeline::context::DefaultPipelineContext,
         task::transform::{common::TransformConfig, config::WithColTransformConfig},
+        util::error::CpError,
     };
 
     fn create_with_columns_good_config(map: HashMap<&'static str, Expr>) -> HashMap<StrKeyword, PolarsExprKeyword> {
@@ -152,4 +153,21 @@ mod tests {
         };
         assert_eq!(config.validate().len(), 2);
     }
+
+    #[test]
+    fn invalid_with_columns_transform_inline_symbols() {
+        let config = WithColTransformConfig {
+            with_columns: HashMap::from([(
+                StrKeyword::with_value("points".to_owned()),
+                serde_yaml_ng::from_str::<PolarsExprKeyword>("=goals + assists * $weight + $bonus").unwrap(),
+            )]),
+        };
+        let errors = config.validate();
+        assert_eq!(errors.len(), 1);
+        assert!(
+            matches!(&errors[0], CpError::SymbolMissingValueError("expr", symbols) if symbols == "weight, bonus"),
+            "{:?}",
+            errors
+        );
+    }
 }
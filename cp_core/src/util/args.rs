@@ -33,3 +33,53 @@ pub struct RunPipelineArgs {
 pub fn get_args() -> RunPipelineArgs {
     argh::from_env()
 }
+
+#[derive(FromArgs)]
+#[argh(description = "infer a model from a sample of a source, and print it as yaml")]
+pub struct InferModelArgs {
+    #[argh(
+        option,
+        short = 'c',
+        description = "root directory for configs, to sample a configured source"
+    )]
+    pub config: Option<String>,
+
+    #[argh(option, short = 's', description = "label of the configured source group to sample")]
+    pub source: Option<String>,
+
+    #[argh(option, description = "csv, json, parquet or bson file to sample")]
+    pub file: Option<String>,
+
+    #[argh(option, description = "http url to sample")]
+    pub url: Option<String>,
+
+    #[argh(option, description = "table to sample, with --db-url")]
+    pub table: Option<String>,
+
+    #[argh(option, description = "postgres, mysql or sqlite url of --table")]
+    pub db_url: Option<String>,
+
+    #[argh(
+        option,
+        short = 'a',
+        description = "yaml mapping substituted into the source config symbols"
+    )]
+    pub args: Option<String>,
+
+    #[argh(option, short = 'n', default = "1000", description = "number of rows to sample")]
+    pub rows: u32,
+
+    #[argh(
+        option,
+        short = 'l',
+        description = "model label, defaults to the name of the sampled frame"
+    )]
+    pub label: Option<String>,
+
+    #[argh(option, short = 'o', description = "file to write the model to, instead of stdout")]
+    pub output: Option<String>,
+}
+
+pub fn get_infer_model_args() -> InferModelArgs {
+    argh::from_env()
+}
@@ -0,0 +1,250 @@
+use std::{
+    io::{BufRead, Write},
+    str::FromStr,
+};
+
+use bson::{Binary, Bson, DateTime, Decimal128, Document, spec::BinarySubtype};
+use polars::{
+    frame::DataFrame,
+    prelude::{AnyValue, Column, DataType, Field, PlSmallStr, Schema, TimeUnit},
+    series::Series,
+};
+
+use super::error::CpResult;
+
+/// The column type a bson value is read into, before any casting to the model's dtype.
+/// ObjectIds are read as hex strings, DateTimes as millisecond datetimes (in UTC) and
+/// Decimal128s as decimal strings to avoid losing precision.
+pub fn bson_dtype(value: &Bson) -> DataType {
+    bson_to_any_value(value).dtype()
+}
+
+/// Converts a single bson value into a polars value, recursing into arrays and subdocuments.
+pub fn bson_to_any_value(value: &Bson) -> AnyValue<'static> {
+    match value {
+        Bson::Null | Bson::Undefined | Bson::MaxKey | Bson::MinKey => AnyValue::Null,
+        Bson::Boolean(x) => AnyValue::Boolean(*x),
+        Bson::Int32(x) => AnyValue::Int32(*x),
+        Bson::Int64(x) => AnyValue::Int64(*x),
+        Bson::Double(x) => AnyValue::Float64(*x),
+        Bson::String(x) | Bson::Symbol(x) | Bson::JavaScriptCode(x) => AnyValue::StringOwned(x.into()),
+        Bson::ObjectId(x) => AnyValue::StringOwned(x.to_hex().into()),
+        Bson::DateTime(x) => AnyValue::Datetime(x.timestamp_millis(), TimeUnit::Milliseconds, None),
+        Bson::Decimal128(x) => AnyValue::StringOwned(x.to_string().into()),
+        Bson::Timestamp(x) => AnyValue::Int64(x.time as i64),
+        Bson::Binary(x) => AnyValue::BinaryOwned(x.bytes.clone()),
+        Bson::RegularExpression(x) => AnyValue::StringOwned(x.pattern.as_str().into()),
+        Bson::Array(xs) => {
+            let values = xs.iter().map(bson_to_any_value).collect::<Vec<_>>();
+            match Series::from_any_values(PlSmallStr::EMPTY, &values, false) {
+                Ok(series) => AnyValue::List(series),
+                // mixed arrays are kept as their string representation
+                Err(_) => AnyValue::StringOwned(value.to_string().into()),
+            }
+        }
+        Bson::Document(doc) => {
+            let values = doc.values().map(bson_to_any_value).collect::<Vec<_>>();
+            let fields = doc
+                .keys()
+                .zip(values.iter())
+                .map(|(key, value)| Field::new(key.into(), value.dtype()))
+                .collect::<Vec<_>>();
+            AnyValue::StructOwned(Box::new((values, fields)))
+        }
+        x => AnyValue::StringOwned(x.to_string().into()),
+    }
+}
+
+/// Builds a frame from bson documents, one row per document. With a schema, exactly the schema's
+/// columns are read (missing keys become nulls) and cast to their dtypes, otherwise every key
+/// found is read in order of first appearance.
+pub fn bson_to_df(documents: &[Document], schema: Option<&Schema>) -> CpResult<DataFrame> {
+    let names: Vec<String> = match schema {
+        Some(schema) => schema.iter_names().map(|x| x.to_string()).collect(),
+        None => {
+            let mut names: Vec<String> = vec![];
+            for doc in documents {
+                for key in doc.keys() {
+                    if !names.contains(key) {
+                        names.push(key.to_owned());
+                    }
+                }
+            }
+            names
+        }
+    };
+    let mut columns: Vec<Column> = Vec::with_capacity(names.len());
+    for name in names {
+        let values = documents
+            .iter()
+            .map(|doc| doc.get(&name).map_or(AnyValue::Null, bson_to_any_value))
+            .collect::<Vec<_>>();
+        let mut series = Series::from_any_values(name.as_str().into(), &values, false)?;
+        if let Some(dtype) = schema.and_then(|x| x.get(&name)) {
+            series = series.cast(dtype)?;
+        }
+        columns.push(series.into());
+    }
+    Ok(DataFrame::new(columns)?)
+}
+
+/// Converts a single polars value into a bson value, the inverse of [`bson_to_any_value`] where possible.
+/// Datetimes are truncated to milliseconds, decimals are written as Decimal128s and unsigned integers
+/// too large for an Int64 as doubles. ObjectIds read as hex strings are written back as strings.
+pub fn any_value_to_bson(value: AnyValue) -> Bson {
+    match value {
+        AnyValue::Null => Bson::Null,
+        AnyValue::Boolean(x) => Bson::Boolean(x),
+        AnyValue::Int8(x) => Bson::Int32(x as i32),
+        AnyValue::Int16(x) => Bson::Int32(x as i32),
+        AnyValue::Int32(x) => Bson::Int32(x),
+        AnyValue::Int64(x) => Bson::Int64(x),
+        AnyValue::UInt8(x) => Bson::Int32(x as i32),
+        AnyValue::UInt16(x) => Bson::Int32(x as i32),
+        AnyValue::UInt32(x) => Bson::Int64(x as i64),
+        AnyValue::UInt64(x) => i64::try_from(x).map_or(Bson::Double(x as f64), Bson::Int64),
+        AnyValue::Float32(x) => Bson::Double(x as f64),
+        AnyValue::Float64(x) => Bson::Double(x),
+        AnyValue::Binary(x) => Bson::Binary(Binary {
+            subtype: BinarySubtype::Generic,
+            bytes: x.to_vec(),
+        }),
+        AnyValue::BinaryOwned(x) => Bson::Binary(Binary {
+            subtype: BinarySubtype::Generic,
+            bytes: x,
+        }),
+        AnyValue::Date(days) => Bson::DateTime(DateTime::from_millis(days as i64 * 86_400_000)),
+        AnyValue::Datetime(x, unit, _) | AnyValue::DatetimeOwned(x, unit, _) => {
+            Bson::DateTime(DateTime::from_millis(match unit {
+                TimeUnit::Nanoseconds => x.div_euclid(1_000_000),
+                TimeUnit::Microseconds => x.div_euclid(1_000),
+                TimeUnit::Milliseconds => x,
+            }))
+        }
+        AnyValue::Decimal(..) => {
+            let repr = value.to_string();
+            Decimal128::from_str(&repr).map_or(Bson::String(repr), Bson::Decimal128)
+        }
+        AnyValue::List(series) => Bson::Array(series.iter().map(any_value_to_bson).collect()),
+        AnyValue::Struct(_, _, fields) => Bson::Document(
+            fields
+                .iter()
+                .zip(value._iter_struct_av())
+                .map(|(field, value)| (field.name().to_string(), any_value_to_bson(value)))
+                .collect(),
+        ),
+        AnyValue::StructOwned(payload) => {
+            let (values, fields) = *payload;
+            Bson::Document(
+                fields
+                    .into_iter()
+                    .zip(values)
+                    .map(|(field, value)| (field.name().to_string(), any_value_to_bson(value)))
+                    .collect(),
+            )
+        }
+        x => match x.get_str() {
+            Some(s) => Bson::String(s.to_owned()),
+            None => Bson::String(x.to_string()),
+        },
+    }
+}
+
+/// Converts every row of a frame into a bson document keyed by column name.
+pub fn df_to_bson(dataframe: &DataFrame) -> CpResult<Vec<Document>> {
+    let columns = dataframe.get_columns();
+    let mut documents = Vec::with_capacity(dataframe.height());
+    for idx in 0..dataframe.height() {
+        let mut doc = Document::new();
+        for column in columns {
+            doc.insert(column.name().as_str(), any_value_to_bson(column.get(idx)?));
+        }
+        documents.push(doc);
+    }
+    Ok(documents)
+}
+
+/// Reads concatenated bson documents (e.g. a `mongodump` collection file) until the end of the reader.
+pub fn read_bson_documents<R: BufRead>(mut reader: R) -> CpResult<Vec<Document>> {
+    let mut documents = vec![];
+    while !reader.fill_buf()?.is_empty() {
+        documents.push(Document::from_reader(&mut reader)?);
+    }
+    Ok(documents)
+}
+
+/// Writes bson documents back to back, the layout read by [`read_bson_documents`].
+pub fn write_bson_documents<W: Write>(mut writer: W, documents: &[Document]) -> CpResult<()> {
+    for doc in documents {
+        doc.to_writer(&mut writer)?;
+    }
+    writer.flush()?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use std::str::FromStr;
+
+    use bson::{Bson, DateTime, Decimal128, doc, oid::ObjectId};
+    use polars::prelude::{DataType, Schema, TimeUnit};
+
+    use super::{bson_dtype, bson_to_df};
+
+    #[test]
+    fn valid_bson_dtype() {
+        let oid = ObjectId::from_str("65f1a2b3c4d5e6f708091a2b").unwrap();
+        assert_eq!(bson_dtype(&Bson::ObjectId(oid)), DataType::String);
+        assert_eq!(
+            bson_dtype(&Bson::DateTime(DateTime::from_millis(0))),
+            DataType::Datetime(TimeUnit::Milliseconds, None)
+        );
+        assert_eq!(
+            bson_dtype(&Bson::Decimal128(Decimal128::from_str("1.25").unwrap())),
+            DataType::String
+        );
+        assert_eq!(
+            bson_dtype(&Bson::Array(vec![Bson::Int32(1), Bson::Int32(2)])),
+            DataType::List(Box::new(DataType::Int32))
+        );
+    }
+
+    #[test]
+    fn valid_bson_to_df_infer_and_schema() {
+        let oid = ObjectId::from_str("65f1a2b3c4d5e6f708091a2b").unwrap();
+        let docs = vec![
+            doc! { "_id": oid, "name": "Jake", "at": DateTime::from_millis(1_700_000_000_000i64),
+            "px": Decimal128::from_str("1.25").unwrap(), "info": { "team": "NJD", "num": 9 } },
+            doc! { "name": "Mitch", "extra": true },
+        ];
+        let actual = bson_to_df(&docs, None).unwrap();
+        assert_eq!(actual.shape(), (2, 6));
+        assert_eq!(
+            actual.get_column_names_str(),
+            vec!["_id", "name", "at", "px", "info", "extra"]
+        );
+        assert_eq!(
+            actual.column("_id").unwrap().str().unwrap().get(0),
+            Some("65f1a2b3c4d5e6f708091a2b")
+        );
+        assert!(matches!(actual.column("info").unwrap().dtype(), DataType::Struct(_)));
+
+        let schema = Schema::from_iter(vec![
+            ("name".into(), DataType::String),
+            (
+                "at".into(),
+                DataType::Datetime(TimeUnit::Microseconds, Some("UTC".into())),
+            ),
+            ("px".into(), DataType::Float64),
+            ("missing".into(), DataType::Int64),
+        ]);
+        let actual = bson_to_df(&docs, Some(&schema)).unwrap();
+        assert_eq!(actual.schema(), schema);
+        assert_eq!(actual.column("px").unwrap().f64().unwrap().get(0), Some(1.25));
+        assert_eq!(actual.column("missing").unwrap().null_count(), 2);
+        assert_eq!(
+            actual.column("at").unwrap().datetime().unwrap().get(0),
+            Some(1_700_000_000_000_000i64)
+        );
+    }
+}
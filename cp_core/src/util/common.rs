@@ -3,7 +3,7 @@ use log::{debug, trace};
 use polars::{
     frame::DataFrame,
     io::SerReader,
-    prelude::{JsonReader, Schema, SchemaNamesAndDtypes},
+    prelude::{JsonReader, Schema, SchemaNamesAndDtypes, TimeUnit},
 };
 use std::{collections::HashMap, io::Cursor};
 
@@ -71,6 +71,35 @@ pub fn get_utc_time_str_now() -> String {
     Utc::now().to_rfc3339_opts(chrono::SecondsFormat::Millis, false)
 }
 
+/// Current utc time usable in a table name, e.g. `20250101_093000_123`.
+pub fn get_utc_time_ident_now() -> String {
+    Utc::now().format("%Y%m%d_%H%M%S_%3f").to_string()
+}
+
+/// Iso-8601 date of a polars date, given in days since the epoch.
+pub fn date_to_iso_str(days: i32) -> Option<String> {
+    DateTime::from_timestamp(days as i64 * 86_400, 0).map(|x| x.format("%Y-%m-%d").to_string())
+}
+
+/// Iso-8601 utc datetime (without offset) of a polars datetime.
+pub fn datetime_to_iso_str(value: i64, unit: TimeUnit) -> Option<String> {
+    let datetime = match unit {
+        TimeUnit::Nanoseconds => Some(DateTime::from_timestamp_nanos(value)),
+        TimeUnit::Microseconds => DateTime::from_timestamp_micros(value),
+        TimeUnit::Milliseconds => DateTime::from_timestamp_millis(value),
+    };
+    datetime.map(|x| x.format("%Y-%m-%d %H:%M:%S%.f").to_string())
+}
+
+/// Iso-8601 time of a polars time, given in nanoseconds since midnight.
+pub fn time_to_iso_str(ns: i64) -> Option<String> {
+    chrono::NaiveTime::from_num_seconds_from_midnight_opt(
+        ns.div_euclid(1_000_000_000) as u32,
+        ns.rem_euclid(1_000_000_000) as u32,
+    )
+    .map(|x| x.format("%H:%M:%S%.f").to_string())
+}
+
 pub fn get_full_path(abs_or_rel_path_str: &str, is_config: bool) -> CpResult<std::path::PathBuf> {
     let path = std::path::Path::new(abs_or_rel_path_str);
     if path.is_absolute() {
@@ -43,6 +43,42 @@ impl From<reqwest::Error> for CpError {
     }
 }
 
+impl From<mongodb::error::Error> for CpError {
+    fn from(value: mongodb::error::Error) -> Self {
+        Self::ConnectionError(value.to_string())
+    }
+}
+
+impl From<rusqlite::Error> for CpError {
+    fn from(value: rusqlite::Error) -> Self {
+        Self::ConnectionError(value.to_string())
+    }
+}
+
+impl From<postgres::Error> for CpError {
+    fn from(value: postgres::Error) -> Self {
+        Self::ConnectionError(value.to_string())
+    }
+}
+
+impl From<mysql::Error> for CpError {
+    fn from(value: mysql::Error) -> Self {
+        Self::ConnectionError(value.to_string())
+    }
+}
+
+impl From<bson::de::Error> for CpError {
+    fn from(value: bson::de::Error) -> Self {
+        Self::TaskError("BsonDeserializer", value.to_string())
+    }
+}
+
+impl From<bson::ser::Error> for CpError {
+    fn from(value: bson::ser::Error) -> Self {
+        Self::TaskError("BsonSerializer", value.to_string())
+    }
+}
+
 impl From<polars::error::PolarsError> for CpError {
     fn from(value: polars::error::PolarsError) -> Self {
         Self::PolarsError(value)
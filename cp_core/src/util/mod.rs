@@ -1,4 +1,5 @@
 pub mod args;
+pub mod bson;
 pub mod common;
 pub mod error;
 pub mod test;
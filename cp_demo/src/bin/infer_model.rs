@@ -0,0 +1,21 @@
+use cp_core::{
+    model::infer::infer_models_from_args,
+    util::{args::get_infer_model_args, error::CpResult},
+};
+
+fn exec() -> CpResult<()> {
+    let args = get_infer_model_args();
+    let yaml = infer_models_from_args(&args)?;
+    match &args.output {
+        Some(path) => std::fs::write(path, yaml)?,
+        None => print!("{}", yaml),
+    }
+    Ok(())
+}
+
+fn main() {
+    if let Err(e) = exec() {
+        eprintln!("Failed to infer model: {}", e);
+        std::process::exit(1);
+    }
+}